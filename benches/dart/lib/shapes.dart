@@ -0,0 +1,16 @@
+/// A small layout library: geometry, box constraints, a render tree with
+/// flex and stack layout, and a widget tree on top of it.
+library shapes;
+
+export 'src/foundation/diagnostics.dart';
+export 'src/foundation/observable.dart';
+export 'src/geometry/offset.dart';
+export 'src/geometry/rect.dart';
+export 'src/geometry/size.dart';
+export 'src/layout/constraints.dart';
+export 'src/layout/flex.dart';
+export 'src/layout/node.dart';
+export 'src/layout/stack.dart';
+export 'src/tree/widgets.dart';
+export 'src/util/collections.dart';
+export 'src/util/task_queue.dart';
@@ -0,0 +1,129 @@
+import 'dart:math' as math;
+
+/// How much detail a [DiagnosticsNode] shows.
+enum DiagnosticLevel { hidden, fine, debug, info, warning, error }
+
+/// A named property of an object, for debugging output.
+class DiagnosticsProperty<T> {
+  DiagnosticsProperty(
+    this.name,
+    this.value, {
+    this.defaultValue,
+    this.level: DiagnosticLevel.info,
+    this.showName: true,
+    this.unit,
+  });
+
+  final String name;
+  final T value;
+  final T defaultValue;
+  final DiagnosticLevel level;
+  final bool showName;
+  final String unit;
+
+  /// Whether the property has its default value and can be left out.
+  bool get isDefault => value == defaultValue;
+
+  String valueToString() {
+    if (value is double) {
+      final double number = value as double;
+      final String text = number.truncateToDouble() == number
+          ? number.toStringAsFixed(1)
+          : number.toStringAsFixed(math.min(3, _decimals(number)));
+      return unit == null ? text : '$text$unit';
+    }
+    if (value is Iterable) {
+      final Iterable<Object> values = value as Iterable<Object>;
+      return values.isEmpty ? '[]' : values.map((Object v) => '$v').join(', ');
+    }
+    return unit == null ? '$value' : '$value$unit';
+  }
+
+  static int _decimals(double number) {
+    int decimals = 0;
+    double scaled = number;
+    while (scaled.truncateToDouble() != scaled && decimals < 6) {
+      scaled *= 10.0;
+      decimals += 1;
+    }
+    return decimals;
+  }
+
+  @override
+  String toString() => showName ? '$name: ${valueToString()}' : valueToString();
+}
+
+/// Collects the [DiagnosticsProperty]s of an object.
+class DiagnosticPropertiesBuilder {
+  final List<DiagnosticsProperty<Object>> properties = <DiagnosticsProperty<Object>>[];
+
+  void add(DiagnosticsProperty<Object> property) {
+    properties.add(property);
+  }
+
+  void addFlag(String name, bool value, {String ifTrue, String ifFalse}) {
+    final String text = value ? ifTrue : ifFalse;
+    if (text != null) {
+      add(new DiagnosticsProperty<Object>(name, text, showName: false));
+    }
+  }
+
+  Iterable<DiagnosticsProperty<Object>> visible(DiagnosticLevel minLevel) {
+    return properties.where((DiagnosticsProperty<Object> property) {
+      return property.level.index >= minLevel.index && !property.isDefault;
+    });
+  }
+}
+
+/// An object that can describe itself for debugging.
+abstract class Diagnosticable {
+  const Diagnosticable();
+
+  /// Adds the properties of this object to [properties].
+  void debugFillProperties(DiagnosticPropertiesBuilder properties) {}
+
+  /// A one-line description of this object.
+  String toStringShort() => '$runtimeType';
+
+  @override
+  String toString({DiagnosticLevel minLevel: DiagnosticLevel.debug}) {
+    final DiagnosticPropertiesBuilder builder = new DiagnosticPropertiesBuilder();
+    debugFillProperties(builder);
+    final String properties = builder.visible(minLevel).join(', ');
+    return properties.isEmpty ? toStringShort() : '${toStringShort()}($properties)';
+  }
+}
+
+/// An object that has children, and can describe the whole tree below it.
+abstract class DiagnosticableTree extends Diagnosticable {
+  const DiagnosticableTree();
+
+  List<DiagnosticableTree> debugDescribeChildren() => const <DiagnosticableTree>[];
+
+  String toStringDeep({String prefix: '', DiagnosticLevel minLevel: DiagnosticLevel.debug}) {
+    final StringBuffer buffer = new StringBuffer();
+    _writeTree(buffer, prefix, '', minLevel);
+    return buffer.toString();
+  }
+
+  void _writeTree(
+    StringBuffer buffer,
+    String prefixLineOne,
+    String prefixOtherLines,
+    DiagnosticLevel minLevel,
+  ) {
+    buffer
+      ..write(prefixLineOne)
+      ..writeln(toString(minLevel: minLevel));
+    final List<DiagnosticableTree> children = debugDescribeChildren();
+    for (int i = 0; i < children.length; i += 1) {
+      final bool last = i == children.length - 1;
+      children[i]._writeTree(
+        buffer,
+        prefixOtherLines + (last ? '└─' : '├─'),
+        prefixOtherLines + (last ? '  ' : '│ '),
+        minLevel,
+      );
+    }
+  }
+}
@@ -0,0 +1,146 @@
+import 'dart:collection';
+
+/// Signature of callbacks that take no arguments and return no data.
+typedef void VoidCallback();
+
+/// Signature of callbacks that report a new value.
+typedef void ValueChanged<T>(T value);
+
+/// An object that maintains a list of listeners.
+abstract class Listenable {
+  const Listenable();
+
+  /// Returns a [Listenable] that notifies when any of [listenables] does.
+  factory Listenable.merge(List<Listenable> listenables) = _MergingListenable;
+
+  void addListener(VoidCallback listener);
+
+  void removeListener(VoidCallback listener);
+}
+
+/// A [Listenable] that can also report its current value.
+abstract class ValueListenable<T> extends Listenable {
+  const ValueListenable();
+
+  T get value;
+}
+
+/// Keeps a list of listeners and calls them when [notifyListeners] is called.
+class ChangeNotifier implements Listenable {
+  LinkedList<_ListenerEntry> _listeners = new LinkedList<_ListenerEntry>();
+  bool _disposed = false;
+
+  bool _debugAssertNotDisposed() {
+    assert(() {
+      if (_disposed) {
+        throw new StateError('A $runtimeType was used after being disposed.');
+      }
+      return true;
+    }());
+    return true;
+  }
+
+  /// Whether any listeners are currently registered.
+  bool get hasListeners {
+    assert(_debugAssertNotDisposed());
+    return _listeners.isNotEmpty;
+  }
+
+  @override
+  void addListener(VoidCallback listener) {
+    assert(_debugAssertNotDisposed());
+    _listeners.add(new _ListenerEntry(listener));
+  }
+
+  @override
+  void removeListener(VoidCallback listener) {
+    assert(_debugAssertNotDisposed());
+    for (final _ListenerEntry entry in _listeners) {
+      if (entry.listener == listener) {
+        entry.unlink();
+        return;
+      }
+    }
+  }
+
+  /// Discards any resources used by the object.
+  void dispose() {
+    assert(_debugAssertNotDisposed());
+    _listeners = null;
+    _disposed = true;
+  }
+
+  /// Calls all the registered listeners, in the order they were added.
+  ///
+  /// Listeners added or removed while notifying take effect the next time.
+  void notifyListeners() {
+    assert(_debugAssertNotDisposed());
+    if (_listeners.isEmpty) {
+      return;
+    }
+    final List<_ListenerEntry> localListeners = new List<_ListenerEntry>.from(_listeners);
+    for (final _ListenerEntry entry in localListeners) {
+      try {
+        if (entry.list != null) {
+          entry.listener();
+        }
+      } catch (exception, stack) {
+        _reportError(exception, stack);
+      }
+    }
+  }
+
+  void _reportError(Object exception, StackTrace stack) {
+    print('Exception while notifying $runtimeType listeners: $exception\n$stack');
+  }
+}
+
+class _ListenerEntry extends LinkedListEntry<_ListenerEntry> {
+  _ListenerEntry(this.listener);
+
+  final VoidCallback listener;
+}
+
+class _MergingListenable extends Listenable {
+  _MergingListenable(this._children);
+
+  final List<Listenable> _children;
+
+  @override
+  void addListener(VoidCallback listener) {
+    for (final Listenable child in _children) {
+      child?.addListener(listener);
+    }
+  }
+
+  @override
+  void removeListener(VoidCallback listener) {
+    for (final Listenable child in _children) {
+      child?.removeListener(listener);
+    }
+  }
+
+  @override
+  String toString() {
+    return 'Listenable.merge([${_children.join(", ")}])';
+  }
+}
+
+/// A [ChangeNotifier] that holds a single value.
+class ValueNotifier<T> extends ChangeNotifier implements ValueListenable<T> {
+  ValueNotifier(this._value);
+
+  @override
+  T get value => _value;
+  T _value;
+  set value(T newValue) {
+    if (_value == newValue) {
+      return;
+    }
+    _value = newValue;
+    notifyListeners();
+  }
+
+  @override
+  String toString() => '$runtimeType($value)';
+}
@@ -0,0 +1,95 @@
+import 'dart:math' as math;
+
+/// Base class for [Offset] and [Size], which are both pairs of doubles.
+abstract class OffsetBase {
+  const OffsetBase(this._dx, this._dy);
+
+  final double _dx;
+  final double _dy;
+
+  bool get isInfinite => _dx >= double.infinity || _dy >= double.infinity;
+
+  bool get isFinite => _dx.isFinite && _dy.isFinite;
+
+  bool operator <(OffsetBase other) => _dx < other._dx && _dy < other._dy;
+
+  bool operator <=(OffsetBase other) => _dx <= other._dx && _dy <= other._dy;
+
+  bool operator >(OffsetBase other) => _dx > other._dx && _dy > other._dy;
+
+  bool operator >=(OffsetBase other) => _dx >= other._dx && _dy >= other._dy;
+
+  @override
+  bool operator ==(dynamic other) {
+    if (other is! OffsetBase) {
+      return false;
+    }
+    final OffsetBase typedOther = other;
+    return _dx == typedOther._dx && _dy == typedOther._dy;
+  }
+
+  @override
+  int get hashCode => _dx.hashCode * 31 + _dy.hashCode;
+}
+
+/// An immutable 2D floating-point offset.
+class Offset extends OffsetBase {
+  const Offset(double dx, double dy) : super(dx, dy);
+
+  factory Offset.fromDirection(double direction, [double distance = 1.0]) {
+    return new Offset(distance * math.cos(direction), distance * math.sin(direction));
+  }
+
+  double get dx => _dx;
+
+  double get dy => _dy;
+
+  double get distance => math.sqrt(dx * dx + dy * dy);
+
+  double get distanceSquared => dx * dx + dy * dy;
+
+  double get direction => math.atan2(dy, dx);
+
+  static const Offset zero = const Offset(0.0, 0.0);
+
+  static const Offset infinite = const Offset(double.infinity, double.infinity);
+
+  Offset scale(double scaleX, double scaleY) => new Offset(dx * scaleX, dy * scaleY);
+
+  Offset translate(double translateX, double translateY) {
+    return new Offset(dx + translateX, dy + translateY);
+  }
+
+  Offset operator -() => new Offset(-dx, -dy);
+
+  Offset operator -(Offset other) => new Offset(dx - other.dx, dy - other.dy);
+
+  Offset operator +(Offset other) => new Offset(dx + other.dx, dy + other.dy);
+
+  Offset operator *(double operand) => new Offset(dx * operand, dy * operand);
+
+  Offset operator /(double operand) => new Offset(dx / operand, dy / operand);
+
+  Offset operator ~/(double operand) {
+    return new Offset((dx ~/ operand).toDouble(), (dy ~/ operand).toDouble());
+  }
+
+  Offset operator %(double operand) => new Offset(dx % operand, dy % operand);
+
+  /// Linearly interpolates between two offsets.
+  static Offset lerp(Offset a, Offset b, double t) {
+    if (a == null && b == null) {
+      return null;
+    }
+    if (a == null) {
+      return b * t;
+    }
+    if (b == null) {
+      return a * (1.0 - t);
+    }
+    return new Offset(a.dx + (b.dx - a.dx) * t, a.dy + (b.dy - a.dy) * t);
+  }
+
+  @override
+  String toString() => 'Offset(${dx.toStringAsFixed(1)}, ${dy.toStringAsFixed(1)})';
+}
@@ -0,0 +1,163 @@
+import 'dart:math' as math;
+
+import 'offset.dart';
+import 'size.dart';
+
+/// An immutable, 2D, axis-aligned, floating-point rectangle.
+class Rect {
+  const Rect.fromLTRB(this.left, this.top, this.right, this.bottom);
+
+  const Rect.fromLTWH(double left, double top, double width, double height)
+      : this.fromLTRB(left, top, left + width, top + height);
+
+  factory Rect.fromCircle({Offset center, double radius}) {
+    return new Rect.fromLTRB(
+      center.dx - radius,
+      center.dy - radius,
+      center.dx + radius,
+      center.dy + radius,
+    );
+  }
+
+  factory Rect.fromPoints(Offset a, Offset b) {
+    return new Rect.fromLTRB(
+      math.min(a.dx, b.dx),
+      math.min(a.dy, b.dy),
+      math.max(a.dx, b.dx),
+      math.max(a.dy, b.dy),
+    );
+  }
+
+  final double left;
+  final double top;
+  final double right;
+  final double bottom;
+
+  static const Rect zero = const Rect.fromLTRB(0.0, 0.0, 0.0, 0.0);
+
+  static const double _giantScalar = 1.0E+9;
+
+  static const Rect largest =
+      const Rect.fromLTRB(-_giantScalar, -_giantScalar, _giantScalar, _giantScalar);
+
+  double get width => right - left;
+
+  double get height => bottom - top;
+
+  Size get size => new Size(width, height);
+
+  bool get isEmpty => left >= right || top >= bottom;
+
+  bool get isFinite => left.isFinite && top.isFinite && right.isFinite && bottom.isFinite;
+
+  Offset get topLeft => new Offset(left, top);
+
+  Offset get topRight => new Offset(right, top);
+
+  Offset get bottomLeft => new Offset(left, bottom);
+
+  Offset get bottomRight => new Offset(right, bottom);
+
+  Offset get center => new Offset(left + width / 2.0, top + height / 2.0);
+
+  double get shortestSide => math.min(width.abs(), height.abs());
+
+  Rect shift(Offset offset) {
+    return new Rect.fromLTRB(
+      left + offset.dx,
+      top + offset.dy,
+      right + offset.dx,
+      bottom + offset.dy,
+    );
+  }
+
+  Rect translate(double translateX, double translateY) {
+    return new Rect.fromLTRB(
+      left + translateX,
+      top + translateY,
+      right + translateX,
+      bottom + translateY,
+    );
+  }
+
+  Rect inflate(double delta) {
+    return new Rect.fromLTRB(left - delta, top - delta, right + delta, bottom + delta);
+  }
+
+  Rect deflate(double delta) => inflate(-delta);
+
+  Rect intersect(Rect other) {
+    return new Rect.fromLTRB(
+      math.max(left, other.left),
+      math.max(top, other.top),
+      math.min(right, other.right),
+      math.min(bottom, other.bottom),
+    );
+  }
+
+  Rect expandToInclude(Rect other) {
+    return new Rect.fromLTRB(
+      math.min(left, other.left),
+      math.min(top, other.top),
+      math.max(right, other.right),
+      math.max(bottom, other.bottom),
+    );
+  }
+
+  bool overlaps(Rect other) {
+    if (right <= other.left || other.right <= left) {
+      return false;
+    }
+    if (bottom <= other.top || other.bottom <= top) {
+      return false;
+    }
+    return true;
+  }
+
+  bool contains(Offset offset) {
+    return offset.dx >= left && offset.dx < right && offset.dy >= top && offset.dy < bottom;
+  }
+
+  static Rect lerp(Rect a, Rect b, double t) {
+    if (a == null && b == null) {
+      return null;
+    }
+    if (a == null) {
+      return new Rect.fromLTRB(b.left * t, b.top * t, b.right * t, b.bottom * t);
+    }
+    if (b == null) {
+      final double k = 1.0 - t;
+      return new Rect.fromLTRB(a.left * k, a.top * k, a.right * k, a.bottom * k);
+    }
+    return new Rect.fromLTRB(
+      a.left + (b.left - a.left) * t,
+      a.top + (b.top - a.top) * t,
+      a.right + (b.right - a.right) * t,
+      a.bottom + (b.bottom - a.bottom) * t,
+    );
+  }
+
+  @override
+  bool operator ==(dynamic other) {
+    if (identical(this, other)) {
+      return true;
+    }
+    if (other is! Rect) {
+      return false;
+    }
+    final Rect typedOther = other;
+    return left == typedOther.left &&
+        top == typedOther.top &&
+        right == typedOther.right &&
+        bottom == typedOther.bottom;
+  }
+
+  @override
+  int get hashCode => ((left.hashCode * 31 + top.hashCode) * 31 + right.hashCode) * 31 + bottom.hashCode;
+
+  @override
+  String toString() {
+    return 'Rect.fromLTRB(${left.toStringAsFixed(1)}, ${top.toStringAsFixed(1)}, '
+        '${right.toStringAsFixed(1)}, ${bottom.toStringAsFixed(1)})';
+  }
+}
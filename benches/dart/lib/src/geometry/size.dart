@@ -0,0 +1,74 @@
+import 'offset.dart';
+
+/// Holds a 2D floating-point size.
+class Size extends OffsetBase {
+  const Size(double width, double height) : super(width, height);
+
+  Size.copy(Size source) : super(source.width, source.height);
+
+  const Size.square(double dimension) : super(dimension, dimension);
+
+  const Size.fromWidth(double width) : super(width, double.infinity);
+
+  const Size.fromHeight(double height) : super(double.infinity, height);
+
+  static const Size zero = const Size(0.0, 0.0);
+
+  static const Size infinite = const Size(double.infinity, double.infinity);
+
+  double get width => _dx;
+
+  double get height => _dy;
+
+  double get aspectRatio {
+    if (height != 0.0) {
+      return width / height;
+    }
+    if (width > 0.0) {
+      return double.infinity;
+    }
+    if (width < 0.0) {
+      return double.negativeInfinity;
+    }
+    return 0.0;
+  }
+
+  bool get isEmpty => width <= 0.0 || height <= 0.0;
+
+  double get shortestSide => width < height ? width : height;
+
+  double get longestSide => width > height ? width : height;
+
+  Size get flipped => new Size(height, width);
+
+  Offset center(Offset origin) => new Offset(origin.dx + width / 2.0, origin.dy + height / 2.0);
+
+  Offset bottomRight(Offset origin) => new Offset(origin.dx + width, origin.dy + height);
+
+  /// Whether [offset] lies within a rectangle of this size at the origin.
+  bool contains(Offset offset) {
+    return offset.dx >= 0.0 && offset.dx < width && offset.dy >= 0.0 && offset.dy < height;
+  }
+
+  Size operator +(Offset other) => new Size(width + other.dx, height + other.dy);
+
+  Size operator *(double operand) => new Size(width * operand, height * operand);
+
+  Size operator /(double operand) => new Size(width / operand, height / operand);
+
+  static Size lerp(Size a, Size b, double t) {
+    if (a == null && b == null) {
+      return null;
+    }
+    if (a == null) {
+      return b * t;
+    }
+    if (b == null) {
+      return a * (1.0 - t);
+    }
+    return new Size(a.width + (b.width - a.width) * t, a.height + (b.height - a.height) * t);
+  }
+
+  @override
+  String toString() => 'Size(${width.toStringAsFixed(1)}, ${height.toStringAsFixed(1)})';
+}
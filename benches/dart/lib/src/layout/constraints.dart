@@ -0,0 +1,182 @@
+import '../foundation/diagnostics.dart';
+import '../geometry/size.dart';
+
+/// An abstract set of layout constraints.
+abstract class Constraints {
+  const Constraints();
+
+  /// Whether there is exactly one size possible given these constraints.
+  bool get isTight;
+
+  /// Whether the constraints are in their canonical form.
+  bool get isNormalized;
+
+  bool debugAssertIsValid({bool isAppliedConstraint: false}) {
+    assert(isNormalized);
+    return isNormalized;
+  }
+}
+
+/// Immutable layout constraints for boxes: a size is acceptable if its
+/// width and height lie within the given ranges.
+class BoxConstraints extends Constraints {
+  const BoxConstraints({
+    this.minWidth: 0.0,
+    this.maxWidth: double.infinity,
+    this.minHeight: 0.0,
+    this.maxHeight: double.infinity,
+  });
+
+  BoxConstraints.tight(Size size)
+      : minWidth = size.width,
+        maxWidth = size.width,
+        minHeight = size.height,
+        maxHeight = size.height;
+
+  const BoxConstraints.tightFor({double width, double height})
+      : minWidth = width != null ? width : 0.0,
+        maxWidth = width != null ? width : double.infinity,
+        minHeight = height != null ? height : 0.0,
+        maxHeight = height != null ? height : double.infinity;
+
+  BoxConstraints.loose(Size size)
+      : minWidth = 0.0,
+        maxWidth = size.width,
+        minHeight = 0.0,
+        maxHeight = size.height;
+
+  const BoxConstraints.expand({double width, double height})
+      : minWidth = width != null ? width : double.infinity,
+        maxWidth = width != null ? width : double.infinity,
+        minHeight = height != null ? height : double.infinity,
+        maxHeight = height != null ? height : double.infinity;
+
+  final double minWidth;
+  final double maxWidth;
+  final double minHeight;
+  final double maxHeight;
+
+  BoxConstraints copyWith({
+    double minWidth,
+    double maxWidth,
+    double minHeight,
+    double maxHeight,
+  }) {
+    return new BoxConstraints(
+      minWidth: minWidth ?? this.minWidth,
+      maxWidth: maxWidth ?? this.maxWidth,
+      minHeight: minHeight ?? this.minHeight,
+      maxHeight: maxHeight ?? this.maxHeight,
+    );
+  }
+
+  /// Returns new constraints that are smaller by the given edge dimensions.
+  BoxConstraints deflate(double horizontal, double vertical) {
+    final double deflatedMinWidth = _clampMin(minWidth - horizontal);
+    final double deflatedMinHeight = _clampMin(minHeight - vertical);
+    return new BoxConstraints(
+      minWidth: deflatedMinWidth,
+      maxWidth: _clampMin(maxWidth - horizontal, deflatedMinWidth),
+      minHeight: deflatedMinHeight,
+      maxHeight: _clampMin(maxHeight - vertical, deflatedMinHeight),
+    );
+  }
+
+  static double _clampMin(double value, [double min = 0.0]) => value < min ? min : value;
+
+  BoxConstraints loosen() {
+    return new BoxConstraints(maxWidth: maxWidth, maxHeight: maxHeight);
+  }
+
+  BoxConstraints enforce(BoxConstraints constraints) {
+    return new BoxConstraints(
+      minWidth: minWidth.clamp(constraints.minWidth, constraints.maxWidth),
+      maxWidth: maxWidth.clamp(constraints.minWidth, constraints.maxWidth),
+      minHeight: minHeight.clamp(constraints.minHeight, constraints.maxHeight),
+      maxHeight: maxHeight.clamp(constraints.minHeight, constraints.maxHeight),
+    );
+  }
+
+  BoxConstraints get flipped {
+    return new BoxConstraints(
+      minWidth: minHeight,
+      maxWidth: maxHeight,
+      minHeight: minWidth,
+      maxHeight: maxWidth,
+    );
+  }
+
+  double constrainWidth([double width = double.infinity]) {
+    return width.clamp(minWidth, maxWidth);
+  }
+
+  double constrainHeight([double height = double.infinity]) {
+    return height.clamp(minHeight, maxHeight);
+  }
+
+  Size constrain(Size size) => new Size(constrainWidth(size.width), constrainHeight(size.height));
+
+  Size get biggest => new Size(constrainWidth(), constrainHeight());
+
+  Size get smallest => new Size(constrainWidth(0.0), constrainHeight(0.0));
+
+  bool get hasTightWidth => minWidth >= maxWidth;
+
+  bool get hasTightHeight => minHeight >= maxHeight;
+
+  @override
+  bool get isTight => hasTightWidth && hasTightHeight;
+
+  bool get hasBoundedWidth => maxWidth < double.infinity;
+
+  bool get hasBoundedHeight => maxHeight < double.infinity;
+
+  bool isSatisfiedBy(Size size) {
+    return minWidth <= size.width &&
+        size.width <= maxWidth &&
+        minHeight <= size.height &&
+        size.height <= maxHeight;
+  }
+
+  @override
+  bool get isNormalized {
+    return minWidth >= 0.0 &&
+        minWidth <= maxWidth &&
+        minHeight >= 0.0 &&
+        minHeight <= maxHeight;
+  }
+
+  @override
+  bool operator ==(dynamic other) {
+    if (identical(this, other)) {
+      return true;
+    }
+    if (other is! BoxConstraints) {
+      return false;
+    }
+    final BoxConstraints typedOther = other;
+    return minWidth == typedOther.minWidth &&
+        maxWidth == typedOther.maxWidth &&
+        minHeight == typedOther.minHeight &&
+        maxHeight == typedOther.maxHeight;
+  }
+
+  @override
+  int get hashCode {
+    return ((minWidth.hashCode * 31 + maxWidth.hashCode) * 31 + minHeight.hashCode) * 31 +
+        maxHeight.hashCode;
+  }
+
+  @override
+  String toString() {
+    final DiagnosticsProperty<Size> biggestProperty =
+        new DiagnosticsProperty<Size>('biggest', biggest);
+    if (minWidth == double.infinity && minHeight == double.infinity) {
+      return 'BoxConstraints(biggest)';
+    }
+    if (isTight) {
+      return 'BoxConstraints(tight ${smallest.width} x ${smallest.height})';
+    }
+    return 'BoxConstraints($minWidth<=w<=$maxWidth, $minHeight<=h<=$maxHeight, $biggestProperty)';
+  }
+}
@@ -0,0 +1,242 @@
+import 'dart:math' as math;
+
+import '../geometry/offset.dart';
+import '../geometry/size.dart';
+import 'constraints.dart';
+import 'node.dart';
+
+/// The direction children of a flex are laid out in.
+enum Axis { horizontal, vertical }
+
+/// How children are placed along the main axis of a flex.
+enum MainAxisAlignment { start, end, center, spaceBetween, spaceAround, spaceEvenly }
+
+/// How children are placed along the cross axis of a flex.
+enum CrossAxisAlignment { start, end, center, stretch }
+
+/// How a flexible child is inscribed into its available space.
+enum FlexFit { tight, loose }
+
+/// Parent data for children of a [RenderFlex].
+class FlexParentData extends ContainerBoxParentData<RenderNode> {
+  /// The share of the remaining main-axis space this child takes, if any.
+  int flex;
+
+  FlexFit fit;
+
+  @override
+  String toString() => '${super.toString()}; flex=$flex; fit=$fit';
+}
+
+/// Lays out its children in a row or column, sharing out the remaining
+/// space among those that are flexible.
+class RenderFlex extends ContainerRenderNode<RenderNode, FlexParentData> {
+  RenderFlex({
+    List<RenderNode> children,
+    Axis direction: Axis.horizontal,
+    MainAxisAlignment mainAxisAlignment: MainAxisAlignment.start,
+    CrossAxisAlignment crossAxisAlignment: CrossAxisAlignment.center,
+  })  : _direction = direction,
+        _mainAxisAlignment = mainAxisAlignment,
+        _crossAxisAlignment = crossAxisAlignment {
+    addAll(children);
+  }
+
+  Axis _direction;
+  MainAxisAlignment _mainAxisAlignment;
+  CrossAxisAlignment _crossAxisAlignment;
+  double _overflow = 0.0;
+
+  Axis get direction => _direction;
+
+  set direction(Axis value) {
+    if (_direction != value) {
+      _direction = value;
+      markNeedsLayout();
+    }
+  }
+
+  MainAxisAlignment get mainAxisAlignment => _mainAxisAlignment;
+
+  set mainAxisAlignment(MainAxisAlignment value) {
+    if (_mainAxisAlignment != value) {
+      _mainAxisAlignment = value;
+      markNeedsLayout();
+    }
+  }
+
+  CrossAxisAlignment get crossAxisAlignment => _crossAxisAlignment;
+
+  set crossAxisAlignment(CrossAxisAlignment value) {
+    if (_crossAxisAlignment != value) {
+      _crossAxisAlignment = value;
+      markNeedsLayout();
+    }
+  }
+
+  bool get hasOverflow => _overflow > 0.0;
+
+  @override
+  void setupParentData(RenderNode child) {
+    if (child.parentData is! FlexParentData) {
+      child.parentData = new FlexParentData();
+    }
+  }
+
+  double _mainSize(Size size) => _direction == Axis.horizontal ? size.width : size.height;
+
+  double _crossSize(Size size) => _direction == Axis.horizontal ? size.height : size.width;
+
+  int _flexOf(RenderNode child) {
+    final FlexParentData childParentData = child.parentData;
+    return childParentData.flex ?? 0;
+  }
+
+  FlexFit _fitOf(RenderNode child) {
+    final FlexParentData childParentData = child.parentData;
+    return childParentData.fit ?? FlexFit.tight;
+  }
+
+  @override
+  void performLayout() {
+    final bool horizontal = _direction == Axis.horizontal;
+    final double maxMainSize = horizontal ? constraints.maxWidth : constraints.maxHeight;
+    final bool canFlex = maxMainSize < double.infinity;
+    final double maxCrossSize = horizontal ? constraints.maxHeight : constraints.maxWidth;
+
+    int totalFlex = 0;
+    double crossSize = 0.0;
+    double allocatedSize = 0.0;
+    RenderNode lastFlexChild;
+    for (RenderNode child in getChildrenAsList()) {
+      final int flex = _flexOf(child);
+      if (flex > 0 && canFlex) {
+        totalFlex += flex;
+        lastFlexChild = child;
+        continue;
+      }
+      BoxConstraints innerConstraints;
+      if (_crossAxisAlignment == CrossAxisAlignment.stretch) {
+        innerConstraints = horizontal
+            ? new BoxConstraints.tightFor(height: maxCrossSize)
+            : new BoxConstraints.tightFor(width: maxCrossSize);
+      } else {
+        innerConstraints = horizontal
+            ? new BoxConstraints(maxHeight: maxCrossSize)
+            : new BoxConstraints(maxWidth: maxCrossSize);
+      }
+      child.layout(innerConstraints, parentUsesSize: true);
+      allocatedSize += _mainSize(child.size);
+      crossSize = math.max(crossSize, _crossSize(child.size));
+    }
+
+    final double freeSpace = math.max(0.0, (canFlex ? maxMainSize : 0.0) - allocatedSize);
+    double allocatedFlexSpace = 0.0;
+    if (totalFlex > 0) {
+      final double spacePerFlex = freeSpace / totalFlex;
+      for (RenderNode child in getChildrenAsList()) {
+        final int flex = _flexOf(child);
+        if (flex == 0) {
+          continue;
+        }
+        final double maxChildExtent = child == lastFlexChild
+            ? freeSpace - allocatedFlexSpace
+            : spacePerFlex * flex;
+        final double minChildExtent = _fitOf(child) == FlexFit.tight ? maxChildExtent : 0.0;
+        final BoxConstraints innerConstraints = horizontal
+            ? new BoxConstraints(
+                minWidth: minChildExtent,
+                maxWidth: maxChildExtent,
+                minHeight: _crossAxisAlignment == CrossAxisAlignment.stretch ? maxCrossSize : 0.0,
+                maxHeight: maxCrossSize,
+              )
+            : new BoxConstraints(
+                minWidth: _crossAxisAlignment == CrossAxisAlignment.stretch ? maxCrossSize : 0.0,
+                maxWidth: maxCrossSize,
+                minHeight: minChildExtent,
+                maxHeight: maxChildExtent,
+              );
+        child.layout(innerConstraints, parentUsesSize: true);
+        final double childMainSize = _mainSize(child.size);
+        allocatedSize += childMainSize;
+        allocatedFlexSpace += maxChildExtent;
+        crossSize = math.max(crossSize, _crossSize(child.size));
+      }
+    }
+
+    final double idealSize = canFlex && totalFlex > 0 ? maxMainSize : allocatedSize;
+    size = horizontal
+        ? constraints.constrain(new Size(idealSize, crossSize))
+        : constraints.constrain(new Size(crossSize, idealSize));
+    final double actualSize = _mainSize(size);
+    crossSize = _crossSize(size);
+    _overflow = math.max(0.0, allocatedSize - actualSize);
+
+    final double remainingSpace = math.max(0.0, actualSize - allocatedSize);
+    double leadingSpace;
+    double betweenSpace;
+    switch (_mainAxisAlignment) {
+      case MainAxisAlignment.start:
+        leadingSpace = 0.0;
+        betweenSpace = 0.0;
+        break;
+      case MainAxisAlignment.end:
+        leadingSpace = remainingSpace;
+        betweenSpace = 0.0;
+        break;
+      case MainAxisAlignment.center:
+        leadingSpace = remainingSpace / 2.0;
+        betweenSpace = 0.0;
+        break;
+      case MainAxisAlignment.spaceBetween:
+        leadingSpace = 0.0;
+        betweenSpace = childCount > 1 ? remainingSpace / (childCount - 1) : 0.0;
+        break;
+      case MainAxisAlignment.spaceAround:
+        betweenSpace = childCount > 0 ? remainingSpace / childCount : 0.0;
+        leadingSpace = betweenSpace / 2.0;
+        break;
+      case MainAxisAlignment.spaceEvenly:
+        betweenSpace = childCount > 0 ? remainingSpace / (childCount + 1) : 0.0;
+        leadingSpace = betweenSpace;
+        break;
+    }
+
+    double childMainPosition = leadingSpace;
+    RenderNode child = firstChild;
+    while (child != null) {
+      final FlexParentData childParentData = child.parentData;
+      double childCrossPosition;
+      switch (_crossAxisAlignment) {
+        case CrossAxisAlignment.start:
+        case CrossAxisAlignment.stretch:
+          childCrossPosition = 0.0;
+          break;
+        case CrossAxisAlignment.end:
+          childCrossPosition = crossSize - _crossSize(child.size);
+          break;
+        case CrossAxisAlignment.center:
+          childCrossPosition = crossSize / 2.0 - _crossSize(child.size) / 2.0;
+          break;
+      }
+      childParentData.offset = horizontal
+          ? new Offset(childMainPosition, childCrossPosition)
+          : new Offset(childCrossPosition, childMainPosition);
+      childMainPosition += _mainSize(child.size) + betweenSpace;
+      child = childParentData.nextSibling;
+    }
+  }
+
+  @override
+  bool hitTestChildren(List<RenderNode> result, Offset position) {
+    return hitTestChildrenInReverse(result, position);
+  }
+
+  @override
+  void paint(PaintRecorder recorder, Offset offset) {
+    paintChildren(recorder, offset);
+    if (hasOverflow) {
+      recorder.drawText('overflowed by ${_overflow.toStringAsFixed(1)} pixels', offset);
+    }
+  }
+}
@@ -0,0 +1,459 @@
+import 'dart:collection';
+
+import '../foundation/diagnostics.dart';
+import '../foundation/observable.dart';
+import '../geometry/offset.dart';
+import '../geometry/rect.dart';
+import '../geometry/size.dart';
+import 'constraints.dart';
+
+/// Data a parent keeps on each of its children.
+class ParentData {
+  /// Called when the child is removed from its parent.
+  void detach() {}
+
+  @override
+  String toString() => '<none>';
+}
+
+/// [ParentData] with the offset the child is placed at.
+class BoxParentData extends ParentData {
+  Offset offset = Offset.zero;
+
+  @override
+  String toString() => 'offset=$offset';
+}
+
+/// [BoxParentData] for parents that keep their children in a list.
+class ContainerBoxParentData<ChildType extends RenderNode> extends BoxParentData {
+  ChildType previousSibling;
+  ChildType nextSibling;
+
+  @override
+  void detach() {
+    assert(previousSibling == null);
+    assert(nextSibling == null);
+    super.detach();
+  }
+}
+
+/// Records the drawing commands of a paint pass.
+class PaintRecorder {
+  final List<String> commands = <String>[];
+  final Queue<Offset> _origins = new Queue<Offset>();
+
+  Offset get origin => _origins.isEmpty ? Offset.zero : _origins.last;
+
+  void drawRect(Rect rect, String style) {
+    commands.add('rect ${rect.shift(origin)} $style');
+  }
+
+  void drawText(String text, Offset offset) {
+    commands.add('text "$text" at ${offset + origin}');
+  }
+
+  void withOffset(Offset offset, void paint()) {
+    _origins.addLast(origin + offset);
+    try {
+      paint();
+    } finally {
+      _origins.removeLast();
+    }
+  }
+}
+
+/// Schedules layout and paint for a tree of [RenderNode]s.
+class PipelineOwner extends ChangeNotifier {
+  final List<RenderNode> _nodesNeedingLayout = <RenderNode>[];
+  final List<RenderNode> _nodesNeedingPaint = <RenderNode>[];
+  int _layoutPasses = 0;
+
+  int get layoutPasses => _layoutPasses;
+
+  void requestLayout(RenderNode node) {
+    _nodesNeedingLayout.add(node);
+    notifyListeners();
+  }
+
+  void requestPaint(RenderNode node) {
+    _nodesNeedingPaint.add(node);
+  }
+
+  /// Lays out all dirty nodes, shallowest first.
+  void flushLayout() {
+    while (_nodesNeedingLayout.isNotEmpty) {
+      final List<RenderNode> dirtyNodes = _nodesNeedingLayout.toList();
+      _nodesNeedingLayout.clear();
+      dirtyNodes.sort((RenderNode a, RenderNode b) => a.depth - b.depth);
+      for (RenderNode node in dirtyNodes) {
+        if (node._needsLayout && node.owner == this) {
+          node._layoutWithoutResize();
+        }
+      }
+      _layoutPasses += 1;
+    }
+  }
+
+  List<String> flushPaint() {
+    final PaintRecorder recorder = new PaintRecorder();
+    final List<RenderNode> dirtyNodes = _nodesNeedingPaint.toList();
+    _nodesNeedingPaint.clear();
+    dirtyNodes.sort((RenderNode a, RenderNode b) => b.depth - a.depth);
+    for (RenderNode node in dirtyNodes) {
+      if (node._needsPaint && node.owner == this) {
+        node.paint(recorder, Offset.zero);
+        node._needsPaint = false;
+      }
+    }
+    return recorder.commands;
+  }
+}
+
+/// An object in the render tree, laid out as a box.
+abstract class RenderNode extends DiagnosticableTree {
+  RenderNode() {
+    _needsLayout = true;
+  }
+
+  ParentData parentData;
+  RenderNode _parent;
+  PipelineOwner _owner;
+  int _depth = 0;
+  bool _needsLayout = true;
+  bool _needsPaint = true;
+  BoxConstraints _constraints;
+  Size _size;
+
+  RenderNode get parent => _parent;
+
+  PipelineOwner get owner => _owner;
+
+  int get depth => _depth;
+
+  bool get attached => _owner != null;
+
+  BoxConstraints get constraints => _constraints;
+
+  bool get hasSize => _size != null;
+
+  Size get size {
+    assert(hasSize, 'RenderNode was not laid out: $this');
+    return _size;
+  }
+
+  set size(Size value) {
+    assert(constraints == null || constraints.isSatisfiedBy(value));
+    _size = value;
+  }
+
+  /// Whether this node's size depends only on its constraints.
+  bool get sizedByParent => false;
+
+  void setupParentData(RenderNode child) {
+    if (child.parentData is! BoxParentData) {
+      child.parentData = new BoxParentData();
+    }
+  }
+
+  void adoptChild(RenderNode child) {
+    assert(child._parent == null);
+    setupParentData(child);
+    child._parent = this;
+    child._redepth(_depth + 1);
+    if (attached) {
+      child.attach(_owner);
+    }
+    markNeedsLayout();
+  }
+
+  void dropChild(RenderNode child) {
+    assert(child._parent == this);
+    child.parentData.detach();
+    child._parent = null;
+    if (attached) {
+      child.detach();
+    }
+    markNeedsLayout();
+  }
+
+  void _redepth(int depth) {
+    _depth = depth;
+    visitChildren((RenderNode child) => child._redepth(depth + 1));
+  }
+
+  void attach(PipelineOwner owner) {
+    _owner = owner;
+    if (_needsLayout) {
+      owner.requestLayout(this);
+    }
+    if (_needsPaint) {
+      owner.requestPaint(this);
+    }
+    visitChildren((RenderNode child) => child.attach(owner));
+  }
+
+  void detach() {
+    _owner = null;
+    visitChildren((RenderNode child) => child.detach());
+  }
+
+  void visitChildren(void visitor(RenderNode child)) {}
+
+  void markNeedsLayout() {
+    if (_needsLayout) {
+      return;
+    }
+    _needsLayout = true;
+    if (_parent != null) {
+      _parent.markNeedsLayout();
+    } else if (_owner != null) {
+      _owner.requestLayout(this);
+    }
+    markNeedsPaint();
+  }
+
+  void markNeedsPaint() {
+    if (_needsPaint) {
+      return;
+    }
+    _needsPaint = true;
+    _owner?.requestPaint(this);
+  }
+
+  void _layoutWithoutResize() {
+    performLayout();
+    _needsLayout = false;
+    markNeedsPaint();
+  }
+
+  /// Lays out this node within [constraints].
+  void layout(BoxConstraints constraints, {bool parentUsesSize: false}) {
+    if (!_needsLayout && constraints == _constraints) {
+      return;
+    }
+    _constraints = constraints;
+    if (sizedByParent) {
+      performResize();
+    }
+    performLayout();
+    _needsLayout = false;
+    markNeedsPaint();
+  }
+
+  void performResize() {
+    size = constraints.smallest;
+  }
+
+  void performLayout();
+
+  void paint(PaintRecorder recorder, Offset offset);
+
+  /// Returns the nodes at [position], deepest first.
+  bool hitTest(List<RenderNode> result, Offset position) {
+    if (!size.contains(position)) {
+      return false;
+    }
+    if (hitTestChildren(result, position) || hitTestSelf(position)) {
+      result.add(this);
+      return true;
+    }
+    return false;
+  }
+
+  bool hitTestSelf(Offset position) => false;
+
+  bool hitTestChildren(List<RenderNode> result, Offset position) => false;
+
+  @override
+  List<DiagnosticableTree> debugDescribeChildren() {
+    final List<DiagnosticableTree> children = <DiagnosticableTree>[];
+    visitChildren(children.add);
+    return children;
+  }
+
+  @override
+  void debugFillProperties(DiagnosticPropertiesBuilder properties) {
+    super.debugFillProperties(properties);
+    properties.add(new DiagnosticsProperty<Object>('constraints', _constraints));
+    properties.add(new DiagnosticsProperty<Object>('size', _size));
+    properties.addFlag('needsLayout', _needsLayout, ifTrue: 'NEEDS-LAYOUT');
+  }
+}
+
+/// A [RenderNode] with at most one child.
+abstract class RenderNodeWithChild<ChildType extends RenderNode> extends RenderNode {
+  ChildType _child;
+
+  ChildType get child => _child;
+
+  set child(ChildType value) {
+    if (_child != null) {
+      dropChild(_child);
+    }
+    _child = value;
+    if (_child != null) {
+      adoptChild(_child);
+    }
+  }
+
+  @override
+  void visitChildren(void visitor(RenderNode child)) {
+    if (_child != null) {
+      visitor(_child);
+    }
+  }
+}
+
+/// A [RenderNode] with a list of children, linked through their parent data.
+abstract class ContainerRenderNode<ChildType extends RenderNode,
+    ParentDataType extends ContainerBoxParentData<ChildType>> extends RenderNode {
+  int _childCount = 0;
+  ChildType _firstChild;
+  ChildType _lastChild;
+
+  int get childCount => _childCount;
+
+  ChildType get firstChild => _firstChild;
+
+  ChildType get lastChild => _lastChild;
+
+  ChildType childAfter(ChildType child) {
+    final ParentDataType childParentData = child.parentData;
+    return childParentData.nextSibling;
+  }
+
+  void insert(ChildType child, {ChildType after}) {
+    adoptChild(child);
+    final ParentDataType childParentData = child.parentData;
+    _childCount += 1;
+    if (after == null) {
+      childParentData.nextSibling = _firstChild;
+      if (_firstChild != null) {
+        final ParentDataType firstChildParentData = _firstChild.parentData;
+        firstChildParentData.previousSibling = child;
+      }
+      _firstChild = child;
+      _lastChild ??= child;
+    } else {
+      final ParentDataType afterParentData = after.parentData;
+      childParentData.previousSibling = after;
+      childParentData.nextSibling = afterParentData.nextSibling;
+      if (afterParentData.nextSibling == null) {
+        _lastChild = child;
+      } else {
+        final ParentDataType nextParentData = afterParentData.nextSibling.parentData;
+        nextParentData.previousSibling = child;
+      }
+      afterParentData.nextSibling = child;
+    }
+  }
+
+  void add(ChildType child) {
+    insert(child, after: _lastChild);
+  }
+
+  void addAll(List<ChildType> children) {
+    children?.forEach(add);
+  }
+
+  void remove(ChildType child) {
+    final ParentDataType childParentData = child.parentData;
+    if (childParentData.previousSibling == null) {
+      _firstChild = childParentData.nextSibling;
+    } else {
+      final ParentDataType previousParentData = childParentData.previousSibling.parentData;
+      previousParentData.nextSibling = childParentData.nextSibling;
+    }
+    if (childParentData.nextSibling == null) {
+      _lastChild = childParentData.previousSibling;
+    } else {
+      final ParentDataType nextParentData = childParentData.nextSibling.parentData;
+      nextParentData.previousSibling = childParentData.previousSibling;
+    }
+    childParentData.previousSibling = null;
+    childParentData.nextSibling = null;
+    _childCount -= 1;
+    dropChild(child);
+  }
+
+  List<ChildType> getChildrenAsList() {
+    final List<ChildType> result = <ChildType>[];
+    ChildType child = _firstChild;
+    while (child != null) {
+      result.add(child);
+      child = childAfter(child);
+    }
+    return result;
+  }
+
+  @override
+  void visitChildren(void visitor(RenderNode child)) {
+    ChildType child = _firstChild;
+    while (child != null) {
+      visitor(child);
+      child = childAfter(child);
+    }
+  }
+
+  void paintChildren(PaintRecorder recorder, Offset offset) {
+    ChildType child = _firstChild;
+    while (child != null) {
+      final ParentDataType childParentData = child.parentData;
+      child.paint(recorder, childParentData.offset + offset);
+      child = childAfter(child);
+    }
+  }
+
+  bool hitTestChildrenInReverse(List<RenderNode> result, Offset position) {
+    ChildType child = _lastChild;
+    while (child != null) {
+      final ParentDataType childParentData = child.parentData;
+      if (child.hitTest(result, position - childParentData.offset)) {
+        return true;
+      }
+      child = childParentData.previousSibling;
+    }
+    return false;
+  }
+}
+
+/// A box of a fixed color and optional label, sized by its constraints.
+class RenderColoredBox extends RenderNode {
+  RenderColoredBox({this.color: 'grey', this.label, Size preferredSize: Size.zero})
+      : _preferredSize = preferredSize;
+
+  String color;
+  String label;
+  Size _preferredSize;
+
+  Size get preferredSize => _preferredSize;
+
+  set preferredSize(Size value) {
+    if (value == _preferredSize) {
+      return;
+    }
+    _preferredSize = value;
+    markNeedsLayout();
+  }
+
+  @override
+  void performLayout() {
+    size = constraints.constrain(_preferredSize);
+  }
+
+  @override
+  bool hitTestSelf(Offset position) => true;
+
+  @override
+  void paint(PaintRecorder recorder, Offset offset) {
+    recorder.drawRect(offset & size, color);
+    if (label != null) {
+      recorder.drawText(label, offset);
+    }
+  }
+}
+
+/// Lets [Offset]s and [Size]s be combined into [Rect]s.
+extension RectFromOffset on Offset {
+  Rect operator &(Size other) => new Rect.fromLTWH(dx, dy, other.width, other.height);
+}
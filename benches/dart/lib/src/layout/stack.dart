@@ -0,0 +1,265 @@
+import 'dart:math' as math;
+
+import '../geometry/offset.dart';
+import '../geometry/size.dart';
+import 'constraints.dart';
+import 'node.dart';
+
+/// Where a non-positioned child of a stack is placed, as fractions of the
+/// free space along each axis.
+class Alignment {
+  const Alignment(this.x, this.y);
+
+  final double x;
+  final double y;
+
+  static const Alignment topLeft = const Alignment(-1.0, -1.0);
+  static const Alignment center = const Alignment(0.0, 0.0);
+  static const Alignment bottomRight = const Alignment(1.0, 1.0);
+
+  /// The offset of a rectangle of [inner] size aligned within [outer].
+  Offset alongOffset(Size outer, Size inner) {
+    final double halfWidthDelta = (outer.width - inner.width) / 2.0;
+    final double halfHeightDelta = (outer.height - inner.height) / 2.0;
+    return new Offset(halfWidthDelta + x * halfWidthDelta, halfHeightDelta + y * halfHeightDelta);
+  }
+
+  @override
+  bool operator ==(dynamic other) => other is Alignment && other.x == x && other.y == y;
+
+  @override
+  int get hashCode => x.hashCode * 31 + y.hashCode;
+
+  @override
+  String toString() => 'Alignment($x, $y)';
+}
+
+/// How children of a stack that aren't positioned are sized.
+enum StackFit { loose, expand, passthrough }
+
+/// Parent data for children of a [RenderStack].
+class StackParentData extends ContainerBoxParentData<RenderNode> {
+  double top;
+  double right;
+  double bottom;
+  double left;
+  double width;
+  double height;
+
+  bool get isPositioned =>
+      top != null || right != null || bottom != null || left != null || width != null || height != null;
+
+  @override
+  String toString() {
+    final List<String> values = <String>[];
+    if (top != null) values.add('top=$top');
+    if (right != null) values.add('right=$right');
+    if (bottom != null) values.add('bottom=$bottom');
+    if (left != null) values.add('left=$left');
+    if (width != null) values.add('width=$width');
+    if (height != null) values.add('height=$height');
+    if (values.isEmpty) values.add('not positioned');
+    values.add(super.toString());
+    return values.join('; ');
+  }
+}
+
+/// Lays out its children on top of each other, the first one at the
+/// bottom.
+class RenderStack extends ContainerRenderNode<RenderNode, StackParentData> {
+  RenderStack({
+    List<RenderNode> children,
+    Alignment alignment: Alignment.topLeft,
+    StackFit fit: StackFit.loose,
+  })  : _alignment = alignment,
+        _fit = fit {
+    addAll(children);
+  }
+
+  Alignment _alignment;
+  StackFit _fit;
+  bool _hasVisualOverflow = false;
+
+  Alignment get alignment => _alignment;
+
+  set alignment(Alignment value) {
+    if (_alignment == value) {
+      return;
+    }
+    _alignment = value;
+    markNeedsLayout();
+  }
+
+  StackFit get fit => _fit;
+
+  set fit(StackFit value) {
+    if (_fit == value) {
+      return;
+    }
+    _fit = value;
+    markNeedsLayout();
+  }
+
+  @override
+  void setupParentData(RenderNode child) {
+    if (child.parentData is! StackParentData) {
+      child.parentData = new StackParentData();
+    }
+  }
+
+  @override
+  void performLayout() {
+    _hasVisualOverflow = false;
+    if (childCount == 0) {
+      size = constraints.biggest;
+      return;
+    }
+
+    double width = constraints.minWidth;
+    double height = constraints.minHeight;
+    BoxConstraints nonPositionedConstraints;
+    switch (_fit) {
+      case StackFit.loose:
+        nonPositionedConstraints = constraints.loosen();
+        break;
+      case StackFit.expand:
+        nonPositionedConstraints = new BoxConstraints.tight(constraints.biggest);
+        break;
+      case StackFit.passthrough:
+        nonPositionedConstraints = constraints;
+        break;
+    }
+
+    bool hasNonPositionedChildren = false;
+    for (RenderNode child in getChildrenAsList()) {
+      final StackParentData childParentData = child.parentData;
+      if (!childParentData.isPositioned) {
+        hasNonPositionedChildren = true;
+        child.layout(nonPositionedConstraints, parentUsesSize: true);
+        width = math.max(width, child.size.width);
+        height = math.max(height, child.size.height);
+      }
+    }
+    size = hasNonPositionedChildren ? new Size(width, height) : constraints.biggest;
+
+    for (RenderNode child in getChildrenAsList()) {
+      final StackParentData childParentData = child.parentData;
+      if (!childParentData.isPositioned) {
+        childParentData.offset = _alignment.alongOffset(size, child.size);
+        continue;
+      }
+      _hasVisualOverflow = _layoutPositionedChild(child, childParentData) || _hasVisualOverflow;
+    }
+  }
+
+  bool _layoutPositionedChild(RenderNode child, StackParentData childParentData) {
+    BoxConstraints childConstraints = const BoxConstraints();
+    if (childParentData.left != null && childParentData.right != null) {
+      childConstraints = childConstraints.copyWith(
+        minWidth: size.width - childParentData.right - childParentData.left,
+        maxWidth: size.width - childParentData.right - childParentData.left,
+      );
+    } else if (childParentData.width != null) {
+      childConstraints = childConstraints.copyWith(
+        minWidth: childParentData.width,
+        maxWidth: childParentData.width,
+      );
+    }
+    if (childParentData.top != null && childParentData.bottom != null) {
+      childConstraints = childConstraints.copyWith(
+        minHeight: size.height - childParentData.bottom - childParentData.top,
+        maxHeight: size.height - childParentData.bottom - childParentData.top,
+      );
+    } else if (childParentData.height != null) {
+      childConstraints = childConstraints.copyWith(
+        minHeight: childParentData.height,
+        maxHeight: childParentData.height,
+      );
+    }
+    child.layout(childConstraints, parentUsesSize: true);
+
+    double x;
+    if (childParentData.left != null) {
+      x = childParentData.left;
+    } else if (childParentData.right != null) {
+      x = size.width - childParentData.right - child.size.width;
+    } else {
+      x = _alignment.alongOffset(size, child.size).dx;
+    }
+    double y;
+    if (childParentData.top != null) {
+      y = childParentData.top;
+    } else if (childParentData.bottom != null) {
+      y = size.height - childParentData.bottom - child.size.height;
+    } else {
+      y = _alignment.alongOffset(size, child.size).dy;
+    }
+    childParentData.offset = new Offset(x, y);
+
+    return x < 0.0 ||
+        x + child.size.width > size.width ||
+        y < 0.0 ||
+        y + child.size.height > size.height;
+  }
+
+  @override
+  bool hitTestChildren(List<RenderNode> result, Offset position) {
+    return hitTestChildrenInReverse(result, position);
+  }
+
+  @override
+  void paint(PaintRecorder recorder, Offset offset) {
+    if (_hasVisualOverflow) {
+      recorder.drawRect(offset & size, 'clip');
+    }
+    paintChildren(recorder, offset);
+  }
+}
+
+/// A [RenderStack] that paints only the child at [index].
+class RenderIndexedStack extends RenderStack {
+  RenderIndexedStack({List<RenderNode> children, Alignment alignment: Alignment.topLeft, int index: 0})
+      : _index = index,
+        super(children: children, alignment: alignment);
+
+  int _index;
+
+  int get index => _index;
+
+  set index(int value) {
+    if (_index != value) {
+      _index = value;
+      markNeedsPaint();
+    }
+  }
+
+  RenderNode _childAtIndex() {
+    RenderNode child = firstChild;
+    int i = 0;
+    while (child != null && i < index) {
+      child = childAfter(child);
+      i += 1;
+    }
+    return child;
+  }
+
+  @override
+  bool hitTestChildren(List<RenderNode> result, Offset position) {
+    final RenderNode child = _childAtIndex();
+    if (child == null) {
+      return false;
+    }
+    final StackParentData childParentData = child.parentData;
+    return child.hitTest(result, position - childParentData.offset);
+  }
+
+  @override
+  void paint(PaintRecorder recorder, Offset offset) {
+    final RenderNode child = _childAtIndex();
+    if (child == null) {
+      return;
+    }
+    final StackParentData childParentData = child.parentData;
+    child.paint(recorder, childParentData.offset + offset);
+  }
+}
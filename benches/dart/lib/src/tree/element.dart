@@ -0,0 +1,424 @@
+part of widgets;
+
+/// A handle to the location of a widget in the tree.
+abstract class BuildContext {
+  Widget get widget;
+
+  bool get mounted;
+
+  /// The nearest render node at or below this location.
+  RenderNode findRenderNode();
+
+  /// Registers a dependency on the nearest [InheritedWidget] of type [T].
+  T dependOnInheritedWidgetOfExactType<T extends InheritedWidget>();
+
+  /// Returns the nearest [State] of type [T] above this location.
+  T findAncestorStateOfType<T extends State<StatefulWidget>>();
+
+  void visitChildElements(void visitor(Element element));
+}
+
+/// Keeps track of the elements that need rebuilding.
+class BuildOwner {
+  BuildOwner({this.onBuildScheduled});
+
+  final VoidCallback onBuildScheduled;
+  final List<Element> _dirtyElements = <Element>[];
+  bool _scheduledFlushDirtyElements = false;
+  int _builds = 0;
+
+  int get builds => _builds;
+
+  void scheduleBuildFor(Element element) {
+    if (element._inDirtyList) {
+      return;
+    }
+    if (!_scheduledFlushDirtyElements && onBuildScheduled != null) {
+      _scheduledFlushDirtyElements = true;
+      onBuildScheduled();
+    }
+    _dirtyElements.add(element);
+    element._inDirtyList = true;
+  }
+
+  void buildScope() {
+    _dirtyElements.sort((Element a, Element b) => a.depth - b.depth);
+    int index = 0;
+    while (index < _dirtyElements.length) {
+      _dirtyElements[index].rebuild();
+      _builds += 1;
+      index += 1;
+    }
+    for (Element element in _dirtyElements) {
+      element._inDirtyList = false;
+    }
+    _dirtyElements.clear();
+    _scheduledFlushDirtyElements = false;
+  }
+}
+
+enum _ElementLifecycle { initial, active, inactive, defunct }
+
+/// An instantiation of a [Widget] at a particular location in the tree.
+abstract class Element extends DiagnosticableTree implements BuildContext {
+  Element(Widget widget) : _widget = widget;
+
+  Element _parent;
+  Widget _widget;
+  BuildOwner _owner;
+  int _depth;
+  bool _dirty = true;
+  bool _inDirtyList = false;
+  _ElementLifecycle _lifecycleState = _ElementLifecycle.initial;
+  Map<Type, InheritedElement> _inheritedWidgets;
+
+  @override
+  Widget get widget => _widget;
+
+  @override
+  bool get mounted => _widget != null;
+
+  BuildOwner get owner => _owner;
+
+  int get depth => _depth;
+
+  bool get dirty => _dirty;
+
+  Element get parent => _parent;
+
+  void mount(Element parent) {
+    assert(_lifecycleState == _ElementLifecycle.initial);
+    _parent = parent;
+    _lifecycleState = _ElementLifecycle.active;
+    _depth = _parent != null ? _parent.depth + 1 : 1;
+    if (parent != null) {
+      _owner = parent.owner;
+    }
+    _updateInheritance();
+  }
+
+  void update(covariant Widget newWidget) {
+    assert(Widget.canUpdate(widget, newWidget));
+    _widget = newWidget;
+  }
+
+  void unmount() {
+    _lifecycleState = _ElementLifecycle.defunct;
+    _widget = null;
+  }
+
+  void deactivate() {
+    _lifecycleState = _ElementLifecycle.inactive;
+    visitChildElements((Element child) => child.deactivate());
+  }
+
+  void _updateInheritance() {
+    _inheritedWidgets = _parent?._inheritedWidgets;
+  }
+
+  /// Updates [child] to show [newWidget], inflating or dropping elements as
+  /// needed.
+  Element updateChild(Element child, Widget newWidget) {
+    if (newWidget == null) {
+      if (child != null) {
+        child.deactivate();
+        child.unmount();
+      }
+      return null;
+    }
+    if (child != null) {
+      if (child.widget == newWidget) {
+        return child;
+      }
+      if (Widget.canUpdate(child.widget, newWidget)) {
+        child.update(newWidget);
+        return child;
+      }
+      child.deactivate();
+      child.unmount();
+    }
+    return inflateWidget(newWidget);
+  }
+
+  Element inflateWidget(Widget newWidget) {
+    final Element newChild = newWidget.createElement();
+    newChild.mount(this);
+    return newChild;
+  }
+
+  void markNeedsBuild() {
+    if (_lifecycleState != _ElementLifecycle.active || _dirty) {
+      return;
+    }
+    _dirty = true;
+    owner.scheduleBuildFor(this);
+  }
+
+  void rebuild() {
+    if (_lifecycleState != _ElementLifecycle.active || !_dirty) {
+      return;
+    }
+    performRebuild();
+  }
+
+  void performRebuild();
+
+  @override
+  RenderNode findRenderNode() {
+    RenderNode result;
+    void visit(Element element) {
+      if (result != null) {
+        return;
+      }
+      if (element is RenderNodeElement) {
+        result = element.renderNode;
+      } else {
+        element.visitChildElements(visit);
+      }
+    }
+    visit(this);
+    return result;
+  }
+
+  @override
+  T dependOnInheritedWidgetOfExactType<T extends InheritedWidget>() {
+    final InheritedElement ancestor = _inheritedWidgets == null ? null : _inheritedWidgets[T];
+    if (ancestor == null) {
+      return null;
+    }
+    ancestor._dependents.add(this);
+    return ancestor.widget as T;
+  }
+
+  @override
+  T findAncestorStateOfType<T extends State<StatefulWidget>>() {
+    Element ancestor = _parent;
+    while (ancestor != null) {
+      if (ancestor is StatefulElement && ancestor.state is T) {
+        return ancestor.state as T;
+      }
+      ancestor = ancestor._parent;
+    }
+    return null;
+  }
+
+  void didChangeDependencies() {
+    markNeedsBuild();
+  }
+
+  @override
+  void visitChildElements(void visitor(Element element)) {}
+
+  @override
+  List<DiagnosticableTree> debugDescribeChildren() {
+    final List<DiagnosticableTree> children = <DiagnosticableTree>[];
+    visitChildElements(children.add);
+    return children;
+  }
+
+  @override
+  String toStringShort() => widget != null ? widget.toStringShort() : '[$runtimeType]';
+}
+
+/// An element with a single child, built by [build].
+abstract class ComponentElement extends Element {
+  ComponentElement(Widget widget) : super(widget);
+
+  Element _child;
+
+  @override
+  void mount(Element parent) {
+    super.mount(parent);
+    _firstBuild();
+  }
+
+  void _firstBuild() {
+    rebuild();
+  }
+
+  Widget build();
+
+  @override
+  void performRebuild() {
+    Widget built;
+    try {
+      built = build();
+    } catch (e) {
+      built = new ErrorWidget(e);
+    }
+    _dirty = false;
+    _child = updateChild(_child, built);
+  }
+
+  @override
+  void visitChildElements(void visitor(Element element)) {
+    if (_child != null) {
+      visitor(_child);
+    }
+  }
+}
+
+class StatelessElement extends ComponentElement {
+  StatelessElement(StatelessWidget widget) : super(widget);
+
+  @override
+  StatelessWidget get widget => super.widget as StatelessWidget;
+
+  @override
+  Widget build() => widget.build(this);
+
+  @override
+  void update(StatelessWidget newWidget) {
+    super.update(newWidget);
+    _dirty = true;
+    rebuild();
+  }
+}
+
+class StatefulElement extends ComponentElement {
+  StatefulElement(StatefulWidget widget)
+      : _state = widget.createState(),
+        super(widget) {
+    _state._element = this;
+    _state._widget = widget;
+  }
+
+  final State<StatefulWidget> _state;
+
+  State<StatefulWidget> get state => _state;
+
+  @override
+  Widget build() => _state.build(this);
+
+  @override
+  void _firstBuild() {
+    _state.initState();
+    _state.didChangeDependencies();
+    super._firstBuild();
+  }
+
+  @override
+  void update(StatefulWidget newWidget) {
+    super.update(newWidget);
+    final StatefulWidget oldWidget = _state._widget;
+    _dirty = true;
+    _state._widget = widget;
+    _state.didUpdateWidget(oldWidget);
+    rebuild();
+  }
+
+  @override
+  void didChangeDependencies() {
+    super.didChangeDependencies();
+    _state.didChangeDependencies();
+  }
+
+  @override
+  void unmount() {
+    _state.dispose();
+    _state._element = null;
+    super.unmount();
+  }
+}
+
+class InheritedElement extends ComponentElement {
+  InheritedElement(InheritedWidget widget) : super(widget);
+
+  final Set<Element> _dependents = new Set<Element>();
+
+  @override
+  InheritedWidget get widget => super.widget as InheritedWidget;
+
+  @override
+  void _updateInheritance() {
+    final Map<Type, InheritedElement> incoming = _parent?._inheritedWidgets;
+    _inheritedWidgets = incoming != null
+        ? new Map<Type, InheritedElement>.from(incoming)
+        : <Type, InheritedElement>{};
+    _inheritedWidgets[widget.runtimeType] = this;
+  }
+
+  @override
+  Widget build() => widget.child;
+
+  @override
+  void update(InheritedWidget newWidget) {
+    final InheritedWidget oldWidget = widget;
+    super.update(newWidget);
+    if (widget.updateShouldNotify(oldWidget)) {
+      for (Element dependent in _dependents) {
+        dependent.didChangeDependencies();
+      }
+    }
+    _dirty = true;
+    rebuild();
+  }
+}
+
+/// An element that owns a [RenderNode].
+class RenderNodeElement extends Element {
+  RenderNodeElement(RenderNodeWidget widget) : super(widget);
+
+  RenderNode _renderNode;
+  List<Element> _children = <Element>[];
+
+  @override
+  RenderNodeWidget get widget => super.widget as RenderNodeWidget;
+
+  RenderNode get renderNode => _renderNode;
+
+  @override
+  void mount(Element parent) {
+    super.mount(parent);
+    _renderNode = widget.createRenderNode(this);
+    _attachToParent();
+    performRebuild();
+  }
+
+  void _attachToParent() {
+    Element ancestor = _parent;
+    while (ancestor != null && ancestor is! RenderNodeElement) {
+      ancestor = ancestor._parent;
+    }
+    if (ancestor is RenderNodeElement) {
+      ancestor.insertChildRenderNode(_renderNode);
+    }
+  }
+
+  void insertChildRenderNode(RenderNode child) {
+    final RenderNode node = _renderNode;
+    if (node is ContainerRenderNode) {
+      node.add(child);
+    } else if (node is RenderNodeWithChild) {
+      node.child = child;
+    }
+  }
+
+  @override
+  void update(RenderNodeWidget newWidget) {
+    super.update(newWidget);
+    widget.updateRenderNode(this, _renderNode);
+    _dirty = true;
+    rebuild();
+  }
+
+  @override
+  void performRebuild() {
+    final List<Widget> newWidgets = widget.children;
+    final List<Element> newChildren = <Element>[];
+    for (int i = 0; i < newWidgets.length; i += 1) {
+      final Element oldChild = i < _children.length ? _children[i] : null;
+      newChildren.add(updateChild(oldChild, newWidgets[i]));
+    }
+    for (int i = newWidgets.length; i < _children.length; i += 1) {
+      updateChild(_children[i], null);
+    }
+    _children = newChildren;
+    _dirty = false;
+  }
+
+  @override
+  void visitChildElements(void visitor(Element element)) {
+    _children.forEach(visitor);
+  }
+}
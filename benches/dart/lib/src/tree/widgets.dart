@@ -0,0 +1,350 @@
+/// Widgets, and the elements that keep track of them in the tree.
+library widgets;
+
+import '../foundation/diagnostics.dart';
+import '../foundation/observable.dart';
+import '../geometry/size.dart';
+import '../layout/constraints.dart';
+import '../layout/flex.dart';
+import '../layout/node.dart';
+import '../layout/stack.dart';
+
+part 'element.dart';
+
+/// Identifies a widget across rebuilds.
+class Key {
+  const Key(this.value);
+
+  final String value;
+
+  @override
+  bool operator ==(dynamic other) => other is Key && other.value == value;
+
+  @override
+  int get hashCode => value.hashCode;
+
+  @override
+  String toString() => "[<'$value'>]";
+}
+
+/// An immutable description of part of the interface.
+abstract class Widget extends DiagnosticableTree {
+  const Widget({this.key});
+
+  final Key key;
+
+  Element createElement();
+
+  /// Whether an element showing [oldWidget] can be updated to show
+  /// [newWidget] instead of being replaced.
+  static bool canUpdate(Widget oldWidget, Widget newWidget) {
+    return oldWidget.runtimeType == newWidget.runtimeType && oldWidget.key == newWidget.key;
+  }
+
+  @override
+  String toStringShort() => key == null ? '$runtimeType' : '$runtimeType-$key';
+}
+
+typedef Widget WidgetBuilder(BuildContext context);
+
+/// A widget whose interface depends only on its configuration.
+abstract class StatelessWidget extends Widget {
+  const StatelessWidget({Key key}) : super(key: key);
+
+  @override
+  StatelessElement createElement() => new StatelessElement(this);
+
+  Widget build(BuildContext context);
+}
+
+/// A widget with mutable [State].
+abstract class StatefulWidget extends Widget {
+  const StatefulWidget({Key key}) : super(key: key);
+
+  @override
+  StatefulElement createElement() => new StatefulElement(this);
+
+  State<StatefulWidget> createState();
+}
+
+/// The mutable state of a [StatefulWidget].
+abstract class State<T extends StatefulWidget> extends Diagnosticable {
+  T _widget;
+  StatefulElement _element;
+
+  T get widget => _widget;
+
+  BuildContext get context => _element;
+
+  bool get mounted => _element != null;
+
+  void initState() {}
+
+  void didUpdateWidget(covariant T oldWidget) {}
+
+  void didChangeDependencies() {}
+
+  void dispose() {}
+
+  /// Runs [fn] and schedules a rebuild.
+  void setState(VoidCallback fn) {
+    assert(mounted, 'setState() called after dispose(): $this');
+    fn();
+    _element.markNeedsBuild();
+  }
+
+  Widget build(BuildContext context);
+
+  @override
+  void debugFillProperties(DiagnosticPropertiesBuilder properties) {
+    super.debugFillProperties(properties);
+    properties.add(new DiagnosticsProperty<T>('widget', _widget));
+    properties.addFlag('mounted', mounted, ifFalse: 'not mounted');
+  }
+}
+
+/// Makes [data] available to every widget below it.
+abstract class InheritedWidget extends Widget {
+  const InheritedWidget({Key key, this.child}) : super(key: key);
+
+  final Widget child;
+
+  @override
+  InheritedElement createElement() => new InheritedElement(this);
+
+  bool updateShouldNotify(covariant InheritedWidget oldWidget);
+}
+
+/// A widget that configures a [RenderNode].
+abstract class RenderNodeWidget extends Widget {
+  const RenderNodeWidget({Key key}) : super(key: key);
+
+  List<Widget> get children => const <Widget>[];
+
+  @override
+  RenderNodeElement createElement() => new RenderNodeElement(this);
+
+  RenderNode createRenderNode(BuildContext context);
+
+  void updateRenderNode(BuildContext context, covariant RenderNode renderNode) {}
+}
+
+/// Shows an exception that was thrown while building.
+class ErrorWidget extends RenderNodeWidget {
+  ErrorWidget(Object exception)
+      : message = exception.toString(),
+        super(key: null);
+
+  final String message;
+
+  @override
+  RenderNode createRenderNode(BuildContext context) {
+    return new RenderColoredBox(color: 'red', label: message);
+  }
+}
+
+class ColoredBox extends RenderNodeWidget {
+  const ColoredBox({Key key, this.color: 'grey', this.label, this.size: Size.zero})
+      : super(key: key);
+
+  final String color;
+  final String label;
+  final Size size;
+
+  @override
+  RenderColoredBox createRenderNode(BuildContext context) {
+    return new RenderColoredBox(color: color, label: label, preferredSize: size);
+  }
+
+  @override
+  void updateRenderNode(BuildContext context, RenderColoredBox renderNode) {
+    renderNode
+      ..color = color
+      ..label = label
+      ..preferredSize = size;
+  }
+}
+
+class Flex extends RenderNodeWidget {
+  const Flex({
+    Key key,
+    this.direction: Axis.horizontal,
+    this.mainAxisAlignment: MainAxisAlignment.start,
+    this.crossAxisAlignment: CrossAxisAlignment.center,
+    this.children: const <Widget>[],
+  }) : super(key: key);
+
+  final Axis direction;
+  final MainAxisAlignment mainAxisAlignment;
+  final CrossAxisAlignment crossAxisAlignment;
+
+  @override
+  final List<Widget> children;
+
+  @override
+  RenderFlex createRenderNode(BuildContext context) {
+    return new RenderFlex(
+      direction: direction,
+      mainAxisAlignment: mainAxisAlignment,
+      crossAxisAlignment: crossAxisAlignment,
+    );
+  }
+
+  @override
+  void updateRenderNode(BuildContext context, RenderFlex renderNode) {
+    renderNode
+      ..direction = direction
+      ..mainAxisAlignment = mainAxisAlignment
+      ..crossAxisAlignment = crossAxisAlignment;
+  }
+}
+
+class Row extends Flex {
+  const Row({
+    Key key,
+    MainAxisAlignment mainAxisAlignment: MainAxisAlignment.start,
+    CrossAxisAlignment crossAxisAlignment: CrossAxisAlignment.center,
+    List<Widget> children: const <Widget>[],
+  }) : super(
+          key: key,
+          direction: Axis.horizontal,
+          mainAxisAlignment: mainAxisAlignment,
+          crossAxisAlignment: crossAxisAlignment,
+          children: children,
+        );
+}
+
+class Column extends Flex {
+  const Column({
+    Key key,
+    MainAxisAlignment mainAxisAlignment: MainAxisAlignment.start,
+    CrossAxisAlignment crossAxisAlignment: CrossAxisAlignment.center,
+    List<Widget> children: const <Widget>[],
+  }) : super(
+          key: key,
+          direction: Axis.vertical,
+          mainAxisAlignment: mainAxisAlignment,
+          crossAxisAlignment: crossAxisAlignment,
+          children: children,
+        );
+}
+
+class Stack extends RenderNodeWidget {
+  const Stack({
+    Key key,
+    this.alignment: Alignment.topLeft,
+    this.fit: StackFit.loose,
+    this.children: const <Widget>[],
+  }) : super(key: key);
+
+  final Alignment alignment;
+  final StackFit fit;
+
+  @override
+  final List<Widget> children;
+
+  @override
+  RenderStack createRenderNode(BuildContext context) {
+    return new RenderStack(alignment: alignment, fit: fit);
+  }
+
+  @override
+  void updateRenderNode(BuildContext context, RenderStack renderNode) {
+    renderNode
+      ..alignment = alignment
+      ..fit = fit;
+  }
+}
+
+/// Builds its child with a callback.
+class Builder extends StatelessWidget {
+  const Builder({Key key, this.builder}) : super(key: key);
+
+  final WidgetBuilder builder;
+
+  @override
+  Widget build(BuildContext context) => builder(context);
+}
+
+/// Rebuilds whenever [listenable] notifies its listeners.
+class ListenableBuilder extends StatefulWidget {
+  const ListenableBuilder({Key key, this.listenable, this.builder}) : super(key: key);
+
+  final Listenable listenable;
+  final WidgetBuilder builder;
+
+  @override
+  _ListenableBuilderState createState() => new _ListenableBuilderState();
+}
+
+class _ListenableBuilderState extends State<ListenableBuilder> {
+  @override
+  void initState() {
+    super.initState();
+    widget.listenable.addListener(_handleChange);
+  }
+
+  @override
+  void didUpdateWidget(ListenableBuilder oldWidget) {
+    super.didUpdateWidget(oldWidget);
+    if (widget.listenable != oldWidget.listenable) {
+      oldWidget.listenable.removeListener(_handleChange);
+      widget.listenable.addListener(_handleChange);
+    }
+  }
+
+  @override
+  void dispose() {
+    widget.listenable.removeListener(_handleChange);
+    super.dispose();
+  }
+
+  void _handleChange() {
+    setState(() {
+      // The listenable's state is our build state, and it changed already.
+    });
+  }
+
+  @override
+  Widget build(BuildContext context) => widget.builder(context);
+}
+
+/// Drives a [BuildOwner] and a [PipelineOwner] from a root widget.
+class WidgetTester {
+  WidgetTester(this.constraints) {
+    buildOwner = new BuildOwner();
+    pipelineOwner = new PipelineOwner();
+  }
+
+  final BoxConstraints constraints;
+  BuildOwner buildOwner;
+  PipelineOwner pipelineOwner;
+  Element _root;
+
+  Element get root => _root;
+
+  List<String> pump(Widget widget) {
+    if (_root == null) {
+      _root = widget.createElement();
+      _root._owner = buildOwner;
+      _root.mount(null);
+    } else if (Widget.canUpdate(_root.widget, widget)) {
+      _root.update(widget);
+    } else {
+      _root.unmount();
+      _root = null;
+      return pump(widget);
+    }
+    buildOwner.buildScope();
+    final RenderNode renderNode = _root.findRenderNode();
+    if (renderNode == null) {
+      return const <String>[];
+    }
+    if (!renderNode.attached) {
+      renderNode.attach(pipelineOwner);
+    }
+    renderNode.layout(constraints);
+    pipelineOwner.flushLayout();
+    return pipelineOwner.flushPaint();
+  }
+}
@@ -0,0 +1,234 @@
+import 'dart:collection';
+
+/// A value that can be compared with others of the same type.
+abstract class Ranked<T> implements Comparable<T> {
+  int get rank;
+
+  @override
+  int compareTo(covariant Ranked<T> other) => rank - other.rank;
+}
+
+/// A pair of values.
+class Pair<A, B> {
+  const Pair(this.first, this.second);
+
+  final A first;
+  final B second;
+
+  Pair<B, A> get swapped => new Pair<B, A>(second, first);
+
+  @override
+  bool operator ==(dynamic other) => other is Pair<A, B> && other.first == first && other.second == second;
+
+  @override
+  int get hashCode => first.hashCode * 31 + second.hashCode;
+
+  @override
+  String toString() => '($first, $second)';
+}
+
+/// A least-recently-used cache with a fixed capacity.
+class LruCache<K, V> {
+  LruCache(this.capacity) : assert(capacity > 0);
+
+  final int capacity;
+  final LinkedHashMap<K, V> _entries = new LinkedHashMap<K, V>();
+  int _hits = 0;
+  int _misses = 0;
+
+  int get length => _entries.length;
+
+  double get hitRate => _hits + _misses == 0 ? 0.0 : _hits / (_hits + _misses);
+
+  V operator [](K key) {
+    final V value = _entries.remove(key);
+    if (value == null) {
+      _misses += 1;
+      return null;
+    }
+    _hits += 1;
+    _entries[key] = value;
+    return value;
+  }
+
+  void operator []=(K key, V value) {
+    _entries.remove(key);
+    _entries[key] = value;
+    while (_entries.length > capacity) {
+      _entries.remove(_entries.keys.first);
+    }
+  }
+
+  V putIfAbsent(K key, V ifAbsent()) {
+    final V existing = this[key];
+    if (existing != null) {
+      return existing;
+    }
+    final V value = ifAbsent();
+    this[key] = value;
+    return value;
+  }
+
+  void clear() {
+    _entries.clear();
+  }
+}
+
+/// A min-heap ordered by a comparator.
+class PriorityQueue<E> extends IterableBase<E> {
+  PriorityQueue([int comparison(E a, E b)])
+      : _comparison = comparison ?? ((E a, E b) => (a as Comparable<dynamic>).compareTo(b));
+
+  final Comparator<E> _comparison;
+  final List<E> _heap = <E>[];
+
+  @override
+  int get length => _heap.length;
+
+  @override
+  bool get isEmpty => _heap.isEmpty;
+
+  @override
+  Iterator<E> get iterator => (_heap.toList()..sort(_comparison)).iterator;
+
+  E get first => _heap.first;
+
+  void add(E element) {
+    _heap.add(element);
+    _siftUp(_heap.length - 1);
+  }
+
+  E removeFirst() {
+    final E result = _heap.first;
+    final E last = _heap.removeLast();
+    if (_heap.isNotEmpty) {
+      _heap[0] = last;
+      _siftDown(0);
+    }
+    return result;
+  }
+
+  void _siftUp(int index) {
+    while (index > 0) {
+      final int parent = (index - 1) ~/ 2;
+      if (_comparison(_heap[index], _heap[parent]) >= 0) {
+        return;
+      }
+      _swap(index, parent);
+      index = parent;
+    }
+  }
+
+  void _siftDown(int index) {
+    while (true) {
+      final int left = index * 2 + 1;
+      final int right = left + 1;
+      int smallest = index;
+      if (left < _heap.length && _comparison(_heap[left], _heap[smallest]) < 0) {
+        smallest = left;
+      }
+      if (right < _heap.length && _comparison(_heap[right], _heap[smallest]) < 0) {
+        smallest = right;
+      }
+      if (smallest == index) {
+        return;
+      }
+      _swap(index, smallest);
+      index = smallest;
+    }
+  }
+
+  void _swap(int i, int j) {
+    final E tmp = _heap[i];
+    _heap[i] = _heap[j];
+    _heap[j] = tmp;
+  }
+}
+
+/// Counts how often each distinct element was added.
+class Multiset<E> extends IterableBase<E> with _Counting<E> {
+  @override
+  Iterator<E> get iterator => _counts.keys.iterator;
+}
+
+abstract class _Counting<E> {
+  final Map<E, int> _counts = <E, int>{};
+
+  void add(E element) {
+    _counts[element] = (_counts[element] ?? 0) + 1;
+  }
+
+  bool remove(E element) {
+    final int count = _counts[element];
+    if (count == null) {
+      return false;
+    }
+    if (count == 1) {
+      _counts.remove(element);
+    } else {
+      _counts[element] = count - 1;
+    }
+    return true;
+  }
+
+  int countOf(E element) => _counts[element] ?? 0;
+}
+
+extension IterableExtensions<E> on Iterable<E> {
+  /// The first element satisfying [test], or `null`.
+  E firstWhereOrNull(bool test(E element)) {
+    for (E element in this) {
+      if (test(element)) {
+        return element;
+      }
+    }
+    return null;
+  }
+
+  /// Groups elements by the key [keyOf] returns for them.
+  Map<K, List<E>> groupBy<K>(K keyOf(E element)) {
+    final Map<K, List<E>> result = <K, List<E>>{};
+    for (E element in this) {
+      result.putIfAbsent(keyOf(element), () => <E>[]).add(element);
+    }
+    return result;
+  }
+
+  Iterable<Pair<int, E>> get indexed sync* {
+    int index = 0;
+    for (E element in this) {
+      yield new Pair<int, E>(index, element);
+      index += 1;
+    }
+  }
+
+  Iterable<List<E>> chunked(int size) sync* {
+    List<E> chunk = <E>[];
+    for (E element in this) {
+      chunk.add(element);
+      if (chunk.length == size) {
+        yield chunk;
+        chunk = <E>[];
+      }
+    }
+    if (chunk.isNotEmpty) {
+      yield chunk;
+    }
+  }
+}
+
+extension NumIterableExtensions on Iterable<num> {
+  num get sum => fold(0, (num total, num value) => total + value);
+
+  double get average => isEmpty ? 0.0 : sum / length;
+}
+
+extension MapExtensions<K, V> on Map<K, V> {
+  Map<V, K> get inverted {
+    final Map<V, K> result = <V, K>{};
+    forEach((K key, V value) {
+      result[value] = key;
+    });
+    return result;
+  }
+}
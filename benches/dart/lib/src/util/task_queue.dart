@@ -0,0 +1,140 @@
+import 'dart:async';
+import 'dart:collection';
+
+import '../foundation/observable.dart';
+
+typedef Future<T> Task<T>();
+
+class _QueuedTask<T> {
+  _QueuedTask(this.task, this.completer);
+
+  final Task<T> task;
+  final Completer<T> completer;
+
+  Future<void> run() async {
+    try {
+      completer.complete(await task());
+    } catch (error, stackTrace) {
+      completer.completeError(error, stackTrace);
+    }
+  }
+}
+
+/// Runs asynchronous tasks, at most [concurrency] of them at a time, in the
+/// order they were added.
+class TaskQueue extends ChangeNotifier {
+  TaskQueue({this.concurrency: 1}) : assert(concurrency > 0);
+
+  final int concurrency;
+  final Queue<_QueuedTask<dynamic>> _pending = new Queue<_QueuedTask<dynamic>>();
+  int _running = 0;
+  int _completed = 0;
+  Completer<void> _idle;
+
+  int get pending => _pending.length;
+
+  int get running => _running;
+
+  int get completed => _completed;
+
+  bool get isIdle => _pending.isEmpty && _running == 0;
+
+  /// Adds [task] to the queue; the returned future completes with its result.
+  Future<T> add<T>(Task<T> task) {
+    final Completer<T> completer = new Completer<T>();
+    _pending.add(new _QueuedTask<T>(task, completer));
+    _pump();
+    return completer.future;
+  }
+
+  /// Adds all [tasks] and waits for every one of them.
+  Future<List<T>> addAll<T>(Iterable<Task<T>> tasks) {
+    return Future.wait(tasks.map((Task<T> task) => add(task)).toList());
+  }
+
+  /// Completes once the queue has no more work.
+  Future<void> get whenIdle {
+    if (isIdle) {
+      return new Future<void>.value();
+    }
+    _idle ??= new Completer<void>();
+    return _idle.future;
+  }
+
+  void _pump() {
+    while (_running < concurrency && _pending.isNotEmpty) {
+      final _QueuedTask<dynamic> next = _pending.removeFirst();
+      _running += 1;
+      _runTask(next);
+    }
+    if (isIdle && _idle != null) {
+      final Completer<void> idle = _idle;
+      _idle = null;
+      idle.complete();
+    }
+  }
+
+  Future<void> _runTask(_QueuedTask<dynamic> task) async {
+    await task.run();
+    _running -= 1;
+    _completed += 1;
+    notifyListeners();
+    _pump();
+  }
+}
+
+/// Delays calls to [action] until [duration] has passed without another
+/// call.
+class Debouncer {
+  Debouncer(this.duration, this.action);
+
+  final Duration duration;
+  final VoidCallback action;
+  Timer _timer;
+
+  bool get isPending => _timer?.isActive ?? false;
+
+  void call() {
+    _timer?.cancel();
+    _timer = new Timer(duration, action);
+  }
+
+  void flush() {
+    if (isPending) {
+      _timer.cancel();
+      _timer = null;
+      action();
+    }
+  }
+
+  void cancel() {
+    _timer?.cancel();
+    _timer = null;
+  }
+}
+
+/// Retries [task] with exponential backoff until it succeeds or
+/// [maxAttempts] are used up.
+Future<T> retry<T>(Task<T> task, {int maxAttempts: 3, Duration delay: const Duration(milliseconds: 10)}) async {
+  int attempt = 0;
+  while (true) {
+    attempt += 1;
+    try {
+      return await task();
+    } catch (error) {
+      if (attempt >= maxAttempts) {
+        rethrow;
+      }
+      await new Future<void>.delayed(delay * (1 << (attempt - 1)));
+    }
+  }
+}
+
+/// Emits the running total of the numbers in [values].
+Stream<num> runningTotal(Stream<num> values) async* {
+  num total = 0;
+  await for (num value in values) {
+    total += value;
+    yield total;
+  }
+}
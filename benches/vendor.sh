@@ -0,0 +1,42 @@
+#!/bin/sh
+# Snapshots a few of the largest Flutter framework files into
+# benches/flutter, along with Flutter's LICENSE, keeping their layout under
+# lib/, for `cargo run --release --example dart-bench -- benches/flutter`.
+# The snapshot is meant to be committed, so that the bench measures real
+# framework code on a fresh clone, offline.
+#
+# The files come from the revision lyken pins (`FLUTTER_REPO_REV` in
+# src/dart/sdk.rs), taken from lyken's cached checkout if there is one, or
+# else cloned.
+#
+# The snapshot lacks most of what its files import, so resolving is only
+# measured on benches/dart, a smaller package written for the bench.
+set -e
+
+repo=https://github.com/lykenware/flutter
+rev=$(sed -n 's/^const FLUTTER_REPO_REV: &str = "\(.*\)";$/\1/p' "$(dirname "$0")/../src/dart/sdk.rs")
+
+files="
+lib/src/widgets/framework.dart
+lib/src/rendering/object.dart
+lib/src/rendering/box.dart
+lib/src/material/theme_data.dart
+lib/src/painting/text_style.dart
+"
+
+root="${XDG_CACHE_HOME:-$HOME/.cache}/lyken/flutter"
+if [ ! -d "$root/packages/flutter/lib" ]; then
+    root=$(mktemp -d)
+    trap 'rm -rf "$root"' EXIT
+    git clone --quiet --no-checkout "$repo" "$root"
+    git -C "$root" checkout --quiet "$rev"
+fi
+
+dest="$(dirname "$0")/flutter"
+rm -rf "$dest"
+mkdir -p "$dest"
+cp "$root/LICENSE" "$dest/"
+for file in $files; do
+    mkdir -p "$dest/$(dirname "$file")"
+    cp "$root/packages/flutter/$file" "$dest/$file"
+done
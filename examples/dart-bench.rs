@@ -0,0 +1,187 @@
+#![feature(rustc_private)]
+
+extern crate lyken;
+extern crate walkdir;
+
+use lyken::dart::ast::{Item, Module};
+use lyken::dart::lex::Lexer;
+use lyken::dart::parse::Parser;
+use lyken::dart::print::Printer;
+use lyken::dart::resolve;
+use lyken::dart::summary;
+use std::env;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::Instant;
+use walkdir::WalkDir;
+
+const ITERATIONS: u32 = 5;
+
+// Usage: dart-bench [DIR] [--resolve]
+//
+// DIR defaults to `benches/dart`, a small package committed with the
+// repository; `benches/flutter` holds a snapshot of real Flutter framework
+// files, taken by `benches/vendor.sh`. Resolving needs the Dart SDK from
+// lyken's Flutter checkout, so it's only measured with `--resolve`.
+// Parsing generated, deeply nested components is always measured.
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let with_resolve = args.iter().any(|arg| arg == "--resolve");
+    let dir = args.iter()
+        .find(|arg| !arg.starts_with("--"))
+        .cloned()
+        .unwrap_or_else(|| "benches/dart".to_string());
+    let paths = WalkDir::new(&dir)
+        .into_iter()
+        .map(|entry| entry.unwrap().path().to_path_buf())
+        .filter(|path| path.extension().map_or(false, |x| x == "dart"))
+        .collect::<Vec<_>>();
+    if paths.is_empty() {
+        println!("{}: no .dart files, see benches/vendor.sh", dir);
+    } else {
+        bench_files(paths, with_resolve);
+    }
+    lyken::with_globals(bench_nesting);
+}
+
+fn bench_files(paths: Vec<PathBuf>, with_resolve: bool) {
+    let bytes: u64 = paths.iter().map(|path| path.metadata().unwrap().len()).sum();
+    println!("{} files, {} kB, best of {} runs", paths.len(), bytes / 1024, ITERATIONS);
+
+    lyken::with_globals(|| {
+        let files = paths
+            .iter()
+            .map(|path| {
+                let file = lyken::codemap().load_file(path).unwrap();
+                (path, lyken::mk_sp(file.start_pos, file.end_pos))
+            })
+            .collect::<Vec<_>>();
+        measure("lex", bytes, || for &(_, span) in &files {
+            Lexer::new(span).tokenize().unwrap();
+        });
+
+        let tokens = files
+            .iter()
+            .map(|&(path, span)| (path, Lexer::new(span).tokenize().unwrap()))
+            .collect::<Vec<_>>();
+        measure("parse", bytes, || for &(path, ref tokens) in &tokens {
+            Parser::new(path, tokens).dart_module().unwrap();
+        });
+
+        let modules = tokens
+            .iter()
+            .map(|&(path, ref tokens)| Parser::new(path, tokens).dart_module().unwrap())
+            .collect::<Vec<_>>();
+        measure("print", bytes, || for module in &modules {
+            Printer::new().dart_items(&module.items);
+        });
+    });
+
+    if with_resolve {
+        // The `Module::load` cache is per-thread, so a new thread starts cold.
+        // It also gets an empty summary directory of its own, as summaries
+        // stored by an earlier run would make it start warm.
+        let summaries = env::temp_dir().join(format!("lyken-bench-{}", process::id()));
+        let mut run = 0;
+        measure("resolve (cold)", bytes, || {
+            run += 1;
+            let dir = summaries.join(run.to_string());
+            let paths = paths.clone();
+            thread::spawn(move || {
+                summary::set_dir(dir);
+                lyken::with_globals(|| resolve_all(&paths))
+            }).join()
+                .unwrap();
+        });
+        let _ = fs::remove_dir_all(&summaries);
+        thread::spawn(move || {
+            lyken::with_globals(|| {
+                resolve_all(&paths);
+                measure("resolve (warm)", bytes, || resolve_all(&paths));
+            })
+        }).join()
+            .unwrap();
+    }
+}
+
+/// Parses components nested ever deeper. The throughput should stay the
+/// same at every depth, unless backtracking makes parsing superlinear.
+fn bench_nesting() {
+    for &depth in &[8, 16, 32, 64, 128] {
+        let src = nested_component(depth);
+        let tokens = Lexer::from_string(src.clone()).tokenize().unwrap();
+        measure(&format!("nested .lyk {}", depth), src.len() as u64, || {
+            Parser::new(Path::new("nested.lyk"), &tokens)
+                .dsl_items()
+                .unwrap();
+        });
+    }
+}
+
+fn nested_component(depth: usize) -> String {
+    let mut body = "Text('leaf')".to_string();
+    for i in 0..depth {
+        body = match i % 3 {
+            0 => format!("Padding {{ child: {} }}", body),
+            1 => format!("Column {{ children: [Text('{}'), {}] }}", i, body),
+            _ => format!("Opacity(opacity) {{ child: {} }}", body),
+        };
+    }
+    format!("def Nested {{\n  opacity: int = 1,\n\n  ..{}\n}}\n", body)
+}
+
+fn resolve_all(paths: &[PathBuf]) {
+    'outer: for path in paths {
+        let module = Module::load(path);
+        for item in &module.items {
+            if let Item::PartOf { .. } = **item {
+                continue 'outer;
+            }
+        }
+        resolve::resolve(module, true);
+    }
+}
+
+/// Runs `f` a few times, printing the best time, the throughput over
+/// `bytes` of source and the peak memory use while running it.
+fn measure<F: FnMut()>(stage: &str, bytes: u64, mut f: F) {
+    reset_peak_memory();
+    let mut best = None;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        f();
+        let elapsed = start.elapsed();
+        let secs = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+        best = Some(best.map_or(secs, |best: f64| best.min(secs)));
+    }
+    let best = best.unwrap();
+    let peak = peak_memory().map_or("?".to_string(), |kb| kb.to_string());
+    println!(
+        "{:<16} {:>10.2} ms {:>10.2} MB/s {:>10} kB peak",
+        stage,
+        best * 1000.0,
+        bytes as f64 / best / (1024.0 * 1024.0),
+        peak
+    );
+}
+
+/// Returns the peak resident memory of the process, in kB (Linux only).
+fn peak_memory() -> Option<u64> {
+    let mut status = String::new();
+    File::open("/proc/self/status")
+        .ok()?
+        .read_to_string(&mut status)
+        .ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    line["VmHWM:".len()..].trim().trim_right_matches("kB").trim().parse().ok()
+}
+
+/// Resets the peak measured by `peak_memory`, where supported.
+fn reset_peak_memory() {
+    if let Ok(mut file) = File::create("/proc/self/clear_refs") {
+        let _ = file.write_all(b"5");
+    }
+}
@@ -6,9 +6,10 @@ extern crate lyken;
 
 use walkdir::WalkDir;
 use difference::Changeset;
-use lyken::dart::lex::Lexer;
+use lyken::dart::lex::{Lexer, TextEdit};
 use std::env;
 use std::fmt::Write;
+use std::rc::Rc;
 
 fn main() { lyken::with_globals(lyken_main) }
 fn lyken_main() {
@@ -27,6 +28,7 @@ fn lyken_main() {
                         let diff = Changeset::new(&src, &result, " ");
                         println!("{} {}", entry.path().display(), diff);
                     }
+                    check_relex(&entry.path().display().to_string(), &src);
                 }
                 Err(error) => {
                     println!("{}", error);
@@ -35,3 +37,30 @@ fn lyken_main() {
         }
     }
 }
+
+/// Checks that relexing after an edit in the middle of `src` gives the
+/// same tokens as lexing the edited text from scratch.
+fn check_relex(name: &str, src: &str) {
+    let mut start = src.len() / 2;
+    while !src.is_char_boundary(start) {
+        start += 1;
+    }
+    let inserted = " foo(/* */ 'bar') ";
+    let mut edited = src.to_string();
+    edited.insert_str(start, inserted);
+    let edited = Rc::new(edited);
+
+    let old_tokens = match Lexer::detached(Rc::new(src.to_string())).tokenize() {
+        Ok(tokens) => tokens,
+        Err(_) => return,
+    };
+    let edit = TextEdit {
+        range: start..start,
+        new_len: inserted.len(),
+    };
+    let relexed = Lexer::relex(edited.clone(), &old_tokens, &edit).ok();
+    let lexed = Lexer::detached(edited).tokenize().ok();
+    if relexed != lexed {
+        println!("{}: relexing after an edit at byte {} differs", name, start);
+    }
+}
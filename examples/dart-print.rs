@@ -1,16 +1,17 @@
-#![feature(rustc_private)]
 extern crate lyken;
-extern crate syntax_pos;
 extern crate walkdir;
 
 use std::env;
-use lyken::dart::lex::{Lexer, Token};
+use std::process;
+use lyken::dart::equiv::modules_equivalent;
+use lyken::dart::lex::Lexer;
 use lyken::dart::parse::Parser;
 use lyken::dart::print::Printer;
 use walkdir::WalkDir;
 
 fn main() { lyken::with_globals(lyken_main) }
 fn lyken_main() {
+    let mut failed = false;
     for entry in WalkDir::new(env::args().nth(1).unwrap()) {
         let entry = entry.unwrap();
         if entry.path().extension().map_or(false, |x| x == "dart") {
@@ -18,56 +19,17 @@ fn lyken_main() {
             match Parser::new(entry.path(), &tokens).dart_module() {
                 Ok(module) => {
                     let result = Printer::new().dart_items(&module.items);
-                    let fm = lyken::codemap().new_filemap(syntax_pos::FileName::Anon, result);
-                    let result = Lexer::new(lyken::mk_sp(fm.start_pos, fm.end_pos))
-                        .tokenize()
-                        .unwrap();
-
-                    fn whitespace_not_comment(token: &Token) -> bool {
-                        match *token {
-                            Token::WhiteSpace(_) => true,
-                            _ => false,
-                        }
-                    }
-
-                    let mut old = tokens
-                        .iter()
-                        .filter(|&&(_, token)| !whitespace_not_comment(&token));
-                    let mut new = result
-                        .iter()
-                        .filter(|&&(_, token)| !whitespace_not_comment(&token));
-                    loop {
-                        let (old, new) = match (old.next(), new.next()) {
-                            (
-                                Some(&(_, Token::Punctuation(','))),
-                                new @ Some(&(_, Token::Punctuation(')'))),
-                            ) |
-                            (
-                                Some(&(_, Token::Punctuation(','))),
-                                new @ Some(&(_, Token::Punctuation(']'))),
-                            ) |
-                            (
-                                Some(&(_, Token::Punctuation(','))),
-                                new @ Some(&(_, Token::Punctuation('}'))),
-                            ) => (old.next(), new),
-                            x => x,
-                        };
-                        match (old, new) {
-                            (Some(&(span, old)), Some(&(_, new))) => {
-                                if old.to_string() != new.to_string() {
-                                    println!("{:?}: `{}` != `{}`", span, old, new);
-                                    break;
-                                }
+                    let tokens = Lexer::from_string(result).tokenize().unwrap();
+                    match Parser::new(entry.path(), &tokens).dart_module() {
+                        Ok(printed) => {
+                            if let Err(divergence) = modules_equivalent(&module, &printed) {
+                                println!("{}: {}", entry.path().display(), divergence);
+                                failed = true;
                             }
-                            (Some(&(span, old)), None) => {
-                                println!("{:?}: `{}` missing", span, old);
-                                break;
-                            }
-                            (None, Some(&(_, new))) => {
-                                println!("{}: `{}` extraneous", entry.path().display(), new);
-                                break;
-                            }
-                            (None, None) => break,
+                        }
+                        Err(error) => {
+                            println!("{}: printed code doesn't parse: {}", entry.path().display(), error);
+                            failed = true;
                         }
                     }
                 }
@@ -77,4 +39,7 @@ fn lyken_main() {
             }
         }
     }
+    if failed {
+        process::exit(1);
+    }
 }
@@ -8,6 +8,7 @@ use std::env;
 
 fn main() { lyken::with_globals(lyken_main) }
 fn lyken_main() {
+    let mut resolved = true;
     'outer: for entry in WalkDir::new(env::args().nth(1).unwrap()) {
         let entry = entry.unwrap();
         if entry.path().extension().map_or(false, |x| x == "dart") {
@@ -17,7 +18,10 @@ fn lyken_main() {
                     continue 'outer;
                 }
             }
-            resolve::resolve(module, true)
+            resolved &= resolve::resolve(module, true);
         }
     }
+    if !resolved {
+        std::process::exit(1);
+    }
 }
@@ -13,14 +13,14 @@ fn main() { lyken::with_globals(lyken_main) }
 fn lyken_main() {
     let path = PathBuf::from(env::args().nth(1).unwrap());
     let module = Module::load(&path);
-    resolve::resolve(module.clone(), true);
+    let resolved = resolve::resolve(module.clone(), true);
     let code = Lifter::new().lift_items(&module.items);
     let result = Printer::new().dsl_items(&code);
     File::create(path.with_extension("lyk"))
         .unwrap()
         .write_all(result.as_bytes())
         .unwrap();
-    if module.has_error {
+    if module.has_error || !resolved {
         std::process::exit(1);
     }
 }
@@ -6,21 +6,48 @@ use std::fs::File;
 use std::io::prelude::*;
 use lyken::dart::parse::Parser;
 use lyken::dart::print::Printer;
+use lyken::dart::sdk::{self, Target};
 use lyken::dsl::lower::Lowerer;
 use lyken::dsl::resolve;
 
+// Usage: dsl-lower [--target native|web] FILE
 fn main() { lyken::with_globals(lyken_main) }
 fn lyken_main() {
-    let path = PathBuf::from(env::args().nth(1).unwrap());
-    match Parser::with_file(&path, |mut p| p.dsl_items()) {
-        Ok(items) => {
-            resolve::resolve(&items, true);
-            let code = Lowerer::new().lower_items(&items);
-            let result = Printer::new().dart_items(&code);
-            File::create(path.with_extension("dart"))
+    let mut args = env::args().skip(1);
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        if arg == "--target" {
+            match args.next().and_then(|name| Target::from_name(&name)) {
+                Some(target) => sdk::set_target(target),
+                None => {
+                    println!("expected native or web after --target");
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            path = Some(PathBuf::from(arg));
+        }
+    }
+    let path = path.unwrap();
+    match Parser::with_file(&path, |p| p.dsl_module()) {
+        Ok(module) => {
+            let resolved = resolve::resolve(&module.items, true);
+            let mut lowerer = Lowerer::new();
+            let code = lowerer.lower_items(&module.items);
+            if lowerer.has_error || !resolved {
+                std::process::exit(1);
+            }
+            let (result, source_map) = Printer::new().dart_items_with_source_map(&code);
+            let dart_path = path.with_extension("dart");
+            File::create(&dart_path)
                 .unwrap()
                 .write_all(result.as_bytes())
                 .unwrap();
+            let dart_name = dart_path.file_name().unwrap().to_string_lossy();
+            File::create(path.with_extension("dart.map"))
+                .unwrap()
+                .write_all(source_map.to_json(&dart_name).as_bytes())
+                .unwrap();
         }
         Err(error) => {
             println!("{}", error);
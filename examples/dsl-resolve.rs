@@ -1,22 +1,227 @@
 extern crate lyken;
 extern crate walkdir;
 
+use lyken::dart;
+use lyken::dart::ast::{ClassMember, Expr, FnName, Function, Item, Module, Suffix};
 use lyken::dart::parse::Parser;
-use walkdir::WalkDir;
-use lyken::dsl::resolve;
+use lyken::dart::resolve::{self, CallBinding, Res};
+use lyken::dart::sdk::{self, Target};
+use lyken::dart::visit::Visit as DartVisit;
+use lyken::dsl;
+use lyken::dsl::lower::config_names;
+use lyken::dsl::visit::Visit;
+use lyken::node::Node;
+use std::any::Any;
 use std::env;
+use std::fmt::Display;
+use walkdir::WalkDir;
 
+// Usage: dsl-resolve [--target native|web] PATH
+//
+// Resolves the .lyk and .dart modules at PATH, printing what each member
+// access resolved to (`widget.title` -> Counter.title) and how the
+// arguments of each call bound to its parameters. Parts are resolved in
+// the scope of the library they belong to. Exits with an error if any
+// name was unknown or ambiguous.
 fn main() { lyken::with_globals(lyken_main) }
 fn lyken_main() {
-    for entry in WalkDir::new(env::args().nth(1).unwrap()) {
+    let mut args = env::args().skip(1);
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        if arg == "--target" {
+            match args.next().and_then(|name| Target::from_name(&name)) {
+                Some(target) => sdk::set_target(target),
+                None => {
+                    println!("expected native or web after --target");
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            path = Some(arg);
+        }
+    }
+
+    let mut resolved = true;
+    for entry in WalkDir::new(path.unwrap()) {
         let entry = entry.unwrap();
-        if entry.path().extension().map_or(false, |x| x == "lyk") {
-            match Parser::with_file(entry.path(), |mut p| p.dsl_items()) {
-                Ok(items) => resolve::resolve(&items, true),
+        let extension = entry.path().extension().and_then(|x| x.to_str());
+        if extension == Some("lyk") {
+            match Parser::with_file(entry.path(), |p| p.dsl_module()) {
+                Ok(module) => {
+                    resolved &= dsl::resolve::resolve(&module.items, true);
+                    module.items.super_visit(&mut Printer);
+                }
                 Err(error) => {
                     println!("{}", error);
                 }
             }
+        } else if extension == Some("dart") {
+            let module = Module::load(entry.path());
+            resolved &= resolve::resolve(module.clone(), true);
+            module.super_visit(&mut Printer);
+        }
+    }
+    if !resolved {
+        std::process::exit(1);
+    }
+}
+
+struct Printer;
+
+impl dart::visit::Visitor for Printer {
+    fn dart_expr(&mut self, expr: Node<Expr>) {
+        match *expr {
+            Expr::Suffix(_, Suffix::Field(_)) | Expr::Suffix(_, Suffix::FieldIfNotNull(_)) => {
+                let res = expr.res().get().map_or("unknown".to_string(), |res| describe(&res));
+                println!("`{}` -> {}", snippet(&expr), res);
+            }
+            _ => {}
+        }
+        match *expr {
+            Expr::New { ref args, .. } | Expr::Suffix(_, Suffix::Call(_, ref args)) => {
+                if let Some(binding) = expr.binding().get() {
+                    let named: Vec<_> = args.named.iter().map(|arg| arg.name).collect();
+                    println!("`{}` binds {}", snippet(&expr), describe_binding(&binding, &named));
+                }
+            }
+            _ => {}
+        }
+        expr.super_visit(self)
+    }
+}
+
+impl dsl::visit::Visitor for Printer {
+    fn dsl_expr(&mut self, expr: Node<dsl::ast::Expr>) {
+        if let dsl::ast::Expr::Instance { ref config, .. } = *expr {
+            if let Some(binding) = expr.binding().get() {
+                let mut named = vec![];
+                for config in config {
+                    config_names(config, &mut named);
+                }
+                println!("`{}` binds {}", snippet(&expr), describe_binding(&binding, &named));
+            }
+        }
+        expr.super_visit(self)
+    }
+}
+
+/// Returns the source of `node`, on a single line.
+fn snippet<T: ?Sized>(node: &Node<T>) -> String {
+    let span = match node.span().get() {
+        Some(span) => span,
+        None => return "?".to_string(),
+    };
+    let source = lyken::codemap().span_to_snippet(span.to_span()).unwrap();
+    source.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Describes `res` as its owner's name and its own (`Counter.title`).
+fn describe(res: &Res) -> String {
+    let (node, name): (Node<Any>, String) = match *res {
+        Res::Var(ref var) => (var.clone() as Node<Any>, var.name.to_string()),
+        Res::Function(ref function) |
+        Res::Getter(ref function) |
+        Res::Setter(ref function) |
+        Res::GetterSetter(ref function, _) => {
+            (function.clone() as Node<Any>, function_name(function))
+        }
+        Res::Constructor(ref constructor) => {
+            let name = match **constructor {
+                ClassMember::Constructor { name, .. } | ClassMember::Redirect { name, .. } => {
+                    name.map_or("new".to_string(), |name| name.to_string())
+                }
+                _ => "?".to_string(),
+            };
+            (constructor.clone() as Node<Any>, name)
+        }
+        Res::Class(ref item) | Res::Enum(ref item) | Res::TypeAlias(ref item) => {
+            return item_name(item).unwrap_or_else(|| "?".to_string());
+        }
+        Res::Dsl(dsl::resolve::Res::Field(ref field)) => {
+            (field.clone() as Node<Any>, field.name.to_string())
+        }
+        Res::Dsl(dsl::resolve::Res::Component(ref item)) => {
+            if let dsl::ast::Item::ComponentDef { name, .. } = **item {
+                return name.to_string();
+            }
+            return "?".to_string();
+        }
+        Res::EnumValue(ref item, name) => {
+            return format!("{}.{}", item_name(item).unwrap_or_else(|| "?".to_string()), name);
+        }
+        Res::EnumValues(ref item) => {
+            return format!("{}.values", item_name(item).unwrap_or_else(|| "?".to_string()));
+        }
+        Res::Error => return "unknown".to_string(),
+        Res::Ambiguous(_) => return "ambiguous".to_string(),
+        ref res => return format!("{:?}", res),
+    };
+    match owner_name(&node) {
+        Some(owner) => format!("{}.{}", owner, name),
+        None => name,
+    }
+}
+
+/// Describes which parameter each argument binds to (`#0 -> data`,
+/// `color -> color`, `extra`), `named` being the names of the named ones,
+/// and which required parameters are missing.
+fn describe_binding<N: Display>(binding: &CallBinding, named: &[N]) -> String {
+    let param = |param: &Option<Res>| param.as_ref().map_or("extra".to_string(), param_name);
+    let mut parts = vec![];
+    for (i, unnamed) in binding.unnamed.iter().enumerate() {
+        parts.push(format!("#{} -> {}", i, param(unnamed)));
+    }
+    for (name, param_res) in named.iter().zip(&binding.named) {
+        parts.push(format!("{} -> {}", name, param(param_res)));
+    }
+    for missing in &binding.missing {
+        parts.push(format!("missing {}", param_name(missing)));
+    }
+    parts.join(", ")
+}
+
+/// Returns the name of `param`, a parameter or a component field.
+fn param_name(param: &Res) -> String {
+    match *param {
+        Res::Var(ref var) => var.name.to_string(),
+        Res::Dsl(dsl::resolve::Res::Field(ref field)) => field.name.to_string(),
+        _ => "?".to_string(),
+    }
+}
+
+fn function_name(function: &Function) -> String {
+    match function.name {
+        FnName::Regular(name) | FnName::Getter(name) | FnName::Setter(name) => name.to_string(),
+        FnName::Operator(op) => format!("operator {:?}", op),
+    }
+}
+
+fn item_name(item: &Item) -> Option<String> {
+    match *item {
+        Item::Class { name, .. } |
+        Item::MixinClass { name, .. } |
+        Item::Mixin { name, .. } |
+        Item::Enum { name, .. } |
+        Item::TypeAlias { name, .. } => Some(name.to_string()),
+        Item::Extension { name, .. } => name.map(|name| name.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the name of the class or component `node` is declared in.
+fn owner_name(node: &Node<Any>) -> Option<String> {
+    let mut parent = node.parent_any().get();
+    while let Some(node) = parent {
+        if let Some(item) = node.downcast::<Item>() {
+            return item_name(&item);
+        }
+        if let Some(item) = node.downcast::<dsl::ast::Item>() {
+            if let dsl::ast::Item::ComponentDef { name, .. } = *item {
+                return Some(name.to_string());
+            }
+            return None;
         }
+        parent = node.parent_any().get();
     }
+    None
 }
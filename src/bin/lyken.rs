@@ -1,28 +1,63 @@
 #[macro_use]
 extern crate clap;
+extern crate difference;
 extern crate lyken;
 extern crate mktemp;
+extern crate walkdir;
 
+use difference::Changeset;
 use mktemp::Temp;
 use std::path::PathBuf;
 use std::process::{self, Stdio};
 use std::fs::{self, File};
+use std::io::BufReader;
 use std::io::prelude::*;
+use walkdir::WalkDir;
+use lyken::dart;
+use lyken::dart::ast::{Item, Module};
+use lyken::dart::equiv;
+use lyken::dart::lex::Lexer;
 use lyken::dart::parse::Parser;
 use lyken::dart::print::Printer;
+use lyken::dsl;
+use lyken::dsl::highlight::{self, Highlight};
+use lyken::dsl::lift::Lifter;
 use lyken::dsl::lower::Lowerer;
+use lyken::dsl::resolve;
 
 fn main() { lyken::with_globals(lyken_main) }
 fn lyken_main() {
     let matches = clap_app!(lyken =>
+        (@arg target: --target +takes_value "native (default) or web, for configurable imports")
         (@subcommand reformat =>
             (@arg FILE: +required)
         )
         (@subcommand run =>
             (@arg FILE: +required)
         )
+        (@subcommand highlight =>
+            (about: "Prints a .lyk file with syntax highlighting")
+            (@arg FILE: +required)
+            (@arg format: --format +takes_value "ansi (default), html or lsp")
+        )
+        (@subcommand lift =>
+            (about: "Migrates the Dart files of a directory to components")
+            (@arg DIR: +required)
+            (@arg dry_run: --("dry-run") "Prints the changes instead of writing .lyk files")
+            (@arg verify: --verify "Checks that lowering the result gives back the same Dart")
+        )
     ).get_matches();
 
+    if let Some(name) = matches.value_of("target") {
+        match lyken::dart::sdk::Target::from_name(name) {
+            Some(target) => lyken::dart::sdk::set_target(target),
+            None => {
+                println!("unknown target `{}`, expected native or web", name);
+                process::exit(1);
+            }
+        }
+    }
+
     if let Some(matches) = matches.subcommand_matches("reformat") {
         let path = PathBuf::from(matches.value_of("FILE").unwrap());
         match Parser::with_file(&path, |mut p| p.dsl_items()) {
@@ -53,28 +88,177 @@ fn lyken_main() {
                 process::exit(1);
             }
         });
-        match Parser::with_file(&path, |mut p| p.dsl_items()) {
-            Ok(items) => {
-                let code = Lowerer::new().lower_items(&items);
-                let result = Printer::new().dart_items(&code);
+        let source_map = match Parser::with_file(&path, |p| p.dsl_module()) {
+            Ok(dsl_module) => {
+                let resolved = resolve::resolve(&dsl_module.items, true);
+                let mut lowerer = Lowerer::new();
+                let code = lowerer.lower_items(&dsl_module.items);
+                if lowerer.has_error || !resolved {
+                    std::process::exit(1);
+                }
+                let (result, source_map) = Printer::new().dart_items_with_source_map(&code);
                 File::create(temp_dir.join("lib/main.dart"))
                     .unwrap()
                     .write_all(result.as_bytes())
                     .unwrap();
+                File::create(temp_dir.join("lib/main.dart.map"))
+                    .unwrap()
+                    .write_all(source_map.to_json("main.dart").as_bytes())
+                    .unwrap();
+                source_map
             }
             Err(error) => {
                 println!("{}", error);
                 std::process::exit(1);
             }
-        }
+        };
         lyken::dart::sdk::with_cmd(|cmd| {
-            let status = cmd.arg("flutter run")
+            // Report errors in the generated code at their `.lyk` locations.
+            let mut child = cmd.arg("flutter run 2>&1")
                 .current_dir(&temp_dir)
-                .status()
+                .stdout(Stdio::piped())
+                .spawn()
                 .unwrap();
-            if !status.success() {
+            let stdout = BufReader::new(child.stdout.take().unwrap());
+            for line in stdout.lines() {
+                println!("{}", source_map.rewrite_locations(&line.unwrap(), "lib/main.dart"));
+            }
+            if !child.wait().unwrap().success() {
                 process::exit(1);
             }
         });
+    } else if let Some(matches) = matches.subcommand_matches("highlight") {
+        let path = PathBuf::from(matches.value_of("FILE").unwrap());
+        let highlighted = match highlight::highlight_file(&path) {
+            Ok(highlighted) => highlighted,
+            Err(error) => {
+                println!("{}", error);
+                process::exit(1);
+            }
+        };
+        match matches.value_of("format").unwrap_or("ansi") {
+            "ansi" => print!("{}", highlighted.to_ansi()),
+            "html" => print!("{}", highlighted.to_html()),
+            "lsp" => {
+                let token_types = Highlight::ALL
+                    .iter()
+                    .map(|highlight| format!("\"{}\"", highlight.lsp_token_type()))
+                    .collect::<Vec<_>>();
+                let data = highlighted
+                    .to_lsp()
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>();
+                println!(
+                    "{{\"legend\":{{\"tokenTypes\":[{}],\"tokenModifiers\":[]}},\"data\":[{}]}}",
+                    token_types.join(","),
+                    data.join(",")
+                );
+            }
+            format => {
+                println!("unknown format `{}`, expected ansi, html or lsp", format);
+                process::exit(1);
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("lift") {
+        let dry_run = matches.is_present("dry_run");
+        let verify = matches.is_present("verify");
+        let mut lifter = Lifter::new();
+        let mut failed = false;
+        for entry in WalkDir::new(matches.value_of("DIR").unwrap()) {
+            let entry = entry.unwrap();
+            let path = entry.path();
+            if !path.extension().map_or(false, |x| x == "dart") {
+                continue;
+            }
+            let module = Module::load(path);
+            if module.has_error {
+                failed = true;
+                continue;
+            }
+            let is_part = module.items.iter().any(|item| match **item {
+                Item::PartOf { .. } => true,
+                _ => false,
+            });
+            if is_part {
+                println!("{}: skipped, part of another library", path.display());
+                continue;
+            }
+
+            if !dart::resolve::resolve(module.clone(), true) {
+                failed = true;
+                continue;
+            }
+            let items = lifter.lift_items(&module.items);
+            for (name, reason) in lifter.kept_as_dart.drain(..) {
+                println!("{}: kept `{}` as Dart: {}", path.display(), name, reason);
+            }
+            let lifted_any = items.iter().any(|item| match **item {
+                dsl::ast::Item::ComponentDef { .. } => true,
+                _ => false,
+            });
+            if !lifted_any {
+                continue;
+            }
+            let result = Printer::new().dsl_items(&items);
+
+            if verify {
+                if let Err(error) = verify_lift(&module, &result) {
+                    println!("{}: {}", path.display(), error);
+                    failed = true;
+                    continue;
+                }
+            }
+
+            let lyk_path = path.with_extension("lyk");
+            if dry_run {
+                let mut src = String::new();
+                File::open(path)
+                    .unwrap()
+                    .read_to_string(&mut src)
+                    .unwrap();
+                println!(
+                    "{} -> {}\n{}",
+                    path.display(),
+                    lyk_path.display(),
+                    Changeset::new(&src, &result, "\n")
+                );
+            } else {
+                File::create(lyk_path)
+                    .unwrap()
+                    .write_all(result.as_bytes())
+                    .unwrap();
+            }
+        }
+        if failed {
+            process::exit(1);
+        }
+    }
+}
+
+/// Checks that lowering the lifted components in `lifted` gives back `module`.
+fn verify_lift(module: &Module, lifted: &str) -> Result<(), String> {
+    let tokens = Lexer::from_string(lifted.to_string())
+        .tokenize()
+        .map_err(|error| error.to_string())?;
+    let dsl_module = Parser::new(&module.path, &tokens)
+        .dsl_module()
+        .map_err(|error| error.to_string())?;
+    if !resolve::resolve(&dsl_module.items, true) {
+        return Err("resolving the lifted components failed".to_string());
+    }
+    let mut lowerer = Lowerer::new();
+    let lowered = lowerer.lower_items(&dsl_module.items);
+    if lowerer.has_error {
+        return Err("lowering the lifted components failed".to_string());
     }
+
+    let relowered = Module {
+        path: module.path.clone(),
+        items: lowered,
+        has_error: false,
+    };
+    equiv::modules_equivalent(module, &relowered).map_err(|divergence| {
+        format!("lowering the lifted components gives different Dart: {}", divergence)
+    })
 }
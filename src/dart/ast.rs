@@ -11,6 +11,11 @@ use syntax::symbol::Symbol;
 use Span;
 
 node_field!(parent_any: Node<Any>);
+node_field!(span: Span);
+node_field!(derived_from: Span);
+/// Set on the empty blocks standing in for function bodies skipped by
+/// `Module::load_exports`, once their module is loaded in full.
+node_field!(filled_body: Node<Statement>);
 
 impl<T: 'static> Node<T> {
     pub fn parent<U: 'static>(&self) -> Option<Node<U>> {
@@ -20,12 +25,21 @@ impl<T: 'static> Node<T> {
             None
         }
     }
-    pub fn root_module(&self) -> Option<Node<Module>> {
+    /// Returns the topmost ancestor of this node, e.g. its `Module`.
+    pub fn root(&self) -> Node<Any> {
         let mut node: Node<Any> = self.clone();
         while let Some(parent) = node.parent_any().get() {
             node = parent;
         }
-        node.downcast()
+        node
+    }
+    pub fn root_module(&self) -> Option<Node<Module>> {
+        self.root().downcast()
+    }
+    /// The span of the source this node was parsed from, or, for synthetic
+    /// nodes, the span of the source they were derived from.
+    pub fn origin_span(&self) -> Option<Span> {
+        self.span().get().or_else(|| self.derived_from().get())
     }
 }
 
@@ -42,19 +56,39 @@ pub struct Module {
 }
 
 impl Module {
+    /// Loads and parses the module at `path`, in full.
     pub fn load(path: &Path) -> Node<Module> {
-        thread_local!(static CACHE: RefCell<HashMap<PathBuf, Node<Module>>> =
+        Module::load_with(path, false)
+    }
+
+    /// Loads the module at `path` for its exports, skipping function bodies,
+    /// unless it was already loaded in full.
+    pub fn load_exports(path: &Path) -> Node<Module> {
+        Module::load_with(path, true)
+    }
+
+    fn load_with(path: &Path, skip_bodies: bool) -> Node<Module> {
+        // Per-thread, like the side tables of `Node` (see there for why).
+        // Each module is cached along with whether its bodies were skipped.
+        thread_local!(static CACHE: RefCell<HashMap<PathBuf, (Node<Module>, bool)>> =
         RefCell::new(HashMap::new()));
 
         let path_buf;
         let mut path = path;
+        let mut skeleton = None;
         let module: parse::ParseResult<_> = do catch {
             path_buf = path.canonicalize()?;
             path = &path_buf;
-            if let Some(module) = CACHE.with(|c| c.borrow().get(path).cloned()) {
-                return module;
+            if let Some((module, skipped)) = CACHE.with(|c| c.borrow().get(path).cloned()) {
+                if skip_bodies || !skipped {
+                    return module;
+                }
+                skeleton = Some(module);
             }
-            parse::Parser::with_file(path, |p| p.dart_module())?
+            parse::Parser::with_file(path, |mut p| {
+                p.skip_bodies = skip_bodies;
+                p.dart_module()
+            })?
         };
         let module = match module {
             Ok(module) => module,
@@ -72,31 +106,102 @@ impl Module {
             }
         };
 
-        struct Parenter {
-            parent: Node<Any>,
-        }
+        module.super_visit(&mut Parenter::new(&module));
 
-        impl Visitor for Parenter {
-            fn visit_node<T: VisitNode>(&mut self, node: Node<T>) {
-                let parent = self.parent.clone();
-                node.parent_any().set(parent.clone());
-                self.parent = node.clone();
-                node.super_visit(self);
-                self.parent = parent;
+        // Nodes may already have been resolved against the module loaded
+        // for its exports, so it stays the module, with its bodies filled in.
+        if let Some(skeleton) = skeleton {
+            if !module.has_error && skeleton.fill_bodies(&module) {
+                CACHE.with(|c| {
+                    c.borrow_mut().insert(skeleton.path.clone(), (skeleton.clone(), false))
+                });
+                return skeleton;
             }
         }
 
-        module.super_visit(&mut Parenter {
-            parent: module.clone(),
-        });
-
         CACHE.with(|c| {
-            c.borrow_mut().insert(module.path.clone(), module.clone())
+            c.borrow_mut().insert(module.path.clone(), (module.clone(), skip_bodies))
         });
         module
     }
 }
 
+impl Node<Module> {
+    /// Fills the bodies this module skipped in with those of `full`, the
+    /// same module loaded in full. Returns false if they don't line up,
+    /// e.g. because the file changed in between.
+    fn fill_bodies(&self, full: &Node<Module>) -> bool {
+        /// Collects function bodies in source order, without those nested
+        /// in them, or in parts, which are loaded and filled in on their own.
+        struct BodyCollector {
+            bodies: Vec<Node<Statement>>,
+        }
+
+        impl Visitor for BodyCollector {
+            fn dart_module(&mut self, _: Node<Module>) {}
+            fn dart_fn_body(&mut self, fn_body: &FnBody) {
+                match *fn_body {
+                    FnBody::Block(ref block) => self.bodies.push(block.clone()),
+                    _ => fn_body.super_visit(self),
+                }
+            }
+        }
+
+        let mut skipped = BodyCollector { bodies: vec![] };
+        self.super_visit(&mut skipped);
+        let mut loaded = BodyCollector { bodies: vec![] };
+        full.super_visit(&mut loaded);
+        if skipped.bodies.len() != loaded.bodies.len() {
+            return false;
+        }
+        for (placeholder, body) in skipped.bodies.iter().zip(loaded.bodies) {
+            if let Some(parent) = placeholder.parent_any().get() {
+                body.parent_any().set(parent);
+            }
+            placeholder.filled_body().set(body);
+        }
+        true
+    }
+}
+
+impl Node<Statement> {
+    /// Returns this statement, or if it stands in for a skipped function
+    /// body, the body loaded since.
+    pub fn filled(&self) -> Node<Statement> {
+        self.filled_body().get().unwrap_or_else(|| self.clone())
+    }
+}
+
+/// Links nodes to their parents, for `Node::parent` and `Node::root`.
+pub struct Parenter {
+    parent: Node<Any>,
+}
+
+impl Parenter {
+    /// Starts linking the descendants of `root`.
+    pub fn new<T: 'static>(root: &Node<T>) -> Parenter {
+        Parenter {
+            parent: root.clone(),
+        }
+    }
+
+    /// Links `node` to the current parent, and the nodes `link_children`
+    /// visits to `node`.
+    pub fn link<T: 'static, F: FnOnce(&mut Self)>(&mut self, node: Node<T>, link_children: F) {
+        let parent = self.parent.clone();
+        node.parent_any().set(parent.clone());
+        self.parent = node;
+        link_children(self);
+        self.parent = parent;
+    }
+}
+
+impl Visitor for Parenter {
+    fn visit_node<T: VisitNode>(&mut self, node: Node<T>) {
+        self.link(node.clone(), |this| node.super_visit(this));
+    }
+}
+
 /// A top-level item.
 #[derive(Debug)]
 pub enum Item {
@@ -107,7 +212,7 @@ pub enum Item {
     Import(Meta, Import),
     /// A directive that makes the items of a library available to
     /// users of the current library (`export 'library';`).
-    Export(Meta, StringLiteral, Vec<ImportFilter>),
+    Export(Meta, StringLiteral, Vec<ImportConfig>, Vec<ImportFilter>),
     /// A directive that specifies where a file that should be incorporated
     /// into the current library is located (`part 'library';`).
     Part {
@@ -117,12 +222,15 @@ pub enum Item {
     },
     /// A directive that specifies that the current file is incorporated
     /// in a library (`part of 'library';`).
-    PartOf { meta: Meta, path: Vec<Symbol> },
+    PartOf { meta: Meta, library: LibraryRef },
     /// A class defines the form and behaviour of a set of objects
     /// that are its instances (`class example { ... }`).
     Class {
         meta: Meta,
         abstract_: bool,
+        modifier: Option<ClassModifier>,
+        /// Whether the class can also be used as a mixin (`mixin class`).
+        mixin: bool,
         name: Symbol,
         generics: Vec<Node<TypeParameter>>,
         superclass: Option<Node<Qualified>>,
@@ -135,19 +243,51 @@ pub enum Item {
     MixinClass {
         meta: Meta,
         abstract_: bool,
+        modifier: Option<ClassModifier>,
         name: Symbol,
         generics: Vec<Node<TypeParameter>>,
         mixins: Vec<Node<Qualified>>,
         interfaces: Vec<Node<Qualified>>,
     },
+    /// A mixin declares members to be mixed into classes, which have
+    /// to be subtypes of its superclass constraints
+    /// (`mixin M<T> on A, B implements C { ... }`).
+    Mixin {
+        meta: Meta,
+        /// Whether the mixin can only be mixed into `base` classes
+        /// (`base mixin M { ... }`).
+        base: bool,
+        name: Symbol,
+        generics: Vec<Node<TypeParameter>>,
+        constraints: Vec<Node<Qualified>>,
+        interfaces: Vec<Node<Qualified>>,
+        members: Vec<Node<ClassMember>>,
+    },
+    /// An extension adds members to an existing type
+    /// (`extension E<T> on List<T> { ... }`), and may be unnamed.
+    Extension {
+        meta: Meta,
+        name: Option<Symbol>,
+        generics: Vec<Node<TypeParameter>>,
+        on: Node<Type>,
+        members: Vec<Node<ClassMember>>,
+    },
     /// Enumerated types are a special kind of class used to represent
     /// a fixed set of constant values.
     ///
     /// (`enum Type { integer, boolean, string }`).
+    ///
+    /// Enhanced enums can also have type parameters, supertypes
+    /// and members, which the values are constructed with
+    /// (`enum Size with Scaled { small(1), large(10); ... }`).
     Enum {
         meta: Meta,
         name: Symbol,
-        values: Vec<(Meta, Symbol)>,
+        generics: Vec<Node<TypeParameter>>,
+        mixins: Vec<Node<Qualified>>,
+        interfaces: Vec<Node<Qualified>>,
+        values: Vec<EnumValue>,
+        members: Vec<Node<ClassMember>>,
     },
     /// A type alias defines a new name for a given type
     /// (`typedef int example(Object a, Object b);`).
@@ -167,6 +307,81 @@ pub enum Item {
     Vars(Meta, VarType, Vec<Node<VarDef>>),
 }
 
+/// Restricts how a class can be used outside of its library.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ClassModifier {
+    /// Can be extended but not implemented (`base class`).
+    Base,
+    /// Can be implemented but not extended (`interface class`).
+    Interface,
+    /// Can be neither extended nor implemented (`final class`).
+    Final,
+    /// Can only be subtyped in its own library, which makes its
+    /// direct subtypes known, e.g. to switches (`sealed class`).
+    Sealed,
+}
+
+impl ClassModifier {
+    pub fn values() -> Vec<ClassModifier> {
+        vec![
+            ClassModifier::Base,
+            ClassModifier::Interface,
+            ClassModifier::Final,
+            ClassModifier::Sealed,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ClassModifier::Base => "base",
+            ClassModifier::Interface => "interface",
+            ClassModifier::Final => "final",
+            ClassModifier::Sealed => "sealed",
+        }
+    }
+}
+
+/// A value of an enum.
+///
+/// `red`
+/// `kilo(1000)`
+/// `unit<int>.named(1)`
+#[derive(Debug)]
+pub struct EnumValue {
+    pub meta: Meta,
+    pub name: Symbol,
+    /// Type arguments passed to the constructor.
+    pub generics: Vec<Node<Type>>,
+    /// The named constructor the value is created with, if any.
+    pub constructor: Option<Symbol>,
+    /// Arguments passed to the constructor, if any.
+    pub args: Option<Args>,
+}
+
+/// Refers to the library a part belongs to.
+#[derive(Clone, Debug)]
+pub enum LibraryRef {
+    /// By its name (`part of name.space;`).
+    Name(Vec<Symbol>),
+    /// By its URI (`part of 'library.dart';`).
+    Uri(StringLiteral),
+}
+
+/// Specifies a URI to use instead of the default one of an import or
+/// export, if a condition holds in the environment.
+///
+/// `if (dart.library.html) 'web.dart'`
+/// `if (flavor == 'debug') 'debug.dart'`
+#[derive(Clone, Debug)]
+pub struct ImportConfig {
+    /// The name of the declared variable tested.
+    pub name: Vec<Symbol>,
+    /// The value the variable is compared with, `'true'` if omitted.
+    pub value: Option<StringLiteral>,
+    /// The URI used if the condition holds.
+    pub uri: StringLiteral,
+}
+
 /// Specifies which items of the imported library to be shown/hidden.
 #[derive(Clone, Debug)]
 pub struct ImportFilter {
@@ -180,11 +395,14 @@ pub struct ImportFilter {
 ///
 /// `import 'dart:io';`
 /// `import '../util.dart';`
+/// `import 'io.dart' if (dart.library.html) 'web.dart';`
 #[derive(Clone, Debug)]
 pub struct Import {
     /// Holds the URI where the declaration of the imported library
     /// is to be found.
     pub uri: StringLiteral,
+    /// Alternative URIs, the first one whose condition holds is used.
+    pub configs: Vec<ImportConfig>,
     /// Distinguishes between deferred and immediate imports.
     pub deferred: bool,
     /// Specifies a prefix for the library.
@@ -526,6 +744,12 @@ pub enum Type {
     FunctionOld(FnSig),
     /// Function type (`R Function(A, B, C)`).
     Function(FnSig),
+    /// Record type, with positional fields that may be named and
+    /// named fields (`(int, String name, {bool flag})`).
+    Record {
+        positional: Vec<(Node<Type>, Option<Symbol>)>,
+        named: Vec<(Node<Type>, Symbol)>,
+    },
     /// Unspecified type.
     Infer,
 }
@@ -589,8 +813,10 @@ pub enum Expr {
         kv_ty: Option<(Node<Type>, Node<Type>)>,
         kv: Vec<(Node<Expr>, Node<Expr>)>,
     },
-    /// A number literal (`1.2`).
-    Number(Symbol),
+    /// An integer literal, as written (`42`, `0xFF2196F3`, `1_000`).
+    Int(Symbol),
+    /// A floating-point literal, as written (`1.2`, `.5`, `2.5E10`).
+    Double(Symbol),
     /// One or more string literals, implicitly
     /// concatenated to form a single `String`.
     ///
@@ -600,6 +826,12 @@ pub enum Expr {
     Symbol(SymbolLiteral),
     /// An expression enclosed in parantheses (`(expr)`).
     Paren(Node<Expr>),
+    /// A record literal (`(1, 'a', flag: true)`, `(x,)`).
+    Record(Args),
+    /// An expression picking the first case whose pattern matches.
+    ///
+    /// `switch (shape) { Square(:var size) => size * size, _ => 0 }`
+    Switch(Node<Expr>, Vec<SwitchExprCase>),
     /// An expression thrown as a result of raising an exception.
     ///
     /// `throw expression`
@@ -696,6 +928,8 @@ pub struct Args {
     pub unnamed: Vec<Node<Expr>>,
     /// Named arguments.
     pub named: Vec<NamedArg>,
+    /// Comments before the closing parenthesis.
+    pub trailing_comments: Vec<Span>,
 }
 
 /// An element of metadata prefixing a definition.
@@ -798,6 +1032,18 @@ pub struct ArgDef {
 }
 
 impl ArgDef {
+    /// Returns true for parameters that are required despite being named
+    /// (`@required`).
+    pub fn is_required(&self) -> bool {
+        self.meta.iter().any(|meta| match *meta {
+            MetaItem::Attribute {
+                ref qualified,
+                arguments: None,
+            } => qualified.prefix.is_none() && qualified.name == "required",
+            _ => false,
+        })
+    }
+
     pub fn simple<S: ::IntoSymbol>(ty: Node<Type>, name: S) -> ArgDef {
         ArgDef {
             meta: vec![],
@@ -871,6 +1117,65 @@ pub struct SwitchCase {
     pub statements: Vec<Node<Statement>>,
 }
 
+/// A case of a switch expression (`pattern when guard => expr`).
+#[derive(Debug)]
+pub struct SwitchExprCase {
+    /// The pattern the value is matched against.
+    pub pattern: Node<Pattern>,
+    /// An additional condition for the case to match.
+    pub guard: Option<Node<Expr>>,
+    /// The value of the switch, should the case match.
+    pub body: Node<Expr>,
+}
+
+/// A pattern that a value can be matched against, destructuring it
+/// into variables.
+#[derive(Debug)]
+pub enum Pattern {
+    /// Matches anything (`_`).
+    Wildcard,
+    /// Matches anything, or values of the given type, and binds them
+    /// to a new variable (`var x`, `final int x`, `int x`).
+    Var(VarType, Node<VarDef>),
+    /// Matches values equal to a constant (`1`, `'a'`, `Color.red`).
+    Const(Node<Expr>),
+    /// Matches values comparing to a constant (`> 0`, `!= null`).
+    Relational(BoolBinOp, Node<Expr>),
+    /// Matches if either pattern matches (`a || b`).
+    Or(Node<Pattern>, Node<Pattern>),
+    /// Matches if both patterns match (`a && b`).
+    And(Node<Pattern>, Node<Pattern>),
+    /// Casts the value before matching it (`x as int`).
+    Cast(Node<Pattern>, Node<Type>),
+    /// Matches non-null values (`x?`).
+    NullCheck(Node<Pattern>),
+    /// Throws on null values (`x!`).
+    NullAssert(Node<Pattern>),
+    /// Matches lists, element by element (`[a, b, ...rest]`).
+    List(Vec<Node<Pattern>>),
+    /// Matches the remaining elements of a list (`...`, `...rest`).
+    Rest(Option<Node<Pattern>>),
+    /// Matches maps with the given keys (`{'a': x}`).
+    Map(Vec<(Node<Expr>, Node<Pattern>)>),
+    /// Matches records with the given fields (`(a, b: var x)`).
+    Record(Vec<PatternField>),
+    /// Matches instances of a type, with the given getters
+    /// (`Point(x: 0, :var y)`).
+    Object(Node<Qualified>, Vec<PatternField>),
+    /// A pattern enclosed in parantheses (`(pattern)`).
+    Paren(Node<Pattern>),
+}
+
+/// A field of a record or object pattern.
+#[derive(Debug)]
+pub enum PatternField {
+    /// A positional field of a record (`(pattern)`).
+    Positional(Node<Pattern>),
+    /// A named field or getter, its name inferred from the variable
+    /// the pattern binds if omitted (`name: pattern`, `:var name`).
+    Named(Option<Symbol>, Node<Pattern>),
+}
+
 /// The catch clause of exception handlers.
 #[derive(Debug)]
 pub struct CatchPart {
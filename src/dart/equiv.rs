@@ -0,0 +1,1210 @@
+use dart::ast::*;
+use node::Node;
+use std::fmt;
+use syntax::symbol::Symbol;
+use Span;
+
+/// The first difference found between two modules.
+#[derive(Debug)]
+pub struct Divergence {
+    /// The enclosing definitions, outermost first (`class Foo`, `method build`).
+    pub context: Vec<String>,
+    /// What differs.
+    pub message: String,
+    /// The closest known source locations on either side.
+    pub spans: (Option<Span>, Option<Span>),
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(span) = self.spans.0 {
+            write!(f, "{}: ", ::codemap().span_to_string(span.to_span()))?;
+        }
+        for context in &self.context {
+            write!(f, "in {}: ", context)?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(span) = self.spans.1 {
+            write!(f, " (see {})", ::codemap().span_to_string(span.to_span()))?;
+        }
+        Ok(())
+    }
+}
+
+pub type EquivResult = Result<(), Divergence>;
+
+/// Compares two modules structurally, ignoring formatting, comments,
+/// trailing commas, optional `new` and the order of items and members.
+pub fn modules_equivalent(a: &Module, b: &Module) -> EquivResult {
+    Comparer {
+        context: vec![],
+        spans: (None, None),
+    }.items(&a.items, &b.items)
+}
+
+struct Comparer {
+    context: Vec<String>,
+    spans: (Option<Span>, Option<Span>),
+}
+
+impl Comparer {
+    fn diverge<T>(&self, message: String) -> Result<T, Divergence> {
+        Err(Divergence {
+            context: self.context.clone(),
+            message,
+            spans: self.spans,
+        })
+    }
+
+    fn in_context<F: FnOnce(&mut Self) -> EquivResult>(
+        &mut self,
+        context: String,
+        f: F,
+    ) -> EquivResult {
+        self.context.push(context);
+        let result = f(self);
+        self.context.pop();
+        result
+    }
+
+    /// Applies `f` with divergences located at the spans of `a` and `b`,
+    /// keeping the enclosing ones for nodes without a span.
+    fn located<T: 'static, F: FnOnce(&mut Self) -> EquivResult>(
+        &mut self,
+        a: &Node<T>,
+        b: &Node<T>,
+        f: F,
+    ) -> EquivResult {
+        let spans = self.spans;
+        self.spans = (a.origin_span().or(spans.0), b.origin_span().or(spans.1));
+        let result = f(self);
+        self.spans = spans;
+        result
+    }
+
+    fn eq<T: PartialEq + fmt::Debug>(&self, what: &str, a: T, b: T) -> EquivResult {
+        if a != b {
+            return self.diverge(format!("{} differs: `{:?}` vs `{:?}`", what, a, b));
+        }
+        Ok(())
+    }
+
+    fn symbol(&self, what: &str, a: Symbol, b: Symbol) -> EquivResult {
+        if a != b {
+            return self.diverge(format!("{} differs: `{}` vs `{}`", what, a, b));
+        }
+        Ok(())
+    }
+
+    fn option<T, C>(&mut self, what: &str, a: &Option<T>, b: &Option<T>, compare: C) -> EquivResult
+    where
+        C: FnOnce(&mut Self, &T, &T) -> EquivResult,
+    {
+        match (a.as_ref(), b.as_ref()) {
+            (Some(a), Some(b)) => compare(self, a, b),
+            (None, None) => Ok(()),
+            (Some(_), None) => self.diverge(format!("{} only on the left", what)),
+            (None, Some(_)) => self.diverge(format!("{} only on the right", what)),
+        }
+    }
+
+    fn ordered<T, C>(&mut self, what: &str, a: &[T], b: &[T], mut compare: C) -> EquivResult
+    where
+        C: FnMut(&mut Self, &T, &T) -> EquivResult,
+    {
+        if a.len() != b.len() {
+            return self.diverge(format!(
+                "number of {} differs: {} vs {}",
+                what,
+                a.len(),
+                b.len()
+            ));
+        }
+        for (a, b) in a.iter().zip(b) {
+            compare(self, a, b)?;
+        }
+        Ok(())
+    }
+
+    /// Pairs up the elements of `a` and `b` by `key`, regardless of order.
+    fn unordered<T, K, C>(&mut self, what: &str, a: &[T], b: &[T], key: K, mut compare: C) -> EquivResult
+    where
+        K: Fn(&T) -> String,
+        C: FnMut(&mut Self, &T, &T) -> EquivResult,
+    {
+        let mut used = vec![false; b.len()];
+        for a in a {
+            let a_key = key(a);
+            let found = (0..b.len()).find(|&i| !used[i] && key(&b[i]) == a_key);
+            match found {
+                Some(i) => {
+                    used[i] = true;
+                    self.in_context(a_key, |this| compare(this, a, &b[i]))?;
+                }
+                None => return self.diverge(format!("{} `{}` only on the left", what, a_key)),
+            }
+        }
+        if let Some(i) = used.iter().position(|&used| !used) {
+            return self.diverge(format!("{} `{}` only on the right", what, key(&b[i])));
+        }
+        Ok(())
+    }
+
+    fn items(&mut self, a: &[Node<Item>], b: &[Node<Item>]) -> EquivResult {
+        self.unordered("item", a, b, |item| item_key(item), |this, a, b| {
+            this.located(a, b, |this| this.item(a, b))
+        })
+    }
+
+    fn item(&mut self, a: &Item, b: &Item) -> EquivResult {
+        match (a, b) {
+            (
+                &Item::LibraryName {
+                    meta: ref meta_a,
+                    path: ref path_a,
+                },
+                &Item::LibraryName {
+                    meta: ref meta_b,
+                    path: ref path_b,
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.dotted_name(path_a, path_b)
+            }
+            (
+                &Item::PartOf {
+                    meta: ref meta_a,
+                    library: ref library_a,
+                },
+                &Item::PartOf {
+                    meta: ref meta_b,
+                    library: ref library_b,
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                match (library_a, library_b) {
+                    (&LibraryRef::Name(ref path_a), &LibraryRef::Name(ref path_b)) => {
+                        self.dotted_name(path_a, path_b)
+                    }
+                    (&LibraryRef::Uri(ref uri_a), &LibraryRef::Uri(ref uri_b)) => {
+                        self.string_literal(uri_a, uri_b)
+                    }
+                    _ => self.diverge("library referred to by name vs by URI".to_string()),
+                }
+            }
+            (&Item::Import(ref meta_a, ref import_a), &Item::Import(ref meta_b, ref import_b)) => {
+                self.meta(meta_a, meta_b)?;
+                self.string_literal(&import_a.uri, &import_b.uri)?;
+                self.import_configs(&import_a.configs, &import_b.configs)?;
+                self.eq("deferred", import_a.deferred, import_b.deferred)?;
+                self.option("alias", &import_a.alias, &import_b.alias, |this, &a, &b| {
+                    this.symbol("alias", a, b)
+                })?;
+                self.import_filters(&import_a.filters, &import_b.filters)
+            }
+            (
+                &Item::Export(ref meta_a, ref uri_a, ref configs_a, ref filters_a),
+                &Item::Export(ref meta_b, ref uri_b, ref configs_b, ref filters_b),
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.string_literal(uri_a, uri_b)?;
+                self.import_configs(configs_a, configs_b)?;
+                self.import_filters(filters_a, filters_b)
+            }
+            (
+                &Item::Part {
+                    meta: ref meta_a,
+                    uri: ref uri_a,
+                    ..
+                },
+                &Item::Part {
+                    meta: ref meta_b,
+                    uri: ref uri_b,
+                    ..
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.string_literal(uri_a, uri_b)
+            }
+            (
+                &Item::Class {
+                    meta: ref meta_a,
+                    abstract_: abstract_a,
+                    modifier: modifier_a,
+                    mixin: mixin_a,
+                    generics: ref generics_a,
+                    superclass: ref superclass_a,
+                    mixins: ref mixins_a,
+                    interfaces: ref interfaces_a,
+                    members: ref members_a,
+                    ..
+                },
+                &Item::Class {
+                    meta: ref meta_b,
+                    abstract_: abstract_b,
+                    modifier: modifier_b,
+                    mixin: mixin_b,
+                    generics: ref generics_b,
+                    superclass: ref superclass_b,
+                    mixins: ref mixins_b,
+                    interfaces: ref interfaces_b,
+                    members: ref members_b,
+                    ..
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.eq("abstract", abstract_a, abstract_b)?;
+                self.eq("class modifier", modifier_a, modifier_b)?;
+                self.eq("mixin", mixin_a, mixin_b)?;
+                self.generics(generics_a, generics_b)?;
+                self.option("superclass", superclass_a, superclass_b, |this, a, b| {
+                    this.qualified(a, b)
+                })?;
+                self.qualifieds("mixins", mixins_a, mixins_b)?;
+                self.qualifieds("interfaces", interfaces_a, interfaces_b)?;
+                self.unordered(
+                    "member",
+                    members_a,
+                    members_b,
+                    |member| member_key(member),
+                    |this, a, b| this.located(a, b, |this| this.class_member(a, b)),
+                )
+            }
+            (
+                &Item::MixinClass {
+                    meta: ref meta_a,
+                    abstract_: abstract_a,
+                    modifier: modifier_a,
+                    generics: ref generics_a,
+                    mixins: ref mixins_a,
+                    interfaces: ref interfaces_a,
+                    ..
+                },
+                &Item::MixinClass {
+                    meta: ref meta_b,
+                    abstract_: abstract_b,
+                    modifier: modifier_b,
+                    generics: ref generics_b,
+                    mixins: ref mixins_b,
+                    interfaces: ref interfaces_b,
+                    ..
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.eq("abstract", abstract_a, abstract_b)?;
+                self.eq("class modifier", modifier_a, modifier_b)?;
+                self.generics(generics_a, generics_b)?;
+                self.qualifieds("mixins", mixins_a, mixins_b)?;
+                self.qualifieds("interfaces", interfaces_a, interfaces_b)
+            }
+            (
+                &Item::Mixin {
+                    meta: ref meta_a,
+                    base: base_a,
+                    generics: ref generics_a,
+                    constraints: ref constraints_a,
+                    interfaces: ref interfaces_a,
+                    members: ref members_a,
+                    ..
+                },
+                &Item::Mixin {
+                    meta: ref meta_b,
+                    base: base_b,
+                    generics: ref generics_b,
+                    constraints: ref constraints_b,
+                    interfaces: ref interfaces_b,
+                    members: ref members_b,
+                    ..
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.eq("base", base_a, base_b)?;
+                self.generics(generics_a, generics_b)?;
+                self.qualifieds("superclass constraints", constraints_a, constraints_b)?;
+                self.qualifieds("interfaces", interfaces_a, interfaces_b)?;
+                self.unordered(
+                    "member",
+                    members_a,
+                    members_b,
+                    |member| member_key(member),
+                    |this, a, b| this.located(a, b, |this| this.class_member(a, b)),
+                )
+            }
+            (
+                &Item::Extension {
+                    meta: ref meta_a,
+                    generics: ref generics_a,
+                    on: ref on_a,
+                    members: ref members_a,
+                    ..
+                },
+                &Item::Extension {
+                    meta: ref meta_b,
+                    generics: ref generics_b,
+                    on: ref on_b,
+                    members: ref members_b,
+                    ..
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.generics(generics_a, generics_b)?;
+                self.ty(on_a, on_b)?;
+                self.unordered(
+                    "member",
+                    members_a,
+                    members_b,
+                    |member| member_key(member),
+                    |this, a, b| this.located(a, b, |this| this.class_member(a, b)),
+                )
+            }
+            (
+                &Item::Enum {
+                    meta: ref meta_a,
+                    generics: ref generics_a,
+                    mixins: ref mixins_a,
+                    interfaces: ref interfaces_a,
+                    values: ref values_a,
+                    members: ref members_a,
+                    ..
+                },
+                &Item::Enum {
+                    meta: ref meta_b,
+                    generics: ref generics_b,
+                    mixins: ref mixins_b,
+                    interfaces: ref interfaces_b,
+                    values: ref values_b,
+                    members: ref members_b,
+                    ..
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.generics(generics_a, generics_b)?;
+                self.qualifieds("mixins", mixins_a, mixins_b)?;
+                self.qualifieds("interfaces", interfaces_a, interfaces_b)?;
+                self.ordered("enum values", values_a, values_b, |this, a, b| {
+                    this.meta(&a.meta, &b.meta)?;
+                    this.symbol("enum value", a.name, b.name)?;
+                    this.types(&a.generics, &b.generics)?;
+                    this.option("constructor", &a.constructor, &b.constructor, |this, &a, &b| {
+                        this.symbol("constructor", a, b)
+                    })?;
+                    this.option("arguments", &a.args, &b.args, |this, a, b| this.args(a, b))
+                })?;
+                self.unordered(
+                    "member",
+                    members_a,
+                    members_b,
+                    |member| member_key(member),
+                    |this, a, b| this.located(a, b, |this| this.class_member(a, b)),
+                )
+            }
+            (
+                &Item::TypeAlias {
+                    meta: ref meta_a,
+                    generics: ref generics_a,
+                    ty: ref ty_a,
+                    ..
+                },
+                &Item::TypeAlias {
+                    meta: ref meta_b,
+                    generics: ref generics_b,
+                    ty: ref ty_b,
+                    ..
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.generics(generics_a, generics_b)?;
+                self.ty(ty_a, ty_b)
+            }
+            (
+                &Item::Function {
+                    meta: ref meta_a,
+                    external: external_a,
+                    function: ref function_a,
+                },
+                &Item::Function {
+                    meta: ref meta_b,
+                    external: external_b,
+                    function: ref function_b,
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.eq("external", external_a, external_b)?;
+                self.function(function_a, function_b)
+            }
+            (
+                &Item::Vars(ref meta_a, ref var_type_a, ref vars_a),
+                &Item::Vars(ref meta_b, ref var_type_b, ref vars_b),
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.var_type(var_type_a, var_type_b)?;
+                self.var_defs(vars_a, vars_b)
+            }
+            _ => self.diverge("different kinds of items".to_string()),
+        }
+    }
+
+    fn dotted_name(&mut self, a: &[Symbol], b: &[Symbol]) -> EquivResult {
+        self.ordered("path segments", a, b, |this, &a, &b| {
+            this.symbol("path segment", a, b)
+        })
+    }
+
+    fn import_configs(&mut self, a: &[ImportConfig], b: &[ImportConfig]) -> EquivResult {
+        self.ordered("import configurations", a, b, |this, a, b| {
+            this.dotted_name(&a.name, &b.name)?;
+            this.option("configuration value", &a.value, &b.value, |this, a, b| {
+                this.string_literal(a, b)
+            })?;
+            this.string_literal(&a.uri, &b.uri)
+        })
+    }
+
+    fn import_filters(&mut self, a: &[ImportFilter], b: &[ImportFilter]) -> EquivResult {
+        self.ordered("combinators", a, b, |this, a, b| {
+            this.eq("combinator", a.hide, b.hide)?;
+            this.ordered("combinator names", &a.names, &b.names, |this, &a, &b| {
+                this.symbol("combinator name", a, b)
+            })
+        })
+    }
+
+    fn class_member(&mut self, a: &ClassMember, b: &ClassMember) -> EquivResult {
+        match (a, b) {
+            (
+                &ClassMember::Redirect {
+                    meta: ref meta_a,
+                    method_qualifiers: ref qualifiers_a,
+                    sig: ref sig_a,
+                    path: ref path_a,
+                    ..
+                },
+                &ClassMember::Redirect {
+                    meta: ref meta_b,
+                    method_qualifiers: ref qualifiers_b,
+                    sig: ref sig_b,
+                    path: ref path_b,
+                    ..
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.method_qualifiers(qualifiers_a, qualifiers_b)?;
+                self.fn_sig(sig_a, sig_b)?;
+                self.qualified(path_a, path_b)
+            }
+            (
+                &ClassMember::Constructor {
+                    meta: ref meta_a,
+                    method_qualifiers: ref qualifiers_a,
+                    sig: ref sig_a,
+                    initializers: ref initializers_a,
+                    function_body: ref body_a,
+                    ..
+                },
+                &ClassMember::Constructor {
+                    meta: ref meta_b,
+                    method_qualifiers: ref qualifiers_b,
+                    sig: ref sig_b,
+                    initializers: ref initializers_b,
+                    function_body: ref body_b,
+                    ..
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.method_qualifiers(qualifiers_a, qualifiers_b)?;
+                self.fn_sig(sig_a, sig_b)?;
+                self.ordered("initializers", initializers_a, initializers_b, |this, a, b| {
+                    this.constructor_initializer(a, b)
+                })?;
+                self.option("body", body_a, body_b, |this, a, b| this.fn_body(a, b))
+            }
+            (
+                &ClassMember::Method(ref meta_a, ref qualifiers_a, ref function_a),
+                &ClassMember::Method(ref meta_b, ref qualifiers_b, ref function_b),
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.method_qualifiers(qualifiers_a, qualifiers_b)?;
+                self.function(function_a, function_b)
+            }
+            (
+                &ClassMember::Fields {
+                    meta: ref meta_a,
+                    static_: static_a,
+                    var_type: ref var_type_a,
+                    initializers: ref vars_a,
+                },
+                &ClassMember::Fields {
+                    meta: ref meta_b,
+                    static_: static_b,
+                    var_type: ref var_type_b,
+                    initializers: ref vars_b,
+                },
+            ) => {
+                self.meta(meta_a, meta_b)?;
+                self.eq("static", static_a, static_b)?;
+                self.var_type(var_type_a, var_type_b)?;
+                self.var_defs(vars_a, vars_b)
+            }
+            _ => self.diverge("different kinds of members".to_string()),
+        }
+    }
+
+    fn method_qualifiers(&self, a: &[MethodQualifiers], b: &[MethodQualifiers]) -> EquivResult {
+        let mut a = a.iter().map(|q| format!("{:?}", q)).collect::<Vec<_>>();
+        let mut b = b.iter().map(|q| format!("{:?}", q)).collect::<Vec<_>>();
+        a.sort();
+        b.sort();
+        self.eq("qualifiers", a, b)
+    }
+
+    fn constructor_initializer(
+        &mut self,
+        a: &ConstructorInitializer,
+        b: &ConstructorInitializer,
+    ) -> EquivResult {
+        match (a, b) {
+            (
+                &ConstructorInitializer::Super(ref name_a, ref args_a),
+                &ConstructorInitializer::Super(ref name_b, ref args_b),
+            ) |
+            (
+                &ConstructorInitializer::This(ref name_a, ref args_a),
+                &ConstructorInitializer::This(ref name_b, ref args_b),
+            ) => {
+                self.option("constructor name", name_a, name_b, |this, &a, &b| {
+                    this.symbol("constructor name", a, b)
+                })?;
+                self.args(args_a, args_b)
+            }
+            (&ConstructorInitializer::Assert(ref a), &ConstructorInitializer::Assert(ref b)) => {
+                self.args(a, b)
+            }
+            (
+                &ConstructorInitializer::Field(this_a, name_a, ref expr_a),
+                &ConstructorInitializer::Field(this_b, name_b, ref expr_b),
+            ) => {
+                self.eq("`this.`", this_a, this_b)?;
+                self.symbol("field", name_a, name_b)?;
+                self.expr(expr_a, expr_b)
+            }
+            _ => self.diverge("different kinds of initializers".to_string()),
+        }
+    }
+
+    fn meta(&mut self, a: &Meta, b: &Meta) -> EquivResult {
+        fn attributes(meta: &Meta) -> Vec<(&Node<Qualified>, &Option<Args>)> {
+            meta.iter()
+                .filter_map(|item| match *item {
+                    MetaItem::Attribute {
+                        ref qualified,
+                        ref arguments,
+                    } => Some((qualified, arguments)),
+                    MetaItem::Comments(_) => None,
+                })
+                .collect()
+        }
+        self.ordered("annotations", &attributes(a), &attributes(b), |this, a, b| {
+            this.qualified(a.0, b.0)?;
+            this.option("annotation arguments", a.1, b.1, |this, a, b| this.args(a, b))
+        })
+    }
+
+    fn qualifieds(&mut self, what: &str, a: &[Node<Qualified>], b: &[Node<Qualified>]) -> EquivResult {
+        self.ordered(what, a, b, |this, a, b| this.qualified(a, b))
+    }
+
+    fn qualified(&mut self, a: &Qualified, b: &Qualified) -> EquivResult {
+        self.option("prefix", &a.prefix, &b.prefix, |this, a, b| {
+            this.qualified(a, b)
+        })?;
+        self.symbol("name", a.name, b.name)?;
+        self.types(&a.params, &b.params)
+    }
+
+    fn generics(&mut self, a: &[Node<TypeParameter>], b: &[Node<TypeParameter>]) -> EquivResult {
+        self.ordered("type parameters", a, b, |this, a, b| {
+            this.meta(&a.meta, &b.meta)?;
+            this.symbol("type parameter", a.name, b.name)?;
+            this.option("bound", &a.extends, &b.extends, |this, a, b| {
+                this.qualified(a, b)
+            })
+        })
+    }
+
+    fn types(&mut self, a: &[Node<Type>], b: &[Node<Type>]) -> EquivResult {
+        self.ordered("type arguments", a, b, |this, a, b| this.ty(a, b))
+    }
+
+    fn ty(&mut self, a: &Type, b: &Type) -> EquivResult {
+        match (a, b) {
+            (&Type::Path(ref a), &Type::Path(ref b)) => self.qualified(a, b),
+            (&Type::FunctionOld(ref a), &Type::FunctionOld(ref b)) |
+            (&Type::Function(ref a), &Type::Function(ref b)) => self.fn_sig(a, b),
+            (
+                &Type::Record {
+                    positional: ref positional_a,
+                    named: ref named_a,
+                },
+                &Type::Record {
+                    positional: ref positional_b,
+                    named: ref named_b,
+                },
+            ) => {
+                self.ordered("positional fields", positional_a, positional_b, |this, a, b| {
+                    this.ty(&a.0, &b.0)?;
+                    this.option("field name", &a.1, &b.1, |this, &a, &b| {
+                        this.symbol("field name", a, b)
+                    })
+                })?;
+                self.unordered(
+                    "named field",
+                    named_a,
+                    named_b,
+                    |field| field.1.to_string(),
+                    |this, a, b| this.ty(&a.0, &b.0),
+                )
+            }
+            (&Type::Infer, &Type::Infer) => Ok(()),
+            _ => self.diverge("different kinds of types".to_string()),
+        }
+    }
+
+    fn function(&mut self, a: &Function, b: &Function) -> EquivResult {
+        self.eq("function name", fn_name_key(a.name), fn_name_key(b.name))?;
+        self.generics(&a.generics, &b.generics)?;
+        self.fn_sig(&a.sig, &b.sig)?;
+        self.option("body", &a.body, &b.body, |this, a, b| this.fn_body(a, b))
+    }
+
+    fn fn_sig(&mut self, a: &FnSig, b: &FnSig) -> EquivResult {
+        self.ty(&a.return_type, &b.return_type)?;
+        self.ordered("required parameters", &a.required, &b.required, |this, a, b| {
+            this.arg_def(a, b)
+        })?;
+        self.ordered("optional parameters", &a.optional, &b.optional, |this, a, b| {
+            this.arg_def(a, b)
+        })?;
+        if !a.optional.is_empty() {
+            self.eq("optional parameter kind", a.optional_kind, b.optional_kind)?;
+        }
+        self.eq("async", a.async, b.async)?;
+        self.eq("generator", a.generator, b.generator)
+    }
+
+    fn arg_def(&mut self, a: &ArgDef, b: &ArgDef) -> EquivResult {
+        self.meta(&a.meta, &b.meta)?;
+        self.eq("covariant", a.covariant, b.covariant)?;
+        self.var_type(&a.ty, &b.ty)?;
+        self.eq("`this.`", a.field, b.field)?;
+        self.var_def(&a.var, &b.var)
+    }
+
+    fn var_type(&mut self, a: &VarType, b: &VarType) -> EquivResult {
+        self.eq("modifier", a.fcv, b.fcv)?;
+        self.ty(&a.ty, &b.ty)
+    }
+
+    fn var_defs(&mut self, a: &[Node<VarDef>], b: &[Node<VarDef>]) -> EquivResult {
+        self.ordered("variables", a, b, |this, a, b| this.var_def(a, b))
+    }
+
+    fn var_def(&mut self, a: &VarDef, b: &VarDef) -> EquivResult {
+        self.symbol("variable", a.name, b.name)?;
+        self.option("initializer", &a.init, &b.init, |this, a, b| this.expr(a, b))
+    }
+
+    fn fn_body(&mut self, a: &FnBody, b: &FnBody) -> EquivResult {
+        match (a, b) {
+            (&FnBody::Arrow(ref a), &FnBody::Arrow(ref b)) => self.expr(a, b),
+            (&FnBody::Block(ref a), &FnBody::Block(ref b)) => {
+                self.statement(&a.filled(), &b.filled())
+            }
+            (&FnBody::Native(ref a), &FnBody::Native(ref b)) => {
+                self.option("native name", a, b, |this, a, b| this.string_literal(a, b))
+            }
+            _ => self.diverge("different kinds of function bodies".to_string()),
+        }
+    }
+
+    fn statements(&mut self, a: &[Node<Statement>], b: &[Node<Statement>]) -> EquivResult {
+        let a = a.iter().filter_map(strip_statement).collect::<Vec<_>>();
+        let b = b.iter().filter_map(strip_statement).collect::<Vec<_>>();
+        self.ordered("statements", &a, &b, |this, a, b| this.statement(a, b))
+    }
+
+    fn statement(&mut self, a: &Node<Statement>, b: &Node<Statement>) -> EquivResult {
+        let (a, b) = match (strip_statement(a), strip_statement(b)) {
+            (Some(a), Some(b)) => (a, b),
+            (None, None) => return Ok(()),
+            _ => return self.diverge("statement only on one side".to_string()),
+        };
+        self.located(a, b, |this| this.statement_kind(a, b))
+    }
+
+    fn statement_kind(&mut self, a: &Statement, b: &Statement) -> EquivResult {
+        match (a, b) {
+            (&Statement::Block(ref a), &Statement::Block(ref b)) => self.statements(a, b),
+            (&Statement::Vars(ref var_type_a, ref vars_a), &Statement::Vars(ref var_type_b, ref vars_b)) => {
+                self.var_type(var_type_a, var_type_b)?;
+                self.var_defs(vars_a, vars_b)
+            }
+            (&Statement::Function(ref a), &Statement::Function(ref b)) => self.function(a, b),
+            (
+                &Statement::For(await_a, ref for_a, ref body_a),
+                &Statement::For(await_b, ref for_b, ref body_b),
+            ) => {
+                self.eq("await", await_a, await_b)?;
+                match (for_a, for_b) {
+                    (
+                        &ForLoop::CLike(ref init_a, ref cond_a, ref update_a),
+                        &ForLoop::CLike(ref init_b, ref cond_b, ref update_b),
+                    ) => {
+                        self.statement(init_a, init_b)?;
+                        self.option("condition", cond_a, cond_b, |this, a, b| this.expr(a, b))?;
+                        self.exprs(update_a, update_b)?;
+                    }
+                    (&ForLoop::In(name_a, ref expr_a), &ForLoop::In(name_b, ref expr_b)) => {
+                        self.symbol("loop variable", name_a, name_b)?;
+                        self.expr(expr_a, expr_b)?;
+                    }
+                    (
+                        &ForLoop::InVar(ref var_type_a, ref var_a, ref expr_a),
+                        &ForLoop::InVar(ref var_type_b, ref var_b, ref expr_b),
+                    ) => {
+                        self.var_type(var_type_a, var_type_b)?;
+                        self.var_def(var_a, var_b)?;
+                        self.expr(expr_a, expr_b)?;
+                    }
+                    _ => return self.diverge("different kinds of `for` loops".to_string()),
+                }
+                self.statement(body_a, body_b)
+            }
+            (&Statement::While(ref cond_a, ref body_a), &Statement::While(ref cond_b, ref body_b)) |
+            (&Statement::DoWhile(ref body_a, ref cond_a), &Statement::DoWhile(ref body_b, ref cond_b)) => {
+                self.expr(cond_a, cond_b)?;
+                self.statement(body_a, body_b)
+            }
+            (&Statement::Switch(ref expr_a, ref cases_a), &Statement::Switch(ref expr_b, ref cases_b)) => {
+                self.expr(expr_a, expr_b)?;
+                self.ordered("cases", cases_a, cases_b, |this, a, b| {
+                    this.ordered("labels", &a.labels, &b.labels, |this, &a, &b| {
+                        this.symbol("label", a, b)
+                    })?;
+                    this.option("case", &a.value, &b.value, |this, a, b| this.expr(a, b))?;
+                    this.statements(&a.statements, &b.statements)
+                })
+            }
+            (
+                &Statement::If(ref cond_a, ref then_a, ref else_a),
+                &Statement::If(ref cond_b, ref then_b, ref else_b),
+            ) => {
+                self.expr(cond_a, cond_b)?;
+                self.statement(then_a, then_b)?;
+                self.option("`else`", else_a, else_b, |this, a, b| this.statement(a, b))
+            }
+            (&Statement::Rethrow, &Statement::Rethrow) => Ok(()),
+            (&Statement::Try(ref block_a, ref parts_a), &Statement::Try(ref block_b, ref parts_b)) => {
+                self.statement(block_a, block_b)?;
+                self.ordered("`catch` clauses", parts_a, parts_b, |this, a, b| {
+                    this.option("`on` type", &a.on, &b.on, |this, a, b| this.ty(a, b))?;
+                    this.option("`catch`", &a.catch, &b.catch, |this, a, b| {
+                        this.var_def(&a.exception, &b.exception)?;
+                        this.option("stack trace", &a.trace, &b.trace, |this, a, b| {
+                            this.var_def(a, b)
+                        })
+                    })?;
+                    this.statement(&a.block, &b.block)
+                })
+            }
+            (&Statement::Break(ref a), &Statement::Break(ref b)) |
+            (&Statement::Continue(ref a), &Statement::Continue(ref b)) => {
+                self.option("label", a, b, |this, &a, &b| this.symbol("label", a, b))
+            }
+            (&Statement::Return(ref a), &Statement::Return(ref b)) |
+            (&Statement::Expression(ref a), &Statement::Expression(ref b)) => {
+                self.option("expression", a, b, |this, a, b| this.expr(a, b))
+            }
+            (&Statement::Yield(ref a), &Statement::Yield(ref b)) |
+            (&Statement::YieldEach(ref a), &Statement::YieldEach(ref b)) => self.expr(a, b),
+            (&Statement::Assert(ref a), &Statement::Assert(ref b)) => self.args(a, b),
+            (
+                &Statement::Labelled(label_a, ref statement_a),
+                &Statement::Labelled(label_b, ref statement_b),
+            ) => {
+                self.symbol("label", label_a, label_b)?;
+                self.statement(statement_a, statement_b)
+            }
+            _ => self.diverge("different kinds of statements".to_string()),
+        }
+    }
+
+    fn exprs(&mut self, a: &[Node<Expr>], b: &[Node<Expr>]) -> EquivResult {
+        self.ordered("expressions", a, b, |this, a, b| this.expr(a, b))
+    }
+
+    fn expr(&mut self, a: &Node<Expr>, b: &Node<Expr>) -> EquivResult {
+        let (a, b) = (strip_expr(a), strip_expr(b));
+        self.located(a, b, |this| this.expr_kind(a, b))
+    }
+
+    fn expr_kind(&mut self, a: &Expr, b: &Expr) -> EquivResult {
+        // `new Foo()` and `Foo()` create the same instance.
+        if is_new(a) != is_new(b) {
+            if let (Some((path_a, args_a)), Some((path_b, args_b))) = (creation(a), creation(b)) {
+                self.ordered("path segments", &path_a, &path_b, |this, a, b| {
+                    this.symbol("path segment", a.0, b.0)?;
+                    this.types(a.1, b.1)
+                })?;
+                return self.args(args_a, args_b);
+            }
+        }
+
+        match (a, b) {
+            (&Expr::Unary(op_a, ref a), &Expr::Unary(op_b, ref b)) => {
+                self.eq("operator", format!("{:?}", op_a), format!("{:?}", op_b))?;
+                self.expr(a, b)
+            }
+            (
+                &Expr::Binary(op_a, ref left_a, ref right_a),
+                &Expr::Binary(op_b, ref left_b, ref right_b),
+            ) => {
+                self.eq("operator", op_a, op_b)?;
+                self.expr(left_a, left_b)?;
+                self.expr(right_a, right_b)
+            }
+            (
+                &Expr::Conditional(ref cond_a, ref then_a, ref else_a),
+                &Expr::Conditional(ref cond_b, ref then_b, ref else_b),
+            ) => {
+                self.expr(cond_a, cond_b)?;
+                self.expr(then_a, then_b)?;
+                self.expr(else_a, else_b)
+            }
+            (&Expr::Is(ref a, ref ty_a), &Expr::Is(ref b, ref ty_b)) |
+            (&Expr::IsNot(ref a, ref ty_a), &Expr::IsNot(ref b, ref ty_b)) |
+            (&Expr::As(ref a, ref ty_a), &Expr::As(ref b, ref ty_b)) => {
+                self.expr(a, b)?;
+                self.ty(ty_a, ty_b)
+            }
+            (&Expr::Suffix(ref a, ref suffix_a), &Expr::Suffix(ref b, ref suffix_b)) => {
+                self.expr(a, b)?;
+                self.suffix(suffix_a, suffix_b)
+            }
+            (&Expr::Identifier(a), &Expr::Identifier(b)) => self.symbol("identifier", a, b),
+            (&Expr::Closure(ref sig_a, ref body_a), &Expr::Closure(ref sig_b, ref body_b)) => {
+                self.fn_sig(sig_a, sig_b)?;
+                self.fn_body(body_a, body_b)
+            }
+            (
+                &Expr::New {
+                    const_: const_a,
+                    path: ref path_a,
+                    args: ref args_a,
+                },
+                &Expr::New {
+                    const_: const_b,
+                    path: ref path_b,
+                    args: ref args_b,
+                },
+            ) => {
+                self.eq("const", const_a, const_b)?;
+                self.qualified(path_a, path_b)?;
+                self.args(args_a, args_b)
+            }
+            (
+                &Expr::List {
+                    const_: const_a,
+                    element_ty: ref ty_a,
+                    elements: ref elements_a,
+                },
+                &Expr::List {
+                    const_: const_b,
+                    element_ty: ref ty_b,
+                    elements: ref elements_b,
+                },
+            ) => {
+                self.eq("const", const_a, const_b)?;
+                self.option("element type", ty_a, ty_b, |this, a, b| this.ty(a, b))?;
+                self.exprs(elements_a, elements_b)
+            }
+            (
+                &Expr::Map {
+                    const_: const_a,
+                    kv_ty: ref ty_a,
+                    kv: ref kv_a,
+                },
+                &Expr::Map {
+                    const_: const_b,
+                    kv_ty: ref ty_b,
+                    kv: ref kv_b,
+                },
+            ) => {
+                self.eq("const", const_a, const_b)?;
+                self.option("key and value types", ty_a, ty_b, |this, a, b| {
+                    this.ty(&a.0, &b.0)?;
+                    this.ty(&a.1, &b.1)
+                })?;
+                self.ordered("entries", kv_a, kv_b, |this, a, b| {
+                    this.expr(&a.0, &b.0)?;
+                    this.expr(&a.1, &b.1)
+                })
+            }
+            (&Expr::Int(a), &Expr::Int(b)) => self.symbol("integer", a, b),
+            (&Expr::Double(a), &Expr::Double(b)) => self.symbol("double", a, b),
+            (&Expr::String(ref a), &Expr::String(ref b)) => {
+                self.ordered("adjacent strings", a, b, |this, a, b| this.string_literal(a, b))
+            }
+            (&Expr::Symbol(ref a), &Expr::Symbol(ref b)) => {
+                self.eq("symbol", format!("{:?}", a), format!("{:?}", b))
+            }
+            (&Expr::Paren(ref a), &Expr::Paren(ref b)) |
+            (&Expr::Throw(ref a), &Expr::Throw(ref b)) => self.expr(a, b),
+            (&Expr::Record(ref a), &Expr::Record(ref b)) => self.args(a, b),
+            (&Expr::Switch(ref a, ref cases_a), &Expr::Switch(ref b, ref cases_b)) => {
+                self.expr(a, b)?;
+                self.ordered("cases", cases_a, cases_b, |this, a, b| {
+                    this.pattern(&a.pattern, &b.pattern)?;
+                    this.option("guard", &a.guard, &b.guard, |this, a, b| this.expr(a, b))?;
+                    this.expr(&a.body, &b.body)
+                })
+            }
+            (&Expr::Cascade(ref a, ref cascade_a), &Expr::Cascade(ref b, ref cascade_b)) => {
+                self.expr(a, b)?;
+                self.ordered("cascade sections", &cascade_a.suffixes, &cascade_b.suffixes, |this, a, b| {
+                    this.suffix(a, b)
+                })?;
+                self.option("cascade assignment", &cascade_a.assign, &cascade_b.assign, |this, a, b| {
+                    this.eq("operator", a.0, b.0)?;
+                    this.expr(&a.1, &b.1)
+                })
+            }
+            _ => self.diverge("different kinds of expressions".to_string()),
+        }
+    }
+
+    fn pattern(&mut self, a: &Node<Pattern>, b: &Node<Pattern>) -> EquivResult {
+        self.located(a, b, |this| this.pattern_kind(a, b))
+    }
+
+    fn pattern_kind(&mut self, a: &Pattern, b: &Pattern) -> EquivResult {
+        match (a, b) {
+            (&Pattern::Wildcard, &Pattern::Wildcard) => Ok(()),
+            (&Pattern::Var(ref ty_a, ref var_a), &Pattern::Var(ref ty_b, ref var_b)) => {
+                self.var_type(ty_a, ty_b)?;
+                self.var_def(var_a, var_b)
+            }
+            (&Pattern::Const(ref a), &Pattern::Const(ref b)) => self.expr(a, b),
+            (&Pattern::Relational(op_a, ref a), &Pattern::Relational(op_b, ref b)) => {
+                self.eq("operator", op_a, op_b)?;
+                self.expr(a, b)
+            }
+            (&Pattern::Or(ref left_a, ref right_a), &Pattern::Or(ref left_b, ref right_b)) |
+            (&Pattern::And(ref left_a, ref right_a), &Pattern::And(ref left_b, ref right_b)) => {
+                self.pattern(left_a, left_b)?;
+                self.pattern(right_a, right_b)
+            }
+            (&Pattern::Cast(ref a, ref ty_a), &Pattern::Cast(ref b, ref ty_b)) => {
+                self.pattern(a, b)?;
+                self.ty(ty_a, ty_b)
+            }
+            (&Pattern::NullCheck(ref a), &Pattern::NullCheck(ref b)) |
+            (&Pattern::NullAssert(ref a), &Pattern::NullAssert(ref b)) |
+            (&Pattern::Paren(ref a), &Pattern::Paren(ref b)) => self.pattern(a, b),
+            (&Pattern::List(ref a), &Pattern::List(ref b)) => {
+                self.ordered("elements", a, b, |this, a, b| this.pattern(a, b))
+            }
+            (&Pattern::Rest(ref a), &Pattern::Rest(ref b)) => {
+                self.option("rest pattern", a, b, |this, a, b| this.pattern(a, b))
+            }
+            (&Pattern::Map(ref a), &Pattern::Map(ref b)) => {
+                self.ordered("entries", a, b, |this, a, b| {
+                    this.expr(&a.0, &b.0)?;
+                    this.pattern(&a.1, &b.1)
+                })
+            }
+            (&Pattern::Record(ref a), &Pattern::Record(ref b)) => self.pattern_fields(a, b),
+            (
+                &Pattern::Object(ref qualified_a, ref a),
+                &Pattern::Object(ref qualified_b, ref b),
+            ) => {
+                self.qualified(qualified_a, qualified_b)?;
+                self.pattern_fields(a, b)
+            }
+            _ => self.diverge("different kinds of patterns".to_string()),
+        }
+    }
+
+    fn pattern_fields(&mut self, a: &[PatternField], b: &[PatternField]) -> EquivResult {
+        self.ordered("fields", a, b, |this, a, b| match (a, b) {
+            (&PatternField::Positional(ref a), &PatternField::Positional(ref b)) => {
+                this.pattern(a, b)
+            }
+            (&PatternField::Named(name_a, ref a), &PatternField::Named(name_b, ref b)) => {
+                this.option("field name", &name_a, &name_b, |this, &a, &b| {
+                    this.symbol("field name", a, b)
+                })?;
+                this.pattern(a, b)
+            }
+            _ => this.diverge("different kinds of fields".to_string()),
+        })
+    }
+
+    fn suffix(&mut self, a: &Suffix, b: &Suffix) -> EquivResult {
+        match (a, b) {
+            (&Suffix::Index(ref a), &Suffix::Index(ref b)) => self.expr(a, b),
+            (&Suffix::Field(a), &Suffix::Field(b)) |
+            (&Suffix::FieldIfNotNull(a), &Suffix::FieldIfNotNull(b)) => self.symbol("field", a, b),
+            (&Suffix::Call(ref generics_a, ref args_a), &Suffix::Call(ref generics_b, ref args_b)) => {
+                self.types(generics_a, generics_b)?;
+                self.args(args_a, args_b)
+            }
+            _ => self.diverge("different kinds of suffixes".to_string()),
+        }
+    }
+
+    fn args(&mut self, a: &Args, b: &Args) -> EquivResult {
+        self.exprs(&a.unnamed, &b.unnamed)?;
+        self.unordered(
+            "named argument",
+            &a.named,
+            &b.named,
+            |arg| arg.name.to_string(),
+            |this, a, b| this.expr(&a.expr, &b.expr),
+        )
+    }
+
+    fn string_literal(&mut self, a: &StringLiteral, b: &StringLiteral) -> EquivResult {
+        let spans = self.spans;
+        self.spans = (Some(a.prefix), Some(b.prefix));
+        let result: EquivResult = do catch {
+            self.eq("raw", a.raw, b.raw)?;
+            self.eq("string", snippet(a.prefix), snippet(b.prefix))?;
+            self.ordered("interpolations", &a.interpolated, &b.interpolated, |this, a, b| {
+                this.expr(&a.0, &b.0)?;
+                this.spans = (Some(a.1), Some(b.1));
+                this.eq("string", snippet(a.1), snippet(b.1))
+            })?;
+        };
+        self.spans = spans;
+        result
+    }
+}
+
+fn snippet(span: Span) -> String {
+    ::codemap().span_to_snippet(span.to_span()).unwrap()
+}
+
+fn item_key(item: &Item) -> String {
+    match *item {
+        Item::LibraryName { .. } => "library".to_string(),
+        Item::Import(_, ref import) => format!("import {}", snippet(import.uri.prefix)),
+        Item::Export(_, ref uri, ..) => format!("export {}", snippet(uri.prefix)),
+        Item::Part { ref uri, .. } => format!("part {}", snippet(uri.prefix)),
+        Item::PartOf { .. } => "part of".to_string(),
+        Item::Class { name, .. } | Item::MixinClass { name, .. } => format!("class {}", name),
+        Item::Mixin { name, .. } => format!("mixin {}", name),
+        Item::Extension { name: Some(name), .. } => format!("extension {}", name),
+        Item::Extension {
+            name: None, ref on, ..
+        } => format!("extension on {:?}", **on),
+        Item::Enum { name, .. } => format!("enum {}", name),
+        Item::TypeAlias { name, .. } => format!("typedef {}", name),
+        Item::Function { ref function, .. } => format!("function {}", fn_name_key(function.name)),
+        Item::Vars(_, _, ref vars) => format!("variable {}", vars[0].name),
+    }
+}
+
+fn member_key(member: &ClassMember) -> String {
+    match *member {
+        ClassMember::Redirect { name, .. } | ClassMember::Constructor { name, .. } => {
+            match name {
+                Some(name) => format!("constructor {}", name),
+                None => "constructor".to_string(),
+            }
+        }
+        ClassMember::Method(_, _, ref function) => format!("method {}", fn_name_key(function.name)),
+        ClassMember::Fields {
+            ref initializers, ..
+        } => format!("field {}", initializers[0].name),
+    }
+}
+
+fn fn_name_key(name: FnName) -> String {
+    match name {
+        FnName::Regular(name) => name.to_string(),
+        FnName::Getter(name) => format!("get {}", name),
+        FnName::Setter(name) => format!("set {}", name),
+        FnName::Operator(op) => format!("operator {:?}", op),
+    }
+}
+
+fn strip_expr(expr: &Node<Expr>) -> &Node<Expr> {
+    match **expr {
+        Expr::Comments(_, ref expr) => strip_expr(expr),
+        _ => expr,
+    }
+}
+
+fn strip_statement(statement: &Node<Statement>) -> Option<&Node<Statement>> {
+    match **statement {
+        Statement::Comments(_, Some(ref statement)) => strip_statement(statement),
+        Statement::Comments(_, None) => None,
+        _ => Some(statement),
+    }
+}
+
+fn is_new(expr: &Expr) -> bool {
+    match *expr {
+        Expr::New { .. } => true,
+        _ => false,
+    }
+}
+
+/// Splits an instance creation written with or without `new` into its
+/// path segments (with their type arguments) and arguments.
+fn creation(expr: &Expr) -> Option<(Vec<(Symbol, &[Node<Type>])>, &Args)> {
+    fn qualified_segments<'a>(qualified: &'a Qualified, segments: &mut Vec<(Symbol, &'a [Node<Type>])>) {
+        if let Some(ref prefix) = qualified.prefix {
+            qualified_segments(prefix, segments);
+        }
+        segments.push((qualified.name, &qualified.params[..]));
+    }
+    fn callee_segments<'a>(callee: &'a Expr, segments: &mut Vec<(Symbol, &'a [Node<Type>])>) -> bool {
+        match *callee {
+            Expr::Identifier(name) => {
+                segments.push((name, &[]));
+                true
+            }
+            Expr::Suffix(ref callee, Suffix::Field(name)) => {
+                if !callee_segments(callee, segments) {
+                    return false;
+                }
+                segments.push((name, &[]));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    let mut segments = vec![];
+    match *expr {
+        Expr::New {
+            const_: false,
+            ref path,
+            ref args,
+        } => {
+            qualified_segments(path, &mut segments);
+            Some((segments, args))
+        }
+        Expr::Suffix(ref callee, Suffix::Call(ref generics, ref args)) => {
+            if !callee_segments(callee, &mut segments) {
+                return None;
+            }
+            if !generics.is_empty() {
+                segments.last_mut().unwrap().1 = &generics[..];
+            }
+            Some((segments, args))
+        }
+        _ => None,
+    }
+}
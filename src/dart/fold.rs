@@ -1,7 +1,7 @@
-use dart::ast::{ArgDef, Args, Cascade, CatchPart, ClassMember, ConstructorInitializer, Expr,
-                FnBody, FnSig, ForLoop, Function, Item, Meta, MetaItem, Module, NamedArg,
-                Qualified, Statement, StringLiteral, Suffix, SwitchCase, TryPart, Type,
-                TypeParameter, VarDef, VarType};
+use dart::ast::{ArgDef, Args, Cascade, CatchPart, ClassMember, ConstructorInitializer, EnumValue,
+                Expr, FnBody, FnSig, ForLoop, Function, Item, Meta, MetaItem, Module, NamedArg,
+                Pattern, PatternField, Qualified, Statement, StringLiteral, Suffix, SwitchCase,
+                SwitchExprCase, TryPart, Type, TypeParameter, VarDef, VarType};
 use node::Node;
 
 pub trait Folder: Sized {
@@ -11,6 +11,9 @@ pub trait Folder: Sized {
     fn dart_item(&mut self, item: Node<Item>) -> Node<Item> {
         item.super_fold(self)
     }
+    fn dart_enum_value(&mut self, value: &EnumValue) -> EnumValue {
+        value.super_fold(self)
+    }
     fn dart_class_member(&mut self, class_member: Node<ClassMember>) -> Node<ClassMember> {
         class_member.super_fold(self)
     }
@@ -89,6 +92,15 @@ pub trait Folder: Sized {
     fn dart_named_arg(&mut self, named_arg: &NamedArg) -> NamedArg {
         named_arg.super_fold(self)
     }
+    fn dart_switch_expr_case(&mut self, case: &SwitchExprCase) -> SwitchExprCase {
+        case.super_fold(self)
+    }
+    fn dart_pattern(&mut self, pattern: Node<Pattern>) -> Node<Pattern> {
+        pattern.super_fold(self)
+    }
+    fn dart_pattern_field(&mut self, field: &PatternField) -> PatternField {
+        field.super_fold(self)
+    }
 }
 
 pub trait Fold {
@@ -96,9 +108,22 @@ pub trait Fold {
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self;
 }
 
+/// Carries the span of `original` over to the node it was folded into,
+/// unless the folder already gave it one.
+pub fn keep_span<T: 'static, U: 'static>(original: &Node<T>, folded: Node<U>) -> Node<U> {
+    if folded.origin_span().is_none() {
+        if let Some(span) = original.span().get() {
+            folded.span().set(span);
+        } else if let Some(span) = original.derived_from().get() {
+            folded.derived_from().set(span);
+        }
+    }
+    folded
+}
+
 impl Fold for Node<Module> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dart_module(self.clone())
+        keep_span(self, folder.dart_module(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         let Module {
@@ -116,7 +141,7 @@ impl Fold for Node<Module> {
 
 impl Fold for Node<Item> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dart_item(self.clone())
+        keep_span(self, folder.dart_item(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         match **self {
@@ -127,11 +152,14 @@ impl Fold for Node<Item> {
             Item::Import(ref meta, ref import) => {
                 Node::new(Item::Import(meta.fold(folder), import.clone()))
             }
-            Item::Export(ref meta, ref str_lit, ref import_filters) => Node::new(Item::Export(
-                meta.fold(folder),
-                str_lit.clone(),
-                import_filters.clone(),
-            )),
+            Item::Export(ref meta, ref str_lit, ref configs, ref import_filters) => {
+                Node::new(Item::Export(
+                    meta.fold(folder),
+                    str_lit.clone(),
+                    configs.clone(),
+                    import_filters.clone(),
+                ))
+            }
             Item::Part {
                 ref meta,
                 ref uri,
@@ -141,13 +169,18 @@ impl Fold for Node<Item> {
                 uri: uri.clone(),
                 module: module.fold(folder),
             }),
-            Item::PartOf { ref meta, ref path } => Node::new(Item::PartOf {
+            Item::PartOf {
+                ref meta,
+                ref library,
+            } => Node::new(Item::PartOf {
                 meta: meta.fold(folder),
-                path: path.clone(),
+                library: library.clone(),
             }),
             Item::Class {
                 ref meta,
                 abstract_,
+                modifier,
+                mixin,
                 name,
                 ref generics,
                 ref superclass,
@@ -157,6 +190,8 @@ impl Fold for Node<Item> {
             } => Node::new(Item::Class {
                 meta: meta.fold(folder),
                 abstract_,
+                modifier,
+                mixin,
                 name,
                 generics: generics
                     .iter()
@@ -175,6 +210,7 @@ impl Fold for Node<Item> {
             Item::MixinClass {
                 ref meta,
                 abstract_,
+                modifier,
                 name,
                 ref generics,
                 ref mixins,
@@ -182,6 +218,7 @@ impl Fold for Node<Item> {
             } => Node::new(Item::MixinClass {
                 meta: meta.fold(folder),
                 abstract_,
+                modifier,
                 name,
                 generics: generics
                     .iter()
@@ -193,17 +230,70 @@ impl Fold for Node<Item> {
                     .map(|interface| interface.fold(folder))
                     .collect(),
             }),
+            Item::Mixin {
+                ref meta,
+                base,
+                name,
+                ref generics,
+                ref constraints,
+                ref interfaces,
+                ref members,
+            } => Node::new(Item::Mixin {
+                meta: meta.fold(folder),
+                base,
+                name,
+                generics: generics
+                    .iter()
+                    .map(|generic| generic.fold(folder))
+                    .collect(),
+                constraints: constraints
+                    .iter()
+                    .map(|constraint| constraint.fold(folder))
+                    .collect(),
+                interfaces: interfaces
+                    .iter()
+                    .map(|interface| interface.fold(folder))
+                    .collect(),
+                members: members.iter().map(|member| member.fold(folder)).collect(),
+            }),
+            Item::Extension {
+                ref meta,
+                name,
+                ref generics,
+                ref on,
+                ref members,
+            } => Node::new(Item::Extension {
+                meta: meta.fold(folder),
+                name,
+                generics: generics
+                    .iter()
+                    .map(|generic| generic.fold(folder))
+                    .collect(),
+                on: on.fold(folder),
+                members: members.iter().map(|member| member.fold(folder)).collect(),
+            }),
             Item::Enum {
                 ref meta,
                 name,
+                ref generics,
+                ref mixins,
+                ref interfaces,
                 ref values,
+                ref members,
             } => Node::new(Item::Enum {
                 meta: meta.fold(folder),
                 name,
-                values: values
+                generics: generics
                     .iter()
-                    .map(|&(ref meta, val)| (meta.fold(folder), val))
+                    .map(|generic| generic.fold(folder))
                     .collect(),
+                mixins: mixins.iter().map(|mixin| mixin.fold(folder)).collect(),
+                interfaces: interfaces
+                    .iter()
+                    .map(|interface| interface.fold(folder))
+                    .collect(),
+                values: values.iter().map(|value| value.fold(folder)).collect(),
+                members: members.iter().map(|member| member.fold(folder)).collect(),
             }),
             Item::TypeAlias {
                 ref meta,
@@ -239,9 +329,31 @@ impl Fold for Node<Item> {
     }
 }
 
+impl Fold for EnumValue {
+    fn fold<F: Folder>(&self, folder: &mut F) -> Self {
+        folder.dart_enum_value(self)
+    }
+    fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
+        let EnumValue {
+            ref meta,
+            name,
+            ref generics,
+            constructor,
+            ref args,
+        } = *self;
+        EnumValue {
+            meta: meta.fold(folder),
+            name,
+            generics: generics.iter().map(|ty| ty.fold(folder)).collect(),
+            constructor,
+            args: args.as_ref().map(|args| args.fold(folder)),
+        }
+    }
+}
+
 impl Fold for Node<ClassMember> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dart_class_member(self.clone())
+        keep_span(self, folder.dart_class_member(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         match **self {
@@ -356,7 +468,7 @@ impl Fold for Meta {
 
 impl Fold for Node<Qualified> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dart_qualified(self.clone())
+        keep_span(self, folder.dart_qualified(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         let Qualified {
@@ -377,7 +489,7 @@ impl Fold for Node<Qualified> {
 
 impl Fold for Node<TypeParameter> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dart_type_parameter(self.clone())
+        keep_span(self, folder.dart_type_parameter(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         let TypeParameter {
@@ -404,13 +516,26 @@ impl Fold for Vec<Node<TypeParameter>> {
 
 impl Fold for Node<Type> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dart_type(self.clone())
+        keep_span(self, folder.dart_type(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         match **self {
             Type::Path(ref qualified) => Node::new(Type::Path(qualified.fold(folder))),
             Type::FunctionOld(ref sig) => Node::new(Type::FunctionOld(sig.fold(folder))),
             Type::Function(ref sig) => Node::new(Type::Function(sig.fold(folder))),
+            Type::Record {
+                ref positional,
+                ref named,
+            } => Node::new(Type::Record {
+                positional: positional
+                    .iter()
+                    .map(|&(ref ty, name)| (ty.fold(folder), name))
+                    .collect(),
+                named: named
+                    .iter()
+                    .map(|&(ref ty, name)| (ty.fold(folder), name))
+                    .collect(),
+            }),
             Type::Infer => Node::new(Type::Infer),
         }
     }
@@ -418,7 +543,7 @@ impl Fold for Node<Type> {
 
 impl Fold for Node<Function> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dart_function(self.clone())
+        keep_span(self, folder.dart_function(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         let Function {
@@ -494,7 +619,7 @@ impl Fold for FnBody {
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         match *self {
             FnBody::Arrow(ref expr) => FnBody::Arrow(expr.fold(folder)),
-            FnBody::Block(ref stmt) => FnBody::Block(stmt.fold(folder)),
+            FnBody::Block(ref stmt) => FnBody::Block(stmt.filled().fold(folder)),
             FnBody::Native(ref str_lit) => {
                 FnBody::Native(str_lit.as_ref().map(|str_lit| str_lit.fold(folder)))
             }
@@ -551,7 +676,7 @@ impl Fold for ForLoop {
 
 impl Fold for Node<Statement> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dart_statement(self.clone())
+        keep_span(self, folder.dart_statement(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         match **self {
@@ -635,7 +760,7 @@ impl Fold for Vec<Node<Statement>> {
 
 impl Fold for Node<VarDef> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dart_var_def(self.clone())
+        keep_span(self, folder.dart_var_def(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         let VarDef { name, ref init } = **self;
@@ -648,7 +773,7 @@ impl Fold for Node<VarDef> {
 
 impl Fold for Node<Expr> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dart_expr(self.clone())
+        keep_span(self, folder.dart_expr(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         match **self {
@@ -712,12 +837,18 @@ impl Fold for Node<Expr> {
                     .map(|&(ref k, ref v)| (k.fold(folder), v.fold(folder)))
                     .collect(),
             }),
-            Expr::Number(name) => Node::new(Expr::Number(name)),
+            Expr::Int(value) => Node::new(Expr::Int(value)),
+            Expr::Double(value) => Node::new(Expr::Double(value)),
             Expr::String(ref parts) => Node::new(Expr::String(
                 parts.iter().map(|part| part.fold(folder)).collect(),
             )),
             Expr::Symbol(ref content) => Node::new(Expr::Symbol(content.clone())),
             Expr::Paren(ref expr) => Node::new(Expr::Paren(expr.fold(folder))),
+            Expr::Record(ref args) => Node::new(Expr::Record(args.fold(folder))),
+            Expr::Switch(ref expr, ref cases) => Node::new(Expr::Switch(
+                expr.fold(folder),
+                cases.iter().map(|case| case.fold(folder)).collect(),
+            )),
             Expr::Throw(ref expr) => Node::new(Expr::Throw(expr.fold(folder))),
             Expr::Cascade(ref expr, ref cascade) => {
                 Node::new(Expr::Cascade(expr.fold(folder), cascade.fold(folder)))
@@ -734,10 +865,12 @@ impl Fold for Args {
         let Args {
             ref unnamed,
             ref named,
+            ref trailing_comments,
         } = *self;
         Args {
             unnamed: unnamed.iter().map(|unnamed| unnamed.fold(folder)).collect(),
             named: named.iter().map(|arg| arg.fold(folder)).collect(),
+            trailing_comments: trailing_comments.clone(),
         }
     }
 }
@@ -853,3 +986,84 @@ impl Fold for NamedArg {
         }
     }
 }
+
+impl Fold for SwitchExprCase {
+    fn fold<F: Folder>(&self, folder: &mut F) -> Self {
+        folder.dart_switch_expr_case(self)
+    }
+    fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
+        let SwitchExprCase {
+            ref pattern,
+            ref guard,
+            ref body,
+        } = *self;
+        SwitchExprCase {
+            pattern: pattern.fold(folder),
+            guard: guard.as_ref().map(|guard| guard.fold(folder)),
+            body: body.fold(folder),
+        }
+    }
+}
+
+impl Fold for Node<Pattern> {
+    fn fold<F: Folder>(&self, folder: &mut F) -> Self {
+        keep_span(self, folder.dart_pattern(self.clone()))
+    }
+    fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
+        match **self {
+            Pattern::Wildcard => Node::new(Pattern::Wildcard),
+            Pattern::Var(ref var_ty, ref var) => {
+                Node::new(Pattern::Var(var_ty.fold(folder), var.fold(folder)))
+            }
+            Pattern::Const(ref expr) => Node::new(Pattern::Const(expr.fold(folder))),
+            Pattern::Relational(op, ref expr) => {
+                Node::new(Pattern::Relational(op, expr.fold(folder)))
+            }
+            Pattern::Or(ref a, ref b) => Node::new(Pattern::Or(a.fold(folder), b.fold(folder))),
+            Pattern::And(ref a, ref b) => Node::new(Pattern::And(a.fold(folder), b.fold(folder))),
+            Pattern::Cast(ref pattern, ref ty) => {
+                Node::new(Pattern::Cast(pattern.fold(folder), ty.fold(folder)))
+            }
+            Pattern::NullCheck(ref pattern) => Node::new(Pattern::NullCheck(pattern.fold(folder))),
+            Pattern::NullAssert(ref pattern) => {
+                Node::new(Pattern::NullAssert(pattern.fold(folder)))
+            }
+            Pattern::List(ref elements) => Node::new(Pattern::List(
+                elements
+                    .iter()
+                    .map(|element| element.fold(folder))
+                    .collect(),
+            )),
+            Pattern::Rest(ref rest) => {
+                Node::new(Pattern::Rest(rest.as_ref().map(|rest| rest.fold(folder))))
+            }
+            Pattern::Map(ref kv) => Node::new(Pattern::Map(
+                kv.iter()
+                    .map(|&(ref k, ref v)| (k.fold(folder), v.fold(folder)))
+                    .collect(),
+            )),
+            Pattern::Record(ref fields) => Node::new(Pattern::Record(
+                fields.iter().map(|field| field.fold(folder)).collect(),
+            )),
+            Pattern::Object(ref qualified, ref fields) => Node::new(Pattern::Object(
+                qualified.fold(folder),
+                fields.iter().map(|field| field.fold(folder)).collect(),
+            )),
+            Pattern::Paren(ref pattern) => Node::new(Pattern::Paren(pattern.fold(folder))),
+        }
+    }
+}
+
+impl Fold for PatternField {
+    fn fold<F: Folder>(&self, folder: &mut F) -> Self {
+        folder.dart_pattern_field(self)
+    }
+    fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
+        match *self {
+            PatternField::Positional(ref pattern) => PatternField::Positional(pattern.fold(folder)),
+            PatternField::Named(name, ref pattern) => {
+                PatternField::Named(name, pattern.fold(folder))
+            }
+        }
+    }
+}
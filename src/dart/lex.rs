@@ -2,10 +2,11 @@
 
 use unicode_xid::UnicodeXID;
 use std::fmt;
+use std::ops::Range;
 use std::path::Path;
 use std::rc::Rc;
 use std::str;
-use syntax::codemap::{BytePos, FileMap, FileName, Pos};
+use syntax::codemap::{BytePos, FileName, Pos};
 use syntax::symbol::Symbol;
 use Span;
 
@@ -15,7 +16,8 @@ pub enum Token {
     Comment(Span),
     Punctuation(char),
     Identifier(Symbol),
-    IntegerLiteral(Symbol),
+    /// A number literal, exactly as written (e.g. `0xFF2196F3`, `1_000`, `.5e-3`).
+    NumberLiteral(NumberKind, Symbol),
     StringLiteral {
         contents: Span,
         raw: bool,
@@ -26,13 +28,23 @@ pub enum Token {
     },
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum NumberKind {
+    /// A decimal integer (`42`).
+    Int,
+    /// A number with a fraction or an exponent (`1.5`, `.5`, `2e10`).
+    Double,
+    /// A hexadecimal integer (`0xFF`).
+    Hex,
+}
+
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Token::WhiteSpace(s) | Token::Comment(s) => {
                 write!(f, "{}", ::codemap().span_to_snippet(s.to_span()).unwrap())
             }
-            Token::IntegerLiteral(s) | Token::Identifier(s) => write!(f, "{}", s),
+            Token::NumberLiteral(_, s) | Token::Identifier(s) => write!(f, "{}", s),
             Token::Punctuation(c) => write!(f, "{}", c),
             Token::StringLiteral {
                 contents,
@@ -109,16 +121,30 @@ pub enum ErrorMsg {
     UnterminatedShebang,
     UnterminatedStringLiteral,
     UnterminatedBlockComment,
+    MalformedNumberLiteral,
     UnhandledCharacter(char),
 }
 
+/// A change to a document: the bytes in `range` of the old text were
+/// replaced with `new_len` bytes.
+#[derive(Clone, Debug)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_len: usize,
+}
+
 pub struct Lexer {
-    filemap: Rc<FileMap>,
+    src: Rc<String>,
+    /// The position of the start of `src`.
+    start_pos: BytePos,
     pos: BytePos,
     next_pos: BytePos,
     end: BytePos,
     c: char,
     tokens: Vec<(Span, Token)>,
+    /// Token boundaries at which lexing can stop, because the old tokens
+    /// from there on are still valid (see `relex`).
+    resync_at: Vec<BytePos>,
 }
 
 impl Lexer {
@@ -129,12 +155,14 @@ impl Lexer {
         assert_eq!(begin.fm.start_pos, end.fm.start_pos);
 
         Lexer {
-            filemap: begin.fm,
+            src: begin.fm.src.clone().unwrap(),
+            start_pos: begin.fm.start_pos,
             pos: span.lo,
             next_pos: span.lo,
             end: span.hi,
             c: ' ',
             tokens: vec![],
+            resync_at: vec![],
         }
     }
 
@@ -147,18 +175,143 @@ impl Lexer {
         Ok(Lexer::new(::mk_sp(file.start_pos, file.end_pos)))
     }
 
+    /// Registers `src` as an anonymous file and creates a lexer for it.
+    pub fn from_string(src: String) -> Lexer {
+        let file = ::codemap().new_filemap(FileName::Anon, src);
+        Lexer::new(::mk_sp(file.start_pos, file.end_pos))
+    }
+
+    /// Creates a lexer for `src` without registering it in the `CodeMap`,
+    /// e.g. for a document being edited. The spans of its tokens are byte
+    /// offsets into `src`, which the `CodeMap` (and so `Token`'s `Display`)
+    /// knows nothing about.
+    pub fn detached(src: Rc<String>) -> Lexer {
+        let end = BytePos::from_usize(src.len());
+        Lexer {
+            src,
+            start_pos: BytePos(0),
+            pos: BytePos(0),
+            next_pos: BytePos(0),
+            end,
+            c: ' ',
+            tokens: vec![],
+            resync_at: vec![],
+        }
+    }
+
+    /// Lexes `src`, the text of a document after `edit`, given `old_tokens`,
+    /// the tokens `detached` gave for the text before it. Only the tokens
+    /// around the edited range are lexed again; the others are reused.
+    pub fn relex(
+        src: Rc<String>,
+        old_tokens: &[(Span, Token)],
+        edit: &TextEdit,
+    ) -> LexResult<Vec<(Span, Token)>> {
+        let delta = edit.new_len as isize - edit.range.len() as isize;
+
+        // Lexing can restart from and stop at whitespace outside of string
+        // interpolations, as lexing whitespace doesn't depend on the tokens
+        // before it, and neither does lexing what comes after it.
+        let mut restart = None;
+        let mut resync = vec![];
+        let mut interpolation_depth = 0;
+        for (i, &(span, token)) in old_tokens.iter().enumerate() {
+            if let Token::WhiteSpace(_) = token {
+                if interpolation_depth == 0 {
+                    if span.lo.to_usize() < edit.range.start {
+                        restart = Some(i);
+                    } else if span.lo.to_usize() >= edit.range.end {
+                        resync.push(i);
+                    }
+                }
+            }
+            if let Token::StringLiteral {
+                interpolation_before,
+                interpolation_after,
+                ..
+            } = token
+            {
+                if interpolation_before {
+                    interpolation_depth -= 1;
+                }
+                if interpolation_after {
+                    interpolation_depth += 1;
+                }
+            }
+        }
+
+        let restart = restart.unwrap_or(0);
+        let mut lexer = Lexer::detached(src);
+        if restart > 0 {
+            lexer.pos = old_tokens[restart].0.lo;
+            lexer.next_pos = lexer.pos;
+        }
+        lexer.resync_at = resync
+            .iter()
+            .map(|&i| shift(old_tokens[i].0.lo, delta))
+            .collect();
+        let (tokens, resynced) = lexer.lex()?;
+
+        let mut result = old_tokens[..restart].to_vec();
+        result.extend(tokens);
+        if let Some(pos) = resynced {
+            let i = resync[resync.iter()
+                .position(|&i| shift(old_tokens[i].0.lo, delta) == pos)
+                .unwrap()];
+            result.extend(old_tokens[i..].iter().map(|&(span, token)| {
+                let token = match token {
+                    Token::WhiteSpace(span) => Token::WhiteSpace(shift_span(span, delta)),
+                    Token::Comment(span) => Token::Comment(shift_span(span, delta)),
+                    Token::StringLiteral {
+                        contents,
+                        raw,
+                        triple,
+                        quote,
+                        interpolation_before,
+                        interpolation_after,
+                    } => Token::StringLiteral {
+                        contents: shift_span(contents, delta),
+                        raw,
+                        triple,
+                        quote,
+                        interpolation_before,
+                        interpolation_after,
+                    },
+                    token => token,
+                };
+                (shift_span(span, delta), token)
+            }));
+        }
+        Ok(result)
+    }
+
     fn bump(&mut self) {
         if self.next_pos >= self.end {
             return;
         }
         self.pos = self.next_pos;
-        let i = (self.pos - self.filemap.start_pos).to_usize();
-        let src = self.filemap.src.clone().unwrap();
-        self.c = src[i..].chars().next().unwrap();
+        let i = (self.pos - self.start_pos).to_usize();
+        self.c = self.src[i..].chars().next().unwrap();
         self.next_pos = self.pos + Pos::from_usize(self.c.len_utf8());
     }
 
-    pub fn tokenize(mut self) -> LexResult<Vec<(Span, Token)>> {
+    /// Returns the character after the current one, if any.
+    fn peek(&self) -> Option<char> {
+        if self.next_pos >= self.end {
+            return None;
+        }
+        self.src[(self.next_pos - self.start_pos).to_usize()..]
+            .chars()
+            .next()
+    }
+
+    pub fn tokenize(self) -> LexResult<Vec<(Span, Token)>> {
+        Ok(self.lex()?.0)
+    }
+
+    /// Lexes up to the end, or up to the first position in `resync_at`
+    /// reached at a token boundary, which is returned along the tokens.
+    fn lex(mut self) -> LexResult<(Vec<(Span, Token)>, Option<BytePos>)> {
         struct InterpolationLevel {
             quote: char,
             triple: bool,
@@ -173,7 +326,7 @@ impl Lexer {
                     if true {
                         $otherwise;
                     }
-                    return Ok(self.tokens);
+                    return Ok((self.tokens, None));
                 }
                 self.bump();
             };
@@ -205,6 +358,9 @@ impl Lexer {
         }
 
         loop {
+            if interpolation_levels.is_empty() && self.resync_at.binary_search(&self.pos).is_ok() {
+                return Ok((self.tokens, Some(self.pos)));
+            }
             span.lo = self.pos;
             span.hi = self.next_pos;
 
@@ -221,12 +377,25 @@ impl Lexer {
                 }
             }
 
-            if self.c.is_numeric() {
-                while self.c.is_alphanumeric() {
-                    buffer.push(self.c);
-                    bump!(or put!(Token::IntegerLiteral(Symbol::intern(&buffer))));
+            let starts_number = self.c.is_digit(10) ||
+                self.c == '.' && self.peek().map_or(false, |c| c.is_digit(10));
+            if starts_number {
+                let (token, len) = {
+                    let start = (self.pos - self.start_pos).to_usize();
+                    let end = (self.end - self.start_pos).to_usize();
+                    let text = &self.src[start..end];
+                    match scan_number(text) {
+                        Some((kind, len)) => {
+                            (Token::NumberLiteral(kind, Symbol::intern(&text[..len])), len)
+                        }
+                        None => emit!(ErrorMsg::MalformedNumberLiteral),
+                    }
+                };
+                // Number literals are all ASCII, one byte per character.
+                for _ in 0..len {
+                    bump!(or put!(token));
                 }
-                put!(Token::IntegerLiteral(Symbol::intern(&buffer)));
+                put!(token);
             } else if self.c.is_whitespace() {
                 while self.c.is_whitespace() {
                     bump!(or put!(Token::WhiteSpace(span)));
@@ -298,11 +467,7 @@ impl Lexer {
                             interpolation_after: false,
                             interpolation_before: false,
                         },
-                    )) => if ::codemap()
-                        .span_to_snippet(contents.to_span())
-                        .unwrap()
-                        .is_empty() && prev_quote == quote
-                    {
+                    )) => if contents.lo == contents.hi && prev_quote == quote {
                         triple = true;
                         raw = prev_raw;
                         span.lo = sp.lo;
@@ -370,3 +535,74 @@ impl Lexer {
         }
     }
 }
+
+fn shift(pos: BytePos, delta: isize) -> BytePos {
+    BytePos::from_usize((pos.to_usize() as isize + delta) as usize)
+}
+
+fn shift_span(span: Span, delta: isize) -> Span {
+    ::mk_sp(shift(span.lo, delta), shift(span.hi, delta))
+}
+
+/// Returns the kind and length of the number literal `text` starts with,
+/// or `None` if it's malformed, e.g. `0x`, `1_` or `123abc`.
+fn scan_number(text: &str) -> Option<(NumberKind, usize)> {
+    let bytes = text.as_bytes();
+    let is_digit = |i: usize, hex: bool| {
+        i < bytes.len() && (bytes[i] as char).is_digit(if hex { 16 } else { 10 })
+    };
+    // Skips digits from `start`, and any `_` separators between them.
+    let digits = |start: usize, hex: bool| {
+        let mut i = start;
+        loop {
+            if is_digit(i, hex) {
+                i += 1;
+            } else if i > start && i < bytes.len() && bytes[i] == b'_' {
+                let mut j = i;
+                while j < bytes.len() && bytes[j] == b'_' {
+                    j += 1;
+                }
+                if !is_digit(j, hex) {
+                    return i;
+                }
+                i = j;
+            } else {
+                return i;
+            }
+        }
+    };
+
+    let (kind, end) = if text.starts_with("0x") || text.starts_with("0X") {
+        let end = digits(2, true);
+        if end == 2 {
+            return None;
+        }
+        (NumberKind::Hex, end)
+    } else {
+        let mut kind = NumberKind::Int;
+        let mut end = digits(0, false);
+        if end < bytes.len() && bytes[end] == b'.' && is_digit(end + 1, false) {
+            kind = NumberKind::Double;
+            end = digits(end + 1, false);
+        }
+        if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+            let mut i = end + 1;
+            if i < bytes.len() && (bytes[i] == b'+' || bytes[i] == b'-') {
+                i += 1;
+            }
+            let exponent_end = digits(i, false);
+            if exponent_end > i {
+                kind = NumberKind::Double;
+                end = exponent_end;
+            }
+        }
+        (kind, end)
+    };
+
+    // A number can't run into an identifier (or an unused separator).
+    let next = text[end..].chars().next();
+    if next.map_or(false, |c| UnicodeXID::is_xid_continue(c) || c == '$') {
+        return None;
+    }
+    Some((kind, end))
+}
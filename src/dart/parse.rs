@@ -3,9 +3,13 @@
 use syntax::codemap::BytePos;
 use syntax::symbol::Symbol;
 use dart::ast::*;
-use dart::lex::{self, Lexer, Token};
+use dart::lex::{self, Lexer, NumberKind, Token};
 use node::Node;
-use std::{iter, slice};
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::{cmp, iter, slice};
 use Span;
 use std::path::{Path, PathBuf};
 
@@ -14,16 +18,47 @@ type Tokens<'a> = iter::Cloned<slice::Iter<'a, (Span, Token)>>;
 
 #[derive(Clone)]
 pub struct Parser<'a> {
+    /// All the tokens being parsed.
+    all_tokens: &'a [(Span, Token)],
     /// The tokens that the parser works on.
     tokens: Tokens<'a>,
     /// File path of the module being parsed.
-    path: PathBuf,
+    path: Rc<PathBuf>,
     /// The current token.
     cur: Option<Token>,
     /// The current token's span.
     cur_span: Span,
+    /// The span of the last non-whitespace token consumed.
+    prev_span: Span,
     /// Comments between the current and the previous non-whitespace token.
     pub cur_comments: Vec<Span>,
+    /// Whether function bodies are skipped instead of parsed, for modules
+    /// only loaded for their exports.
+    pub skip_bodies: bool,
+    /// Outcomes of the productions applied so far, shared between all the
+    /// clones made by `try` and `probe` (see `memoize`).
+    memo: Rc<RefCell<HashMap<MemoKey, Rc<Any>>>>,
+    /// How many tokens were consumed so far, counting those consumed again
+    /// after backtracking, shared like `memo`.
+    bumps: Rc<Cell<usize>>,
+}
+
+/// The token position and number of pending comments a production was
+/// applied at, and the production's name.
+type MemoKey = (usize, usize, &'static str);
+
+/// A production's result, if it succeeded, or else its expectation, and
+/// the parser state right after applying it.
+type Memoized<T> = (Result<T, (Expected, Span)>, State);
+
+/// The state of the parser between two tokens.
+#[derive(Clone)]
+struct State {
+    index: usize,
+    cur: Option<Token>,
+    cur_span: Span,
+    prev_span: Span,
+    cur_comments: Vec<Span>,
 }
 
 error_chain! {
@@ -46,10 +81,16 @@ error_chain! {
         } {
             display("{:?}: expected {:?}", span, expected)
         }
+        IntegerOutOfRange {
+            number: String,
+            span: Span,
+        } {
+            display("{:?}: integer literal `{}` doesn't fit in 64 bits", span, number)
+        }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 /// Possible unmet expectations during parsing.
 pub enum Expected {
     Punctuation(char),
@@ -73,14 +114,29 @@ macro_rules! expected {
     }
 }
 
+/// Creates a node spanning from `lo` to the last consumed token.
+macro_rules! node {
+    ($p:expr, $lo:expr, $value:expr $(,)*) => {{
+        let value = $value;
+        let node = Node::new(value);
+        node.span().set($p.span_from($lo));
+        node
+    }}
+}
+
 impl<'a> Parser<'a> {
     pub fn new(path: &Path, tokens: &'a [(Span, Token)]) -> Self {
         let mut parser = Parser {
-            path: path.to_path_buf(),
+            path: Rc::new(path.to_path_buf()),
+            all_tokens: tokens,
             tokens: tokens.iter().cloned(),
             cur: None,
             cur_span: ::mk_sp(BytePos(0), BytePos(0)),
+            prev_span: ::mk_sp(BytePos(0), BytePos(0)),
             cur_comments: vec![],
+            skip_bodies: false,
+            memo: Rc::new(RefCell::new(HashMap::new())),
+            bumps: Rc::new(Cell::new(0)),
         };
         parser.bump();
         parser
@@ -98,6 +154,12 @@ impl<'a> Parser<'a> {
 
     /// Advances the current token.
     fn bump_raw(&mut self) {
+        self.bumps.set(self.bumps.get() + 1);
+        if let Some(token) = self.cur {
+            if !token.is_whitespace() {
+                self.prev_span = self.cur_span;
+            }
+        }
         match self.tokens.next() {
             Some((span, token)) => {
                 self.cur_span = span;
@@ -234,6 +296,96 @@ impl<'a> Parser<'a> {
         f(&mut self.clone())
     }
 
+    /// Applies `f` on the parser, recording the span of the tokens it
+    /// consumed on the returned node, unless it already has one.
+    pub fn spanned<F: FnOnce(&mut Self) -> ParseResult<Node<T>>, T>(
+        &mut self,
+        f: F,
+    ) -> ParseResult<Node<T>> {
+        let lo = self.cur_span.lo;
+        let node = f(self)?;
+        if node.span().get().is_none() {
+            node.span().set(self.span_from(lo));
+        }
+        Ok(node)
+    }
+
+    /// Returns the file path of the module being parsed.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns how many tokens were consumed so far, including each time
+    /// backtracking made the parser consume a token again.
+    pub fn bumps(&self) -> usize {
+        self.bumps.get()
+    }
+
+    /// Returns the span of the last non-whitespace token consumed.
+    pub fn prev_span(&self) -> Span {
+        self.prev_span
+    }
+
+    /// Returns the span from `lo` to the end of the last consumed token.
+    fn span_from(&self, lo: BytePos) -> Span {
+        ::mk_sp(lo, cmp::max(lo, self.prev_span.hi))
+    }
+
+    /// Applies the production `f`, or replays its outcome if it was already
+    /// applied at the current position, so that backtracking out of it and
+    /// trying it again later doesn't parse the same tokens twice.
+    pub fn memoize<F, T>(&mut self, production: &'static str, f: F) -> ParseResult<T>
+    where
+        F: FnOnce(&mut Self) -> ParseResult<T>,
+        T: Clone + 'static,
+    {
+        let key = (self.state().index, self.cur_comments.len(), production);
+        let memoized = self.memo.borrow().get(&key).cloned();
+        if let Some(memoized) = memoized {
+            let &(ref result, ref state) = memoized.downcast_ref::<Memoized<T>>().unwrap();
+            self.restore(state);
+            return match *result {
+                Ok(ref value) => Ok(value.clone()),
+                Err((ref expected, span)) => Err(ErrorKind::ExpectedAt {
+                    expected: expected.clone(),
+                    span,
+                }.into()),
+            };
+        }
+
+        let result = f(self);
+        let outcome = match result {
+            Ok(ref value) => Some(Ok(value.clone())),
+            Err(ref error) => match *error.kind() {
+                ErrorKind::ExpectedAt { ref expected, span } => Some(Err((expected.clone(), span))),
+                _ => None,
+            },
+        };
+        if let Some(outcome) = outcome {
+            let memoized: Memoized<T> = (outcome, self.state());
+            self.memo.borrow_mut().insert(key, Rc::new(memoized));
+        }
+        result
+    }
+
+    fn state(&self) -> State {
+        State {
+            index: self.all_tokens.len() - self.tokens.len(),
+            cur: self.cur,
+            cur_span: self.cur_span,
+            prev_span: self.prev_span,
+            cur_comments: self.cur_comments.clone(),
+        }
+    }
+
+    fn restore(&mut self, state: &State) {
+        self.tokens = self.all_tokens[state.index..].iter().cloned();
+        self.cur = state.cur;
+        self.cur_span = state.cur_span;
+        self.prev_span = state.prev_span;
+        self.cur_comments = state.cur_comments.clone();
+    }
+
     /// Applies `f` on the parser, committing the modified state on success,
     /// and rolling back on error.
     pub fn try<F: FnOnce(&mut Self) -> ParseResult<T>, T>(&mut self, f: F) -> Option<T> {
@@ -305,6 +457,7 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_qualified(&mut self) -> ParseResult<Node<Qualified>> {
+        let lo = self.cur_span.lo;
         let mut prefix = None;
         loop {
             let name = self.parse_ident()?;
@@ -313,7 +466,7 @@ impl<'a> Parser<'a> {
             } else {
                 vec![]
             };
-            let qualified = Node::new(Qualified {
+            let qualified = node!(self, lo, Qualified {
                 prefix,
                 name,
                 params,
@@ -327,14 +480,62 @@ impl<'a> Parser<'a> {
     }
 
     pub fn dart_type(&mut self) -> ParseResult<Node<Type>> {
-        let mut ty = Node::new(Type::Path(self.dart_qualified()?));
+        self.memoize("dart_type", |p| p.dart_type_unmemoized())
+    }
+
+    fn dart_type_unmemoized(&mut self) -> ParseResult<Node<Type>> {
+        let lo = self.cur_span.lo;
+        let mut ty = if self.is_punctuation('(') {
+            self.dart_record_type()?
+        } else {
+            node!(self, lo, Type::Path(self.dart_qualified()?))
+        };
         if self.eat_keyword("Function") {
             let sig = self.dart_fn_args(ty)?;
-            ty = Node::new(Type::Function(sig));
+            ty = node!(self, lo, Type::Function(sig));
         }
         Ok(ty)
     }
 
+    fn dart_record_type(&mut self) -> ParseResult<Node<Type>> {
+        let lo = self.cur_span.lo;
+        self.expect_punctuation('(')?;
+        let mut positional = vec![];
+        let mut named = vec![];
+        let mut trailing_comma = false;
+        loop {
+            if self.is_punctuation(')') || self.is_punctuation('{') {
+                break;
+            }
+            let ty = self.dart_type()?;
+            let name = if self.is_punctuation(',') || self.is_punctuation(')') {
+                None
+            } else {
+                Some(self.parse_ident()?)
+            };
+            positional.push((ty, name));
+            trailing_comma = self.eat_punctuation(',');
+            if !trailing_comma {
+                break;
+            }
+        }
+        if self.eat_punctuation('{') {
+            while !self.eat_punctuation('}') {
+                named.push((self.dart_type()?, self.parse_ident()?));
+                if !self.eat_punctuation(',') {
+                    self.expect_punctuation('}')?;
+                    break;
+                }
+            }
+        }
+        // `(T)` is just a parenthesized type, which Dart doesn't allow.
+        if positional.len() == 1 && named.is_empty() && !trailing_comma {
+            expected!(self, Punctuation(','));
+        }
+        self.expect_punctuation(')')?;
+        Ok(node!(self, lo, Type::Record { positional, named }))
+    }
+
     fn dart_type_params(&mut self) -> ParseResult<Vec<Node<Type>>> {
         self.expect_punctuation('<')?;
         let type_args = self.parse_one_or_more(',', |p| p.dart_type())?;
@@ -376,10 +577,12 @@ impl<'a> Parser<'a> {
                 break;
             }
         }
+        let trailing_comments = self.cur_comments.drain(..).collect();
         self.expect_punctuation(')')?;
         Ok(Args {
             unnamed: unnamed_arguments,
             named: named_arguments,
+            trailing_comments,
         })
     }
 
@@ -406,30 +609,36 @@ impl<'a> Parser<'a> {
     }
 
     pub fn dart_expr(&mut self) -> ParseResult<Node<Expr>> {
+        self.memoize("dart_expr", |p| p.dart_expr_unmemoized())
+    }
+
+    fn dart_expr_unmemoized(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         if !self.cur_comments.is_empty() {
             let comments = self.cur_comments.drain(..).collect();
-            return Ok(Node::new(Expr::Comments(comments, self.dart_expr()?)));
+            return Ok(node!(self, lo, Expr::Comments(comments, self.dart_expr()?)));
         }
         if self.eat_keyword("throw") {
-            return Ok(Node::new(Expr::Throw(self.dart_expr()?)));
+            return Ok(node!(self, lo, Expr::Throw(self.dart_expr()?)));
         }
         let mut expr = self.dart_conditional_expr()?;
         while let Some(cascade) = self.try(|p| p.dart_casacade()) {
-            expr = Node::new(Expr::Cascade(expr, cascade));
+            expr = node!(self, lo, Expr::Cascade(expr, cascade));
         }
         if let Some(op) = self.try(|p| p.dart_assign_op()) {
-            expr = Node::new(Expr::Binary(BinOp::Assign(op), expr, self.dart_expr()?));
+            expr = node!(self, lo, Expr::Binary(BinOp::Assign(op), expr, self.dart_expr()?));
         }
         Ok(expr)
     }
 
     pub fn dart_expr_no_cascade(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         if self.eat_keyword("throw") {
-            return Ok(Node::new(Expr::Throw(self.dart_expr_no_cascade()?)));
+            return Ok(node!(self, lo, Expr::Throw(self.dart_expr_no_cascade()?)));
         }
         let mut expr = self.dart_conditional_expr()?;
         if let Some(op) = self.try(|p| p.dart_assign_op()) {
-            expr = Node::new(Expr::Binary(
+            expr = node!(self, lo, Expr::Binary(
                 BinOp::Assign(op),
                 expr,
                 self.dart_expr_no_cascade()?,
@@ -439,11 +648,12 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_conditional_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let expr = self.dart_is_null_expression()?;
         if !self.is_punctuation2('?', '?') && self.eat_punctuation('?') {
             let expr2 = self.dart_expr_no_cascade()?;
             self.expect_punctuation(':')?;
-            Ok(Node::new(
+            Ok(node!(self, lo,
                 Expr::Conditional(expr, expr2, self.dart_expr_no_cascade()?),
             ))
         } else {
@@ -452,9 +662,10 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_is_null_expression(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let mut expr = self.dart_or_expr()?;
         while self.eat_bin_op(BinOp::Value(ValueBinOp::IfNull)) {
-            expr = Node::new(Expr::Binary(
+            expr = node!(self, lo, Expr::Binary(
                 BinOp::Value(ValueBinOp::IfNull),
                 expr,
                 self.dart_or_expr()?,
@@ -464,9 +675,10 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_or_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let mut expr = self.dart_and_expr()?;
         while self.eat_bin_op(BinOp::Bool(BoolBinOp::Or)) {
-            expr = Node::new(Expr::Binary(
+            expr = node!(self, lo, Expr::Binary(
                 BinOp::Bool(BoolBinOp::Or),
                 expr,
                 self.dart_and_expr()?,
@@ -476,9 +688,10 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_and_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let mut expr = self.dart_equality_expr()?;
         while self.eat_bin_op(BinOp::Bool(BoolBinOp::And)) {
-            expr = Node::new(Expr::Binary(
+            expr = node!(self, lo, Expr::Binary(
                 BinOp::Bool(BoolBinOp::And),
                 expr,
                 self.dart_equality_expr()?,
@@ -488,15 +701,16 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_equality_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let expr = self.dart_relational_expr()?;
         if self.eat_bin_op(BinOp::Bool(BoolBinOp::Eq)) {
-            Ok(Node::new(Expr::Binary(
+            Ok(node!(self, lo, Expr::Binary(
                 BinOp::Bool(BoolBinOp::Eq),
                 expr,
                 self.dart_relational_expr()?,
             )))
         } else if self.eat_bin_op(BinOp::Bool(BoolBinOp::Ne)) {
-            Ok(Node::new(Expr::Binary(
+            Ok(node!(self, lo, Expr::Binary(
                 BinOp::Bool(BoolBinOp::Ne),
                 expr,
                 self.dart_relational_expr()?,
@@ -507,35 +721,36 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_relational_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let expr = self.dart_bit_or_expr()?;
         if self.eat_keyword("is") {
             if self.eat_punctuation('!') {
-                Ok(Node::new(Expr::IsNot(expr, self.dart_type()?)))
+                Ok(node!(self, lo, Expr::IsNot(expr, self.dart_type()?)))
             } else {
-                Ok(Node::new(Expr::Is(expr, self.dart_type()?)))
+                Ok(node!(self, lo, Expr::Is(expr, self.dart_type()?)))
             }
         } else if self.eat_keyword("as") {
-            Ok(Node::new(Expr::As(expr, self.dart_type()?)))
+            Ok(node!(self, lo, Expr::As(expr, self.dart_type()?)))
         } else if self.eat_bin_op(BinOp::Bool(BoolBinOp::Ge)) {
-            Ok(Node::new(Expr::Binary(
+            Ok(node!(self, lo, Expr::Binary(
                 BinOp::Bool(BoolBinOp::Ge),
                 expr,
                 self.dart_bit_or_expr()?,
             )))
         } else if self.eat_bin_op(BinOp::Bool(BoolBinOp::Gt)) {
-            Ok(Node::new(Expr::Binary(
+            Ok(node!(self, lo, Expr::Binary(
                 BinOp::Bool(BoolBinOp::Gt),
                 expr,
                 self.dart_bit_or_expr()?,
             )))
         } else if self.eat_bin_op(BinOp::Bool(BoolBinOp::Le)) {
-            Ok(Node::new(Expr::Binary(
+            Ok(node!(self, lo, Expr::Binary(
                 BinOp::Bool(BoolBinOp::Le),
                 expr,
                 self.dart_bit_or_expr()?,
             )))
         } else if self.eat_bin_op(BinOp::Bool(BoolBinOp::Lt)) {
-            Ok(Node::new(Expr::Binary(
+            Ok(node!(self, lo, Expr::Binary(
                 BinOp::Bool(BoolBinOp::Lt),
                 expr,
                 self.dart_bit_or_expr()?,
@@ -546,9 +761,10 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_bit_or_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let mut expr = self.dart_bit_xor_expr()?;
         while self.eat_bin_op(BinOp::Value(ValueBinOp::BitOr)) {
-            expr = Node::new(Expr::Binary(
+            expr = node!(self, lo, Expr::Binary(
                 BinOp::Value(ValueBinOp::BitOr),
                 expr,
                 self.dart_bit_xor_expr()?,
@@ -558,9 +774,10 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_bit_xor_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let mut expr = self.dart_bit_and_expr()?;
         while self.eat_bin_op(BinOp::Value(ValueBinOp::BitXor)) {
-            expr = Node::new(Expr::Binary(
+            expr = node!(self, lo, Expr::Binary(
                 BinOp::Value(ValueBinOp::BitXor),
                 expr,
                 self.dart_bit_and_expr()?,
@@ -570,9 +787,10 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_bit_and_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let mut expr = self.dart_shift_expr()?;
         while self.eat_bin_op(BinOp::Value(ValueBinOp::BitAnd)) {
-            expr = Node::new(Expr::Binary(
+            expr = node!(self, lo, Expr::Binary(
                 BinOp::Value(ValueBinOp::BitAnd),
                 expr,
                 self.dart_shift_expr()?,
@@ -582,16 +800,17 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_shift_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let mut expr = self.dart_add_expr()?;
         loop {
             if self.eat_bin_op(BinOp::Value(ValueBinOp::Lsh)) {
-                expr = Node::new(Expr::Binary(
+                expr = node!(self, lo, Expr::Binary(
                     BinOp::Value(ValueBinOp::Lsh),
                     expr,
                     self.dart_add_expr()?,
                 ));
             } else if self.eat_bin_op(BinOp::Value(ValueBinOp::Rsh)) {
-                expr = Node::new(Expr::Binary(
+                expr = node!(self, lo, Expr::Binary(
                     BinOp::Value(ValueBinOp::Rsh),
                     expr,
                     self.dart_add_expr()?,
@@ -604,16 +823,17 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_add_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let mut expr = self.dart_mult_expr()?;
         loop {
             if self.eat_bin_op(BinOp::Value(ValueBinOp::Add)) {
-                expr = Node::new(Expr::Binary(
+                expr = node!(self, lo, Expr::Binary(
                     BinOp::Value(ValueBinOp::Add),
                     expr,
                     self.dart_mult_expr()?,
                 ));
             } else if self.eat_bin_op(BinOp::Value(ValueBinOp::Sub)) {
-                expr = Node::new(Expr::Binary(
+                expr = node!(self, lo, Expr::Binary(
                     BinOp::Value(ValueBinOp::Sub),
                     expr,
                     self.dart_mult_expr()?,
@@ -626,28 +846,29 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_mult_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let mut expr = self.dart_unary_expr()?;
         loop {
             if self.eat_bin_op(BinOp::Value(ValueBinOp::Mul)) {
-                expr = Node::new(Expr::Binary(
+                expr = node!(self, lo, Expr::Binary(
                     BinOp::Value(ValueBinOp::Mul),
                     expr,
                     self.dart_unary_expr()?,
                 ));
             } else if self.eat_bin_op(BinOp::Value(ValueBinOp::Div)) {
-                expr = Node::new(Expr::Binary(
+                expr = node!(self, lo, Expr::Binary(
                     BinOp::Value(ValueBinOp::Div),
                     expr,
                     self.dart_unary_expr()?,
                 ));
             } else if self.eat_bin_op(BinOp::Value(ValueBinOp::Mod)) {
-                expr = Node::new(Expr::Binary(
+                expr = node!(self, lo, Expr::Binary(
                     BinOp::Value(ValueBinOp::Mod),
                     expr,
                     self.dart_unary_expr()?,
                 ));
             } else if self.eat_bin_op(BinOp::Value(ValueBinOp::TruncDiv)) {
-                expr = Node::new(Expr::Binary(
+                expr = node!(self, lo, Expr::Binary(
                     BinOp::Value(ValueBinOp::TruncDiv),
                     expr,
                     self.dart_unary_expr()?,
@@ -660,34 +881,48 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_unary_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         if self.eat_punctuation('-') {
-            Ok(Node::new(Expr::Unary(UnOp::Neg, self.dart_unary_expr()?)))
+            // A decimal literal can only be one more than `i64::MAX` when
+            // negated, as in `-9223372036854775808`, not `-9223372036854775808.abs()`.
+            let min = self.try(|p| {
+                let literal = p.dart_number_literal(true)?;
+                match p.dart_suffix()? {
+                    Some(_) => expected!(p, Expr),
+                    None => Ok(literal),
+                }
+            });
+            if let Some(literal) = min {
+                return Ok(node!(self, lo, Expr::Unary(UnOp::Neg, literal)));
+            }
+            Ok(node!(self, lo, Expr::Unary(UnOp::Neg, self.dart_unary_expr()?)))
         } else if self.eat_punctuation('!') {
-            Ok(Node::new(Expr::Unary(UnOp::Not, self.dart_unary_expr()?)))
+            Ok(node!(self, lo, Expr::Unary(UnOp::Not, self.dart_unary_expr()?)))
         } else if self.eat_punctuation('~') {
-            Ok(Node::new(
+            Ok(node!(self, lo,
                 Expr::Unary(UnOp::BitNot, self.dart_unary_expr()?),
             ))
         } else if self.eat_keyword("await") {
-            Ok(Node::new(Expr::Unary(UnOp::Await, self.dart_unary_expr()?)))
+            Ok(node!(self, lo, Expr::Unary(UnOp::Await, self.dart_unary_expr()?)))
         } else if self.eat_punctuation2('+', '+') {
-            Ok(Node::new(Expr::Unary(UnOp::PreInc, self.dart_expr()?)))
+            Ok(node!(self, lo, Expr::Unary(UnOp::PreInc, self.dart_expr()?)))
         } else if self.eat_punctuation2('-', '-') {
-            Ok(Node::new(Expr::Unary(UnOp::PreDec, self.dart_expr()?)))
+            Ok(node!(self, lo, Expr::Unary(UnOp::PreDec, self.dart_expr()?)))
         } else {
             Ok(self.dart_postfix_expr()?)
         }
     }
 
     fn dart_postfix_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         let mut expr = self.dart_primary_expr()?;
         while let Some(suffix) = self.dart_suffix()? {
-            expr = Node::new(Expr::Suffix(expr, suffix));
+            expr = node!(self, lo, Expr::Suffix(expr, suffix));
         }
         if self.eat_punctuation2('+', '+') {
-            Ok(Node::new(Expr::Unary(UnOp::PostInc, expr)))
+            Ok(node!(self, lo, Expr::Unary(UnOp::PostInc, expr)))
         } else if self.eat_punctuation2('-', '-') {
-            Ok(Node::new(Expr::Unary(UnOp::PostDec, expr)))
+            Ok(node!(self, lo, Expr::Unary(UnOp::PostDec, expr)))
         } else {
             Ok(expr)
         }
@@ -708,6 +943,10 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_assign_op(&mut self) -> ParseResult<Option<ValueBinOp>> {
+        // The `=>` ending a guard in a switch expression.
+        if self.is_punctuation2('=', '>') {
+            expected!(self, BinOp(BinOp::Assign(None)));
+        }
         for op in ValueBinOp::values() {
             if self.eat_bin_op(BinOp::Assign(Some(op))) {
                 return Ok(Some(op));
@@ -736,6 +975,7 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_primary_expr(&mut self) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
         if self.is_punctuation('(') {
             let args = self.try(|p| {
                 let args = p.dart_fn_args(Node::new(Type::Infer))?;
@@ -746,12 +986,44 @@ impl<'a> Parser<'a> {
                 }
             });
             if let Some(args) = args {
-                return Ok(Node::new(Expr::Closure(args, self.dart_fn_body(false)?)));
+                return Ok(node!(self, lo, Expr::Closure(args, self.dart_fn_body(false)?)));
             }
+            let paren = self.try(|p| {
+                p.expect_punctuation('(')?;
+                let expr = p.dart_expr()?;
+                p.expect_punctuation(')')?;
+                Ok(expr)
+            });
+            if let Some(expr) = paren {
+                return Ok(node!(self, lo, Expr::Paren(expr)));
+            }
+            return Ok(node!(self, lo, Expr::Record(self.dart_arguments()?)));
+        }
+        if self.eat_keyword("switch") {
             self.expect_punctuation('(')?;
             let expr = self.dart_expr()?;
             self.expect_punctuation(')')?;
-            return Ok(Node::new(Expr::Paren(expr)));
+            self.expect_punctuation('{')?;
+            let mut cases = vec![];
+            while !self.eat_punctuation('}') {
+                let pattern = self.dart_pattern()?;
+                let guard = if self.eat_keyword("when") {
+                    Some(self.dart_expr()?)
+                } else {
+                    None
+                };
+                self.expect_punctuation2('=', '>')?;
+                cases.push(SwitchExprCase {
+                    pattern,
+                    guard,
+                    body: self.dart_expr()?,
+                });
+                if !self.eat_punctuation(',') {
+                    self.expect_punctuation('}')?;
+                    break;
+                }
+            }
+            return Ok(node!(self, lo, Expr::Switch(expr, cases)));
         }
         let const_ = self.eat_keyword("const");
         let mut generics = if self.is_punctuation('<') {
@@ -777,7 +1049,7 @@ impl<'a> Parser<'a> {
                 assert_eq!(generics.len(), 1);
                 generics.pop()
             };
-            return Ok(Node::new(Expr::List {
+            return Ok(node!(self, lo, Expr::List {
                 const_,
                 element_ty,
                 elements,
@@ -805,66 +1077,223 @@ impl<'a> Parser<'a> {
                 let k_ty = generics.pop().unwrap();
                 Some((k_ty, v_ty))
             };
-            return Ok(Node::new(Expr::Map { const_, kv_ty, kv }));
+            return Ok(node!(self, lo, Expr::Map { const_, kv_ty, kv }));
         }
         if const_ || self.eat_keyword("new") {
-            return Ok(Node::new(Expr::New {
+            return Ok(node!(self, lo, Expr::New {
                 const_,
                 path: self.dart_qualified()?,
                 args: self.dart_arguments()?,
             }));
         }
-        if let Some(number) = self.try(|p| p.dart_number_literal()) {
-            return Ok(number);
+        if let Some(Token::NumberLiteral(..)) = self.cur {
+            return self.dart_number_literal(false);
         }
         if let Some(str_lit) = self.try(|p| p.dart_string_literal()) {
             let mut strings = vec![str_lit];
             while let Some(str_lit) = self.try(|p| p.dart_string_literal()) {
                 strings.push(str_lit);
             }
-            return Ok(Node::new(Expr::String(strings)));
+            return Ok(node!(self, lo, Expr::String(strings)));
         }
         if self.eat_punctuation('#') {
-            return Ok(Node::new(
+            return Ok(node!(self, lo,
                 Expr::Symbol(SymbolLiteral::Path(vec![self.parse_ident()?])),
             ));
         }
         if let Ok(ident) = self.parse_ident() {
-            return Ok(Node::new(Expr::Identifier(ident)));
+            return Ok(node!(self, lo, Expr::Identifier(ident)));
         }
         expected!(self, Expr);
     }
 
-    fn dart_number_literal(&mut self) -> ParseResult<Node<Expr>> {
-        let mut number = String::new();
+    /// Parses a number literal, `negated` if it's the operand of unary minus.
+    fn dart_number_literal(&mut self, negated: bool) -> ParseResult<Node<Expr>> {
+        let lo = self.cur_span.lo;
+        let (kind, number) = match self.cur {
+            Some(Token::NumberLiteral(kind, number)) => (kind, number),
+            _ => expected!(self, NumberLiteral),
+        };
 
-        if let Some(Token::IntegerLiteral(int_part)) = self.cur {
-            self.bump_raw();
-            number += &int_part.as_str();
+        // Dart integers are 64-bit. Hexadecimal ones can use all 64 bits,
+        // negated decimal ones one more than `i64::MAX`.
+        let digits = number.as_str().replace('_', "");
+        let max = if negated { 1 << 63 } else { i64::max_value() as u64 };
+        let in_range = match kind {
+            NumberKind::Int => digits.parse::<u64>().map_or(false, |n| n <= max),
+            NumberKind::Hex => u64::from_str_radix(&digits[2..], 16).is_ok(),
+            NumberKind::Double => true,
+        };
+        if !in_range {
+            bail!(ErrorKind::IntegerOutOfRange {
+                number: number.to_string(),
+                span: self.cur_span,
+            });
+        }
+        self.bump();
+
+        Ok(node!(self, lo, match kind {
+            NumberKind::Int | NumberKind::Hex => Expr::Int(number),
+            NumberKind::Double => Expr::Double(number),
+        }))
+    }
+
+    fn dart_pattern(&mut self) -> ParseResult<Node<Pattern>> {
+        let lo = self.cur_span.lo;
+        let mut pattern = self.dart_and_pattern()?;
+        while self.eat_bin_op(BinOp::Bool(BoolBinOp::Or)) {
+            pattern = node!(self, lo, Pattern::Or(pattern, self.dart_and_pattern()?));
         }
+        Ok(pattern)
+    }
 
-        if self.cur == Some(Token::Punctuation('.')) {
-            self.bump_raw();
-            number.push('.');
-            if let Some(Token::IntegerLiteral(frac_part)) = self.cur {
-                self.bump_raw();
-                number += &frac_part.as_str();
+    fn dart_and_pattern(&mut self) -> ParseResult<Node<Pattern>> {
+        let lo = self.cur_span.lo;
+        let mut pattern = self.dart_relational_pattern()?;
+        while self.eat_bin_op(BinOp::Bool(BoolBinOp::And)) {
+            pattern = node!(self, lo, Pattern::And(pattern, self.dart_relational_pattern()?));
+        }
+        Ok(pattern)
+    }
+
+    fn dart_relational_pattern(&mut self) -> ParseResult<Node<Pattern>> {
+        let lo = self.cur_span.lo;
+        let ops = [
+            BoolBinOp::Eq,
+            BoolBinOp::Ne,
+            BoolBinOp::Ge,
+            BoolBinOp::Gt,
+            BoolBinOp::Le,
+            BoolBinOp::Lt,
+        ];
+        for &op in &ops {
+            if self.eat_bin_op(BinOp::Bool(op)) {
+                return Ok(node!(self, lo, Pattern::Relational(op, self.dart_bit_or_expr()?)));
             }
         }
+        self.dart_postfix_pattern()
+    }
 
-        if number.is_empty() {
-            expected!(self, NumberLiteral);
+    fn dart_postfix_pattern(&mut self) -> ParseResult<Node<Pattern>> {
+        let lo = self.cur_span.lo;
+        let mut pattern = self.dart_primary_pattern()?;
+        loop {
+            if self.eat_keyword("as") {
+                pattern = node!(self, lo, Pattern::Cast(pattern, self.dart_type()?));
+            } else if !self.is_punctuation2('?', '?') && self.eat_punctuation('?') {
+                pattern = node!(self, lo, Pattern::NullCheck(pattern));
+            } else if !self.is_bin_op(BinOp::Bool(BoolBinOp::Ne)) && self.eat_punctuation('!') {
+                pattern = node!(self, lo, Pattern::NullAssert(pattern));
+            } else {
+                return Ok(pattern);
+            }
         }
+    }
 
-        if let Some(token) = self.cur {
-            if token.is_whitespace() {
-                self.bump();
+    fn dart_primary_pattern(&mut self) -> ParseResult<Node<Pattern>> {
+        let lo = self.cur_span.lo;
+        if self.eat_keyword("_") {
+            return Ok(node!(self, lo, Pattern::Wildcard));
+        }
+        if self.is_punctuation('(') {
+            let paren = self.try(|p| {
+                p.expect_punctuation('(')?;
+                let pattern = p.dart_pattern()?;
+                p.expect_punctuation(')')?;
+                Ok(pattern)
+            });
+            if let Some(pattern) = paren {
+                return Ok(node!(self, lo, Pattern::Paren(pattern)));
+            }
+            return Ok(node!(self, lo, Pattern::Record(self.dart_pattern_fields()?)));
+        }
+        if self.eat_punctuation('[') {
+            let mut elements = vec![];
+            while !self.eat_punctuation(']') {
+                if self.probe(|p| p.eat_punctuation2('.', '.') && p.is_punctuation('.')) {
+                    let lo = self.cur_span.lo;
+                    self.expect_punctuation2('.', '.')?;
+                    self.expect_punctuation('.')?;
+                    let rest = if self.is_punctuation(',') || self.is_punctuation(']') {
+                        None
+                    } else {
+                        Some(self.dart_pattern()?)
+                    };
+                    elements.push(node!(self, lo, Pattern::Rest(rest)));
+                } else {
+                    elements.push(self.dart_pattern()?);
+                }
+                if !self.eat_punctuation(',') {
+                    self.expect_punctuation(']')?;
+                    break;
+                }
             }
+            return Ok(node!(self, lo, Pattern::List(elements)));
         }
-        Ok(Node::new(Expr::Number(Symbol::intern(&number))))
+        if self.eat_punctuation('{') {
+            let mut kv = vec![];
+            while !self.eat_punctuation('}') {
+                let k = self.dart_expr()?;
+                self.expect_punctuation(':')?;
+                kv.push((k, self.dart_pattern()?));
+                if !self.eat_punctuation(',') {
+                    self.expect_punctuation('}')?;
+                    break;
+                }
+            }
+            return Ok(node!(self, lo, Pattern::Map(kv)));
+        }
+        if self.probe(|p| p.dart_qualified().is_ok() && p.is_punctuation('(')) {
+            let qualified = self.dart_qualified()?;
+            return Ok(node!(self, lo, Pattern::Object(qualified, self.dart_pattern_fields()?)));
+        }
+        // `const` starts constant patterns, not variables.
+        if !self.is_keyword("const") {
+            let var = self.try(|p| {
+                let var_type = p.dart_var_type(false)?;
+                if var_type.fcv.is_none() {
+                    if let Type::Infer = *var_type.ty {
+                        expected!(p, Ident);
+                    }
+                }
+                let var_lo = p.cur_span.lo;
+                let name = p.parse_ident()?;
+                // Keep `x when ...` and `x as T` for the guard and the cast.
+                if name == "when" || name == "as" {
+                    expected!(p, Ident);
+                }
+                Ok((var_type, node!(p, var_lo, VarDef { name, init: None })))
+            });
+            if let Some((var_type, var)) = var {
+                return Ok(node!(self, lo, Pattern::Var(var_type, var)));
+            }
+        }
+        Ok(node!(self, lo, Pattern::Const(self.dart_unary_expr()?)))
+    }
+
+    /// Parses the fields of a record or object pattern, in parentheses.
+    fn dart_pattern_fields(&mut self) -> ParseResult<Vec<PatternField>> {
+        self.expect_punctuation('(')?;
+        let mut fields = vec![];
+        while !self.eat_punctuation(')') {
+            if self.eat_punctuation(':') {
+                fields.push(PatternField::Named(None, self.dart_pattern()?));
+            } else if self.probe(|p| p.parse_ident().is_ok() && p.is_punctuation(':')) {
+                let name = self.parse_ident()?;
+                self.expect_punctuation(':')?;
+                fields.push(PatternField::Named(Some(name), self.dart_pattern()?));
+            } else {
+                fields.push(PatternField::Positional(self.dart_pattern()?));
+            }
+            if !self.eat_punctuation(',') {
+                self.expect_punctuation(')')?;
+                break;
+            }
+        }
+        Ok(fields)
     }
 
-    fn dart_fn_args(&mut self, return_type: Node<Type>) -> ParseResult<FnSig> {
+    pub fn dart_fn_args(&mut self, return_type: Node<Type>) -> ParseResult<FnSig> {
         let mut sig = FnSig {
             return_type,
             required: vec![],
@@ -922,6 +1351,7 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_arg_def(&mut self, default_separators: &[char]) -> ParseResult<ArgDef> {
+        let lo = self.cur_span.lo;
         let meta = self.dart_meta()?;
         let covariant = self.eat_keyword("covariant");
         let mut ty = self.dart_var_type(false)?;
@@ -932,7 +1362,7 @@ impl<'a> Parser<'a> {
         }
         let name = self.parse_ident()?;
         if self.is_punctuation('(') {
-            ty.ty = Node::new(Type::FunctionOld(self.dart_fn_args(ty.ty)?));
+            ty.ty = node!(self, lo, Type::FunctionOld(self.dart_fn_args(ty.ty)?));
         }
         let mut init = None;
         let mut default_uses_eq = false;
@@ -951,7 +1381,7 @@ impl<'a> Parser<'a> {
             ty,
             field,
             default_uses_eq,
-            var: Node::new(VarDef { name, init }),
+            var: node!(self, lo, VarDef { name, init }),
         })
     }
 
@@ -990,6 +1420,7 @@ impl<'a> Parser<'a> {
     }
 
     pub fn dart_block(&mut self) -> ParseResult<Node<Statement>> {
+        let lo = self.cur_span.lo;
         self.expect_punctuation('{')?;
         let mut statements = vec![];
         loop {
@@ -999,17 +1430,18 @@ impl<'a> Parser<'a> {
             statements.push(self.dart_statement()?);
         }
         self.expect_punctuation('}')?;
-        Ok(Node::new(Statement::Block(statements)))
+        Ok(node!(self, lo, Statement::Block(statements)))
     }
 
     fn dart_catch_part(&mut self) -> ParseResult<CatchPart> {
+        let lo = self.cur_span.lo;
         self.expect_punctuation('(')?;
-        let exception = Node::new(VarDef {
+        let exception = node!(self, lo, VarDef {
             name: self.parse_ident()?,
             init: None,
         });
         let trace = if self.eat_punctuation(',') {
-            Some(Node::new(VarDef {
+            Some(node!(self, lo, VarDef {
                 name: self.parse_ident()?,
                 init: None,
             }))
@@ -1021,15 +1453,20 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_statement(&mut self) -> ParseResult<Node<Statement>> {
+        self.memoize("dart_statement", |p| p.dart_statement_unmemoized())
+    }
+
+    fn dart_statement_unmemoized(&mut self) -> ParseResult<Node<Statement>> {
+        let lo = self.cur_span.lo;
         if !self.cur_comments.is_empty() {
             let comments = self.cur_comments.drain(..).collect();
-            return Ok(Node::new(Statement::Comments(
+            return Ok(node!(self, lo, Statement::Comments(
                 comments,
                 self.try(|p| p.dart_statement()),
             )));
         }
         if let Some((label, _)) = self.try(|p| Ok((p.parse_ident()?, p.expect_punctuation(':')?))) {
-            return Ok(Node::new(
+            return Ok(node!(self, lo,
                 Statement::Labelled(label, self.dart_statement()?),
             ));
         }
@@ -1048,7 +1485,7 @@ impl<'a> Parser<'a> {
                 if let Some(var_type) = var_type {
                     Ok(ForLoop::InVar(
                         var_type,
-                        Node::new(VarDef { name, init: None }),
+                        node!(self, lo, VarDef { name, init: None }),
                         expr,
                     ))
                 } else {
@@ -1071,7 +1508,7 @@ impl<'a> Parser<'a> {
                     Ok(ForLoop::CLike(statement, cond, exprs))
                 })?;
             self.expect_punctuation(')')?;
-            return Ok(Node::new(
+            return Ok(node!(self, lo,
                 Statement::For(await, for_loop, self.dart_statement()?),
             ));
         }
@@ -1079,7 +1516,7 @@ impl<'a> Parser<'a> {
             self.expect_punctuation('(')?;
             let expr = self.dart_expr()?;
             self.expect_punctuation(')')?;
-            return Ok(Node::new(Statement::While(expr, self.dart_statement()?)));
+            return Ok(node!(self, lo, Statement::While(expr, self.dart_statement()?)));
         }
         if self.eat_keyword("do") {
             let statement = self.dart_statement()?;
@@ -1088,7 +1525,7 @@ impl<'a> Parser<'a> {
             let expr = self.dart_expr()?;
             self.expect_punctuation(')')?;
             self.expect_punctuation(';')?;
-            return Ok(Node::new(Statement::DoWhile(statement, expr)));
+            return Ok(node!(self, lo, Statement::DoWhile(statement, expr)));
         }
         if self.eat_keyword("switch") {
             self.expect_punctuation('(')?;
@@ -1135,7 +1572,7 @@ impl<'a> Parser<'a> {
                 });
             }
             self.expect_punctuation('}')?;
-            return Ok(Node::new(Statement::Switch(expr, sc)));
+            return Ok(node!(self, lo, Statement::Switch(expr, sc)));
         }
         if self.eat_keyword("if") {
             self.expect_punctuation('(')?;
@@ -1147,10 +1584,10 @@ impl<'a> Parser<'a> {
             } else {
                 None
             };
-            return Ok(Node::new(Statement::If(expr, statement, else_statement)));
+            return Ok(node!(self, lo, Statement::If(expr, statement, else_statement)));
         }
         if self.eat_keyword("rethrow") {
-            return Ok(Node::new(Statement::Rethrow));
+            return Ok(node!(self, lo, Statement::Rethrow));
         }
         if self.eat_keyword("try") {
             let block = self.dart_block()?;
@@ -1186,73 +1623,73 @@ impl<'a> Parser<'a> {
                     break;
                 }
             }
-            return Ok(Node::new(Statement::Try(block, parts)));
+            return Ok(node!(self, lo, Statement::Try(block, parts)));
         }
         if self.eat_keyword("break") {
             if self.is_punctuation(';') {
                 self.expect_punctuation(';')?;
-                return Ok(Node::new(Statement::Break(None)));
+                return Ok(node!(self, lo, Statement::Break(None)));
             } else {
                 let ident = self.parse_ident()?;
                 self.expect_punctuation(';')?;
-                return Ok(Node::new(Statement::Break(Some(ident))));
+                return Ok(node!(self, lo, Statement::Break(Some(ident))));
             }
         }
         if self.eat_keyword("continue") {
             if self.is_punctuation(';') {
                 self.expect_punctuation(';')?;
-                return Ok(Node::new(Statement::Continue(None)));
+                return Ok(node!(self, lo, Statement::Continue(None)));
             } else {
                 let ident = self.parse_ident()?;
                 self.expect_punctuation(';')?;
-                return Ok(Node::new(Statement::Continue(Some(ident))));
+                return Ok(node!(self, lo, Statement::Continue(Some(ident))));
             }
         }
         if self.eat_keyword("return") {
             if self.eat_punctuation(';') {
-                return Ok(Node::new(Statement::Return(None)));
+                return Ok(node!(self, lo, Statement::Return(None)));
             }
             let expr = self.dart_expr()?;
             self.expect_punctuation(';')?;
-            return Ok(Node::new(Statement::Return(Some(expr))));
+            return Ok(node!(self, lo, Statement::Return(Some(expr))));
         }
         if self.eat_keyword("yield") {
             if self.eat_punctuation('*') {
                 let expr = self.dart_expr()?;
                 self.expect_punctuation(';')?;
-                return Ok(Node::new(Statement::YieldEach(expr)));
+                return Ok(node!(self, lo, Statement::YieldEach(expr)));
             } else {
                 let expr = self.dart_expr()?;
                 self.expect_punctuation(';')?;
-                return Ok(Node::new(Statement::Yield(expr)));
+                return Ok(node!(self, lo, Statement::Yield(expr)));
             }
         }
         if self.eat_keyword("assert") {
             let args = self.dart_arguments()?;
             self.expect_punctuation(';')?;
-            return Ok(Node::new(Statement::Assert(args)));
+            return Ok(node!(self, lo, Statement::Assert(args)));
         }
         let var_stmt = self.try(|p| {
             let var_type = p.dart_var_type(true)?;
             let vars = p.parse_one_or_more(',', |p| p.dart_name_and_initializer())?;
             p.expect_punctuation(';')?;
-            Ok(Node::new(Statement::Vars(var_type, vars)))
+            Ok(node!(p, lo, Statement::Vars(var_type, vars)))
         });
         if let Some(var_stmt) = var_stmt {
             return Ok(var_stmt);
         }
         if let Some(function) = self.try(|p| p.dart_function(true)) {
-            return Ok(Node::new(Statement::Function(function)));
+            return Ok(node!(self, lo, Statement::Function(function)));
         }
         if self.eat_punctuation(';') {
-            return Ok(Node::new(Statement::Expression(None)));
+            return Ok(node!(self, lo, Statement::Expression(None)));
         }
         let expr = self.dart_expr()?;
         self.expect_punctuation(';')?;
-        Ok(Node::new(Statement::Expression(Some(expr))))
+        Ok(node!(self, lo, Statement::Expression(Some(expr))))
     }
 
-    fn dart_fn_body(&mut self, requires_semi: bool) -> ParseResult<FnBody> {
+    pub fn dart_fn_body(&mut self, requires_semi: bool) -> ParseResult<FnBody> {
         if self.eat_keyword("native") {
             let native_thing = if !self.is_punctuation(';') {
                 self.dart_string_literal().ok()
@@ -1268,12 +1705,33 @@ impl<'a> Parser<'a> {
                 self.expect_punctuation(';')?;
             }
             Ok(FnBody::Arrow(expr))
+        } else if self.skip_bodies {
+            let lo = self.cur_span.lo;
+            self.skip_block()?;
+            Ok(FnBody::Block(node!(self, lo, Statement::Block(vec![]))))
         } else {
             Ok(FnBody::Block(self.dart_block()?))
         }
     }
 
+    /// Skips over a `{...}` block, only matching up braces.
+    fn skip_block(&mut self) -> ParseResult<()> {
+        self.expect_punctuation('{')?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.cur {
+                Some(Token::Punctuation('{')) => depth += 1,
+                Some(Token::Punctuation('}')) => depth -= 1,
+                None => expected!(self, Punctuation('}')),
+                _ => {}
+            }
+            self.bump();
+        }
+        Ok(())
+    }
+
     fn dart_type_param_def(&mut self) -> ParseResult<Node<TypeParameter>> {
+        let lo = self.cur_span.lo;
         let meta = self.dart_meta()?;
         let name = self.parse_ident()?;
         let extends = if self.eat_keyword("extends") {
@@ -1281,7 +1739,7 @@ impl<'a> Parser<'a> {
         } else {
             None
         };
-        Ok(Node::new(TypeParameter {
+        Ok(node!(self, lo, TypeParameter {
             meta,
             name,
             extends,
@@ -1289,16 +1747,18 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_name_and_initializer(&mut self) -> ParseResult<Node<VarDef>> {
+        let lo = self.cur_span.lo;
         let name = self.parse_ident()?;
         let init = if self.eat_punctuation('=') {
             Some(self.dart_expr()?)
         } else {
             None
         };
-        Ok(Node::new(VarDef { name, init }))
+        Ok(node!(self, lo, VarDef { name, init }))
     }
 
     fn dart_constructor_initializer(&mut self) -> ParseResult<ConstructorInitializer> {
+        let lo = self.cur_span.lo;
         if self.eat_keyword("assert") {
             let args = self.dart_arguments()?;
             return Ok(ConstructorInitializer::Assert(args));
@@ -1331,7 +1791,7 @@ impl<'a> Parser<'a> {
         self.expect_punctuation('=')?;
         let mut expr = self.dart_conditional_expr()?;
         while let Some(cascade) = self.try(|p| p.dart_casacade()) {
-            expr = Node::new(Expr::Cascade(expr, cascade));
+            expr = node!(self, lo, Expr::Cascade(expr, cascade));
         }
         return Ok(ConstructorInitializer::Field(this, field, expr));
     }
@@ -1406,6 +1866,7 @@ impl<'a> Parser<'a> {
     }
 
     fn dart_function(&mut self, requires_body: bool) -> ParseResult<Node<Function>> {
+        let lo = self.cur_span.lo;
         let return_type_and_name = if self.is_keyword("get") || self.is_keyword("set") {
             None
         } else {
@@ -1435,7 +1896,7 @@ impl<'a> Parser<'a> {
                 self.dart_fn_args(return_type)?
             }
         };
-        Ok(Node::new(Function {
+        Ok(node!(self, lo, Function {
             name,
             generics,
             sig,
@@ -1448,6 +1909,7 @@ impl<'a> Parser<'a> {
     }
 
     pub fn dart_class_member(&mut self, class_name: Symbol) -> ParseResult<Node<ClassMember>> {
+        let lo = self.cur_span.lo;
         let meta = self.dart_meta()?;
         let mut method_qualifiers = vec![];
         if self.eat_keyword("external") {
@@ -1483,7 +1945,7 @@ impl<'a> Parser<'a> {
             if !self.is_punctuation2('=', '>') && self.eat_punctuation('=') {
                 let path = self.dart_qualified()?;
                 self.expect_punctuation(';')?;
-                return Ok(Node::new(ClassMember::Redirect {
+                return Ok(node!(self, lo, ClassMember::Redirect {
                     meta,
                     method_qualifiers,
                     name,
@@ -1501,7 +1963,7 @@ impl<'a> Parser<'a> {
             } else {
                 Some(self.dart_fn_body(true)?)
             };
-            return Ok(Node::new(ClassMember::Constructor {
+            return Ok(node!(self, lo, ClassMember::Constructor {
                 meta,
                 method_qualifiers,
                 name,
@@ -1544,7 +2006,7 @@ impl<'a> Parser<'a> {
         });
 
         if let Some((var_type, initializers)) = fields {
-            return Ok(Node::new(ClassMember::Fields {
+            return Ok(node!(self, lo, ClassMember::Fields {
                 meta,
                 static_,
                 var_type,
@@ -1552,22 +2014,24 @@ impl<'a> Parser<'a> {
             }));
         }
         let function = self.dart_function(false)?;
-        Ok(Node::new(
+        Ok(node!(self, lo,
             ClassMember::Method(meta, method_qualifiers, function),
         ))
     }
 
     pub fn dart_item(&mut self) -> ParseResult<Node<Item>> {
+        let lo = self.cur_span.lo;
         let meta = self.dart_meta()?;
 
         if self.eat_keyword("library") {
             let path = self.parse_one_or_more('.', |p| p.parse_ident())?;
             self.expect_punctuation(';')?;
-            return Ok(Node::new(Item::LibraryName { meta, path }));
+            return Ok(node!(self, lo, Item::LibraryName { meta, path }));
         }
 
         if self.eat_keyword("import") {
             let uri = self.dart_string_literal()?;
+            let configs = self.dart_import_configs()?;
             let deferred = if self.eat_keyword("deferred") {
                 true
             } else {
@@ -1580,10 +2044,11 @@ impl<'a> Parser<'a> {
             };
             let filters = self.dart_import_filters()?;
             self.expect_punctuation(';')?;
-            return Ok(Node::new(Item::Import(
+            return Ok(node!(self, lo, Item::Import(
                 meta,
                 Import {
                     uri,
+                    configs,
                     deferred,
                     alias,
                     filters,
@@ -1597,30 +2062,50 @@ impl<'a> Parser<'a> {
         }) && self.eat_keyword("export")
         {
             let uri = self.dart_string_literal()?;
+            let configs = self.dart_import_configs()?;
             let import_filters = self.dart_import_filters()?;
             self.expect_punctuation(';')?;
-            return Ok(Node::new(Item::Export(meta, uri, import_filters)));
+            return Ok(node!(self, lo, Item::Export(meta, uri, configs, import_filters)));
         }
 
         if self.eat_keyword("part") {
             if self.eat_keyword("of") {
-                let path = self.parse_one_or_more('.', |p| p.parse_ident())?;
+                let library = if let Some(Token::StringLiteral { .. }) = self.cur {
+                    LibraryRef::Uri(self.dart_string_literal()?)
+                } else {
+                    LibraryRef::Name(self.parse_one_or_more('.', |p| p.parse_ident())?)
+                };
                 self.expect_punctuation(';')?;
-                return Ok(Node::new(Item::PartOf { meta, path }));
+                return Ok(node!(self, lo, Item::PartOf { meta, library }));
             }
             let uri = self.dart_string_literal()?;
             self.expect_punctuation(';')?;
             let mut path = self.path.parent().unwrap().to_path_buf();
             path.extend(uri.get_simple_string().split('/'));
-            return Ok(Node::new(Item::Part {
+            return Ok(node!(self, lo, Item::Part {
                 meta,
                 uri,
-                module: Module::load(&path),
+                module: if self.skip_bodies {
+                    Module::load_exports(&path)
+                } else {
+                    Module::load(&path)
+                },
             }));
         }
 
-        let abstract_ = self.eat_keyword("abstract");
-        if self.eat_keyword("class") {
+        // Class modifiers are built-in identifiers too, and `final`
+        // also starts variables, so only take them before a class.
+        let is_class = self.probe(|p| {
+            p.eat_keyword("abstract");
+            p.dart_class_modifier();
+            p.eat_keyword("mixin");
+            p.is_keyword("class")
+        });
+        if is_class {
+            let abstract_ = self.eat_keyword("abstract");
+            let modifier = self.dart_class_modifier();
+            let mixin = self.eat_keyword("mixin");
+            self.expect_keyword("class")?;
             let class_name = self.parse_ident()?;
             let mut generics = vec![];
             if self.eat_punctuation('<') {
@@ -1644,16 +2129,12 @@ impl<'a> Parser<'a> {
                 vec![]
             };
             if self.eat_punctuation('{') {
-                let mut members = vec![];
-                loop {
-                    if self.eat_punctuation('}') {
-                        break;
-                    }
-                    members.push(self.dart_class_member(class_name)?);
-                }
-                return Ok(Node::new(Item::Class {
+                let members = self.dart_class_members(class_name)?;
+                return Ok(node!(self, lo, Item::Class {
                     meta,
                     abstract_,
+                    modifier,
+                    mixin,
                     name: class_name,
                     generics,
                     superclass,
@@ -1672,9 +2153,10 @@ impl<'a> Parser<'a> {
                     vec![]
                 };
                 self.expect_punctuation(';')?;
-                return Ok(Node::new(Item::MixinClass {
+                return Ok(node!(self, lo, Item::MixinClass {
                     meta,
                     abstract_,
+                    modifier,
                     name: class_name,
                     generics,
                     mixins,
@@ -1683,23 +2165,121 @@ impl<'a> Parser<'a> {
             }
         }
 
+        // `mixin` and `extension` are only built-in identifiers, so they
+        // can still name types, e.g. of variables.
+        if self.probe(|p| {
+            p.eat_keyword("base");
+            p.eat_keyword("mixin") && p.parse_ident().is_ok()
+        }) {
+            let base = self.eat_keyword("base");
+            self.expect_keyword("mixin")?;
+            let name = self.parse_ident()?;
+            let mut generics = vec![];
+            if self.eat_punctuation('<') {
+                generics = self.parse_one_or_more(',', |p| p.dart_type_param_def())?;
+                self.expect_punctuation('>')?;
+            }
+            let constraints = if self.eat_keyword("on") {
+                self.parse_one_or_more(',', |p| p.dart_qualified())?
+            } else {
+                vec![]
+            };
+            let interfaces = if self.eat_keyword("implements") {
+                self.parse_one_or_more(',', |p| p.dart_qualified())?
+            } else {
+                vec![]
+            };
+            self.expect_punctuation('{')?;
+            let members = self.dart_class_members(name)?;
+            return Ok(node!(self, lo, Item::Mixin {
+                meta,
+                base,
+                name,
+                generics,
+                constraints,
+                interfaces,
+                members,
+            }));
+        }
+
+        let is_extension = self.probe(|p| {
+            if !p.eat_keyword("extension") {
+                return false;
+            }
+            if !p.is_keyword("on") && !p.is_punctuation('<') && p.parse_ident().is_err() {
+                return false;
+            }
+            p.is_keyword("on") || p.is_punctuation('<')
+        });
+        if is_extension {
+            self.expect_keyword("extension")?;
+            let name = if self.is_keyword("on") || self.is_punctuation('<') {
+                None
+            } else {
+                Some(self.parse_ident()?)
+            };
+            let mut generics = vec![];
+            if self.eat_punctuation('<') {
+                generics = self.parse_one_or_more(',', |p| p.dart_type_param_def())?;
+                self.expect_punctuation('>')?;
+            }
+            self.expect_keyword("on")?;
+            let on = self.dart_type()?;
+            self.expect_punctuation('{')?;
+            // Extensions have no constructors, so no member is named after them.
+            let members = self.dart_class_members(Symbol::intern(""))?;
+            return Ok(node!(self, lo, Item::Extension {
+                meta,
+                name,
+                generics,
+                on,
+                members,
+            }));
+        }
+
         if self.eat_keyword("enum") {
             let enum_name = self.parse_ident()?;
+            let mut generics = vec![];
+            if self.eat_punctuation('<') {
+                generics = self.parse_one_or_more(',', |p| p.dart_type_param_def())?;
+                self.expect_punctuation('>')?;
+            }
+            let mixins = if self.eat_keyword("with") {
+                self.parse_one_or_more(',', |p| p.dart_qualified())?
+            } else {
+                vec![]
+            };
+            let interfaces = if self.eat_keyword("implements") {
+                self.parse_one_or_more(',', |p| p.dart_qualified())?
+            } else {
+                vec![]
+            };
             self.expect_punctuation('{')?;
             let mut values = vec![];
+            let mut members = vec![];
             loop {
-                values.push((self.dart_meta()?, self.parse_ident()?));
-                if self.is_punctuation(',') {
-                    self.expect_punctuation(',')?;
-                }
                 if self.eat_punctuation('}') {
                     break;
                 }
+                values.push(self.dart_enum_value()?);
+                let comma = self.eat_punctuation(',');
+                if self.eat_punctuation(';') {
+                    members = self.dart_class_members(enum_name)?;
+                    break;
+                }
+                if !comma {
+                    self.expect_punctuation('}')?;
+                    break;
+                }
             }
-            return Ok(Node::new(Item::Enum {
+            return Ok(node!(self, lo, Item::Enum {
                 meta,
                 name: enum_name,
+                generics,
+                mixins,
+                interfaces,
                 values,
+                members,
             }));
         }
 
@@ -1715,9 +2295,9 @@ impl<'a> Parser<'a> {
                 self.expect_punctuation('>')?;
             }
             let sig = self.dart_fn_args(return_type)?;
-            let ty = Node::new(Type::FunctionOld(sig));
+            let ty = node!(self, lo, Type::FunctionOld(sig));
             self.expect_punctuation(';')?;
-            return Ok(Node::new(Item::TypeAlias {
+            return Ok(node!(self, lo, Item::TypeAlias {
                 meta,
                 name,
                 generics,
@@ -1731,17 +2311,80 @@ impl<'a> Parser<'a> {
             p.expect_punctuation(';')?;
             Ok((var_type, vars))
         }) {
-            return Ok(Node::new(Item::Vars(meta, var_type, vars)));
+            return Ok(node!(self, lo, Item::Vars(meta, var_type, vars)));
         }
 
         let external = self.eat_keyword("external");
-        Ok(Node::new(Item::Function {
+        Ok(node!(self, lo, Item::Function {
             meta,
             external,
             function: self.dart_function(false)?,
         }))
     }
 
+    fn dart_class_modifier(&mut self) -> Option<ClassModifier> {
+        for modifier in ClassModifier::values() {
+            if self.eat_keyword(modifier.as_str()) {
+                return Some(modifier);
+            }
+        }
+        None
+    }
+
+    fn dart_enum_value(&mut self) -> ParseResult<EnumValue> {
+        let meta = self.dart_meta()?;
+        let name = self.parse_ident()?;
+        let generics = if self.is_punctuation('<') {
+            self.dart_type_params()?
+        } else {
+            vec![]
+        };
+        let constructor = if self.eat_punctuation('.') {
+            Some(self.parse_ident()?)
+        } else {
+            None
+        };
+        // Type arguments and named constructors need arguments.
+        let args = if !generics.is_empty() || constructor.is_some() || self.is_punctuation('(') {
+            Some(self.dart_arguments()?)
+        } else {
+            None
+        };
+        Ok(EnumValue {
+            meta,
+            name,
+            generics,
+            constructor,
+            args,
+        })
+    }
+
+    /// Parses the members of a class-like item, after its `{`.
+    fn dart_class_members(&mut self, class_name: Symbol) -> ParseResult<Vec<Node<ClassMember>>> {
+        let mut members = vec![];
+        while !self.eat_punctuation('}') {
+            members.push(self.dart_class_member(class_name)?);
+        }
+        Ok(members)
+    }
+
+    fn dart_import_configs(&mut self) -> ParseResult<Vec<ImportConfig>> {
+        let mut configs = vec![];
+        while self.eat_keyword("if") {
+            self.expect_punctuation('(')?;
+            let name = self.parse_one_or_more('.', |p| p.parse_ident())?;
+            let value = if self.eat_punctuation2('=', '=') {
+                Some(self.dart_string_literal()?)
+            } else {
+                None
+            };
+            self.expect_punctuation(')')?;
+            let uri = self.dart_string_literal()?;
+            configs.push(ImportConfig { name, value, uri });
+        }
+        Ok(configs)
+    }
+
     fn dart_import_filters(&mut self) -> ParseResult<Vec<ImportFilter>> {
         let mut import_filters = vec![];
         loop {
@@ -1812,6 +2455,7 @@ impl<'a> Parser<'a> {
     }
 
     pub fn dart_module(mut self) -> ParseResult<Node<Module>> {
+        let lo = self.cur_span.lo;
         let mut items = vec![];
         while !self.out_of_tokens() {
             items.push(self.dart_item()?);
@@ -1822,8 +2466,8 @@ impl<'a> Parser<'a> {
                 has_error |= module.has_error;
             }
         }
-        Ok(Node::new(Module {
-            path: self.path,
+        Ok(node!(self, lo, Module {
+            path: PathBuf::clone(&self.path),
             items,
             has_error,
         }))
@@ -1,12 +1,16 @@
 use dart::ast::*;
 use dart::lex::Token;
+use dart::source_map::{Mapping, SourceMap};
 use syntax::symbol::Symbol;
 use std::fmt::{self, Write};
 use unicode_width::UnicodeWidthChar;
 use node::Node;
+use Span;
 
 pub struct Printer {
     open_boxes: Vec<LayoutBox>,
+    /// Source spans to map to the start of the next printed text.
+    pending_marks: Vec<Span>,
 }
 
 
@@ -23,6 +27,8 @@ struct LayoutBox {
     block: bool,
     children: Vec<LayoutBox>,
     sizing: BoxSizing,
+    /// Offsets into a `Text` box's contents, and the source spans they map to.
+    marks: Vec<(usize, Span)>,
 }
 
 #[derive(Default)]
@@ -43,12 +49,18 @@ impl Printer {
                     block: true,
                     kind: BoxKind::Group,
                     sizing: BoxSizing::default(),
+                    marks: vec![],
                 },
             ],
+            pending_marks: vec![],
         }
     }
 
     pub fn pretty_print(&mut self) -> String {
+        self.pretty_print_with_source_map().0
+    }
+
+    pub fn pretty_print_with_source_map(&mut self) -> (String, SourceMap) {
         self.open_boxes.pop().unwrap().pretty_print()
     }
 
@@ -59,6 +71,7 @@ impl Printer {
             block: false,
             kind,
             sizing: BoxSizing::default(),
+            marks: vec![],
         });
     }
     pub fn enter_indent(&mut self) {
@@ -80,6 +93,9 @@ impl Printer {
         let current = self.open_boxes.last_mut().unwrap();
         if let Some(last) = current.children.last_mut() {
             if let BoxKind::Text(ref mut text) = last.kind {
+                let offset = text.len();
+                last.marks
+                    .extend(self.pending_marks.drain(..).map(|span| (offset, span)));
                 write!(text, "{}", s).unwrap();
                 return;
             }
@@ -95,9 +111,19 @@ impl Printer {
                 before: 0,
                 after: 0,
             },
+            marks: self.pending_marks.drain(..).map(|span| (0, span)).collect(),
         });
     }
 
+    /// Maps the next printed text back to the span `node` was parsed from.
+    pub fn mark<T: 'static>(&mut self, node: &Node<T>) {
+        if let Some(span) = node.origin_span() {
+            if self.pending_marks.last() != Some(&span) {
+                self.pending_marks.push(span);
+            }
+        }
+    }
+
     pub fn print_token(&mut self, token: Token) {
         self.print_str(&token);
     }
@@ -128,16 +154,21 @@ impl Printer {
         }
     }
 
-    pub fn dart_items(mut self, items: &[Node<Item>]) -> String {
+    pub fn dart_items(self, items: &[Node<Item>]) -> String {
+        self.dart_items_with_source_map(items).0
+    }
+
+    pub fn dart_items_with_source_map(mut self, items: &[Node<Item>]) -> (String, SourceMap) {
         for item in items {
             self.dart_item(item);
         }
-        self.pretty_print()
+        self.pretty_print_with_source_map()
     }
 
-    pub fn dart_item(&mut self, item: &Item) {
+    pub fn dart_item(&mut self, item: &Node<Item>) {
+        self.mark(item);
         self.enter_block();
-        match *item {
+        match **item {
             Item::LibraryName { ref meta, ref path } => {
                 self.dart_meta(meta);
                 self.print_str("library ");
@@ -153,6 +184,7 @@ impl Printer {
                 self.dart_meta(meta);
                 self.print_str("import ");
                 self.dart_string_lit(&import.uri);
+                self.dart_import_configs(&import.configs);
                 if import.deferred == true {
                     self.print_str(" deferred ");
                 }
@@ -168,10 +200,11 @@ impl Printer {
                 }
                 self.print_str(";");
             }
-            Item::Export(ref meta, ref uri, ref combinators) => {
+            Item::Export(ref meta, ref uri, ref configs, ref combinators) => {
                 self.dart_meta(meta);
                 self.print_str(" export ");
                 self.dart_string_lit(uri);
+                self.dart_import_configs(configs);
                 self.print_str(" ");
                 if combinators.len() > 0 {
                     self.print_str(" ");
@@ -191,19 +224,20 @@ impl Printer {
                 self.dart_string_lit(uri);
                 self.print_str(";");
             }
-            Item::PartOf { ref meta, ref path } => {
+            Item::PartOf { ref meta, ref library } => {
                 self.dart_meta(meta);
                 self.print_str("part of ");
-                self.print_ident(path[0]);
-                for it in &path[1..] {
-                    self.print_str(".");
-                    self.print_ident(*it);
+                match *library {
+                    LibraryRef::Name(ref path) => self.dart_dotted_name(path),
+                    LibraryRef::Uri(ref uri) => self.dart_string_lit(uri),
                 }
                 self.print_str(";");
             }
             Item::Class {
                 ref meta,
                 abstract_,
+                modifier,
+                mixin,
                 name,
                 ref generics,
                 ref superclass,
@@ -215,6 +249,10 @@ impl Printer {
                 if abstract_ {
                     self.print_str("abstract ");
                 }
+                self.dart_class_modifier(modifier);
+                if mixin {
+                    self.print_str("mixin ");
+                }
                 self.print_str("class ");
                 self.print_ident(name);
                 self.dart_generics(generics);
@@ -255,6 +293,7 @@ impl Printer {
             Item::MixinClass {
                 ref meta,
                 abstract_,
+                modifier,
                 name,
                 ref generics,
                 ref mixins,
@@ -264,6 +303,7 @@ impl Printer {
                 if abstract_ {
                     self.print_str("abstract ");
                 }
+                self.dart_class_modifier(modifier);
                 self.print_str("class ");
                 self.print_ident(name);
                 self.dart_generics(generics);
@@ -289,23 +329,128 @@ impl Printer {
                 }
                 self.print_str(";");
             }
+            Item::Mixin {
+                ref meta,
+                base,
+                name,
+                ref generics,
+                ref constraints,
+                ref interfaces,
+                ref members,
+            } => {
+                self.dart_meta(meta);
+                if base {
+                    self.print_str("base ");
+                }
+                self.print_str("mixin ");
+                self.print_ident(name);
+                self.dart_generics(generics);
+                self.print_str(" ");
+                if !constraints.is_empty() {
+                    self.print_str("on ");
+                    self.enter(BoxKind::CommaDelim);
+                    for constraint in constraints {
+                        self.enter(BoxKind::Group);
+                        self.dart_qualified(constraint);
+                        self.exit();
+                    }
+                    self.exit();
+                    self.print_str(" ");
+                }
+                if !interfaces.is_empty() {
+                    self.print_str("implements ");
+                    self.enter(BoxKind::CommaDelim);
+                    for interface in interfaces {
+                        self.enter(BoxKind::Group);
+                        self.dart_qualified(interface);
+                        self.exit();
+                    }
+                    self.exit();
+                }
+                self.print_str("{");
+                self.enter_indent();
+                for member in members {
+                    self.dart_class_member(member, name);
+                }
+                self.exit();
+                self.print_str("}");
+            }
+            Item::Extension {
+                ref meta,
+                name,
+                ref generics,
+                ref on,
+                ref members,
+            } => {
+                self.dart_meta(meta);
+                self.print_str("extension");
+                if let Some(name) = name {
+                    self.print_str(" ");
+                    self.print_ident(name);
+                }
+                self.dart_generics(generics);
+                self.print_str(" on ");
+                self.dart_type(on);
+                self.print_str(" {");
+                self.enter_indent();
+                for member in members {
+                    self.dart_class_member(member, Symbol::intern(""));
+                }
+                self.exit();
+                self.print_str("}");
+            }
             Item::Enum {
                 ref meta,
                 name,
+                ref generics,
+                ref mixins,
+                ref interfaces,
                 ref values,
+                ref members,
             } => {
                 self.dart_meta(meta);
                 self.print_str("enum ");
                 self.print_ident(name);
-                self.print_str(" {");
+                self.dart_generics(generics);
+                self.print_str(" ");
+                if !mixins.is_empty() {
+                    self.print_str("with ");
+                    self.enter(BoxKind::CommaDelim);
+                    for mixin in mixins {
+                        self.enter(BoxKind::Group);
+                        self.dart_qualified(mixin);
+                        self.exit();
+                    }
+                    self.exit();
+                    self.print_str(" ");
+                }
+                if !interfaces.is_empty() {
+                    self.print_str("implements ");
+                    self.enter(BoxKind::CommaDelim);
+                    for interface in interfaces {
+                        self.enter(BoxKind::Group);
+                        self.dart_qualified(interface);
+                        self.exit();
+                    }
+                    self.exit();
+                    self.print_str(" ");
+                }
+                self.print_str("{");
                 self.enter(BoxKind::CommaDelim);
-                for &(ref meta, value) in values {
+                for value in values {
                     self.enter_indent();
-                    self.dart_meta(meta);
-                    self.print_ident(value);
+                    self.dart_enum_value(value);
                     self.exit();
                 }
                 self.exit();
+                if !members.is_empty() {
+                    self.print_str(";");
+                    self.enter_indent();
+                    for member in members {
+                        self.dart_class_member(member, name);
+                    }
+                    self.exit();
+                }
                 self.print_str("}");
             }
             Item::TypeAlias {
@@ -339,8 +484,39 @@ impl Printer {
         self.exit();
     }
 
-    pub fn dart_statement(&mut self, statement: &Statement) {
-        match *statement {
+    fn dart_class_modifier(&mut self, modifier: Option<ClassModifier>) {
+        if let Some(modifier) = modifier {
+            self.print_str(modifier.as_str());
+            self.print_str(" ");
+        }
+    }
+
+    fn dart_enum_value(&mut self, value: &EnumValue) {
+        self.dart_meta(&value.meta);
+        self.print_ident(value.name);
+        if !value.generics.is_empty() {
+            self.print_str("<");
+            self.enter(BoxKind::CommaDelim);
+            for ty in &value.generics {
+                self.enter(BoxKind::Group);
+                self.dart_type(ty);
+                self.exit();
+            }
+            self.exit();
+            self.print_str(">");
+        }
+        if let Some(constructor) = value.constructor {
+            self.print_str(".");
+            self.print_ident(constructor);
+        }
+        if let Some(ref args) = value.args {
+            self.dart_arguments(args);
+        }
+    }
+
+    pub fn dart_statement(&mut self, statement: &Node<Statement>) {
+        self.mark(statement);
+        match **statement {
             Statement::Comments(ref comments, ref statement) => {
                 for &comment in comments {
                     self.enter_block();
@@ -535,8 +711,9 @@ impl Printer {
         }
     }
 
-    pub fn dart_expr(&mut self, expr: &Expr) {
-        match *expr {
+    pub fn dart_expr(&mut self, expr: &Node<Expr>) {
+        self.mark(expr);
+        match **expr {
             Expr::Comments(ref comments, ref expr) => {
                 for &comment in comments {
                     self.enter_block();
@@ -664,7 +841,7 @@ impl Printer {
                 self.exit();
                 self.print_str("}");
             }
-            Expr::Number(n) => {
+            Expr::Int(n) | Expr::Double(n) => {
                 self.print_str(&n.as_str());
             }
             Expr::String(ref literals) => for lit in literals {
@@ -675,6 +852,35 @@ impl Printer {
                 self.dart_expr(expr);
                 self.print_str(")");
             }
+            Expr::Record(ref args) => {
+                // `(x)` would be a parenthesized expression.
+                if args.unnamed.len() == 1 && args.named.is_empty() {
+                    self.print_str("(");
+                    self.dart_expr(&args.unnamed[0]);
+                    self.print_str(",)");
+                } else {
+                    self.dart_arguments(args);
+                }
+            }
+            Expr::Switch(ref expr, ref cases) => {
+                self.print_str("switch (");
+                self.dart_expr(expr);
+                self.print_str(") {");
+                self.enter(BoxKind::CommaDelim);
+                for case in cases {
+                    self.enter(BoxKind::Group);
+                    self.dart_pattern(&case.pattern);
+                    if let Some(ref guard) = case.guard {
+                        self.print_str(" when ");
+                        self.dart_expr(guard);
+                    }
+                    self.print_str(" => ");
+                    self.dart_expr(&case.body);
+                    self.exit();
+                }
+                self.exit();
+                self.print_str("}");
+            }
             Expr::Symbol(ref literal) => {
                 self.print_str("#");
                 match *literal {
@@ -700,6 +906,122 @@ impl Printer {
         }
     }
 
+    pub fn dart_pattern(&mut self, pattern: &Node<Pattern>) {
+        self.mark(pattern);
+        match **pattern {
+            Pattern::Wildcard => {
+                self.print_str("_");
+            }
+            Pattern::Var(ref var_ty, ref var) => {
+                self.dart_vars(var_ty, &[var.clone()]);
+            }
+            Pattern::Const(ref expr) => {
+                self.dart_expr(expr);
+            }
+            Pattern::Relational(op, ref expr) => {
+                self.dart_binary_op(BinOp::Bool(op));
+                self.print_str(" ");
+                self.dart_expr(expr);
+            }
+            Pattern::Or(ref a, ref b) => {
+                self.dart_pattern(a);
+                self.print_str(" || ");
+                self.dart_pattern(b);
+            }
+            Pattern::And(ref a, ref b) => {
+                self.dart_pattern(a);
+                self.print_str(" && ");
+                self.dart_pattern(b);
+            }
+            Pattern::Cast(ref pattern, ref ty) => {
+                self.dart_pattern(pattern);
+                self.print_str(" as ");
+                self.dart_type(ty);
+            }
+            Pattern::NullCheck(ref pattern) => {
+                self.dart_pattern(pattern);
+                self.print_str("?");
+            }
+            Pattern::NullAssert(ref pattern) => {
+                self.dart_pattern(pattern);
+                self.print_str("!");
+            }
+            Pattern::List(ref elements) => {
+                self.print_str("[");
+                self.enter(BoxKind::CommaDelim);
+                for element in elements {
+                    self.enter(BoxKind::Group);
+                    self.dart_pattern(element);
+                    self.exit();
+                }
+                self.exit();
+                self.print_str("]");
+            }
+            Pattern::Rest(ref rest) => {
+                self.print_str("...");
+                if let Some(ref rest) = *rest {
+                    self.dart_pattern(rest);
+                }
+            }
+            Pattern::Map(ref kv) => {
+                self.print_str("{");
+                self.enter(BoxKind::CommaDelim);
+                for &(ref k, ref v) in kv {
+                    self.enter(BoxKind::Group);
+                    self.dart_expr(k);
+                    self.print_str(": ");
+                    self.dart_pattern(v);
+                    self.exit();
+                }
+                self.exit();
+                self.print_str("}");
+            }
+            Pattern::Record(ref fields) => {
+                // `(x)` would be a parenthesized pattern.
+                match fields.first() {
+                    Some(&PatternField::Positional(ref pattern)) if fields.len() == 1 => {
+                        self.print_str("(");
+                        self.dart_pattern(pattern);
+                        self.print_str(",)");
+                    }
+                    _ => self.dart_pattern_fields(fields),
+                }
+            }
+            Pattern::Object(ref qualified, ref fields) => {
+                self.dart_qualified(qualified);
+                self.dart_pattern_fields(fields);
+            }
+            Pattern::Paren(ref pattern) => {
+                self.print_str("(");
+                self.dart_pattern(pattern);
+                self.print_str(")");
+            }
+        }
+    }
+
+    fn dart_pattern_fields(&mut self, fields: &[PatternField]) {
+        self.print_str("(");
+        self.enter(BoxKind::CommaDelim);
+        for field in fields {
+            self.enter(BoxKind::Group);
+            match *field {
+                PatternField::Positional(ref pattern) => {
+                    self.dart_pattern(pattern);
+                }
+                PatternField::Named(name, ref pattern) => {
+                    if let Some(name) = name {
+                        self.print_ident(name);
+                    }
+                    self.print_str(": ");
+                    self.dart_pattern(pattern);
+                }
+            }
+            self.exit();
+        }
+        self.exit();
+        self.print_str(")");
+    }
+
     fn dart_function(&mut self, func: &Function) {
         self.dart_type_spaced(&func.sig.return_type);
         self.dart_function_name(func.name);
@@ -795,6 +1117,11 @@ impl Printer {
             self.exit();
         }
         self.exit();
+        for &comment in &args.trailing_comments {
+            self.enter_block();
+            self.print_token(Token::Comment(comment));
+            self.exit();
+        }
         self.print_str(")");
     }
 
@@ -812,15 +1139,56 @@ impl Printer {
                 self.print_str(" Function");
                 self.dart_fn_args(&signature);
             }
+            Type::Record {
+                ref positional,
+                ref named,
+            } => {
+                self.print_str("(");
+                // `(T)` would be a parenthesized type.
+                if positional.len() == 1 && named.is_empty() {
+                    self.dart_record_type_field(&positional[0].0, positional[0].1);
+                    self.print_str(",");
+                } else {
+                    self.enter(BoxKind::CommaDelim);
+                    for &(ref ty, name) in positional {
+                        self.enter(BoxKind::Group);
+                        self.dart_record_type_field(ty, name);
+                        self.exit();
+                    }
+                    if !named.is_empty() {
+                        self.enter(BoxKind::Group);
+                        self.print_str("{");
+                        self.enter(BoxKind::CommaDelim);
+                        for &(ref ty, name) in named {
+                            self.enter(BoxKind::Group);
+                            self.dart_record_type_field(ty, Some(name));
+                            self.exit();
+                        }
+                        self.exit();
+                        self.print_str("}");
+                        self.exit();
+                    }
+                    self.exit();
+                }
+                self.print_str(")");
+            }
             Type::FunctionOld(_) | Type::Infer => {
                 unreachable!();
             }
         }
     }
 
+    fn dart_record_type_field(&mut self, ty: &Type, name: Option<Symbol>) {
+        self.dart_type(ty);
+        if let Some(name) = name {
+            self.print_str(" ");
+            self.print_ident(name);
+        }
+    }
+
     fn dart_type_spaced(&mut self, ty: &Type) {
         match *ty {
-            Type::Path(..) | Type::Function(..) => {
+            Type::Path(..) | Type::Function(..) | Type::Record { .. } => {
                 self.dart_type(ty);
                 self.print_str(" ");
             }
@@ -839,7 +1207,7 @@ impl Printer {
         params: &[Node<TypeParameter>],
     ) {
         match *ty {
-            Type::Path(..) | Type::Infer | Type::Function(..) => {
+            Type::Path(..) | Type::Infer | Type::Function(..) | Type::Record { .. } => {
                 self.dart_type_spaced(ty);
                 self.print_str(prefix);
                 self.print_ident(name);
@@ -893,7 +1261,7 @@ impl Printer {
         self.dart_expr(&arg.expr);
     }
 
-    fn dart_function_body(&mut self, body: &FnBody, semicolum: bool) {
+    pub fn dart_function_body(&mut self, body: &FnBody, semicolum: bool) {
         match *body {
             FnBody::Arrow(ref expr) => {
                 self.print_str(" => ");
@@ -904,7 +1272,7 @@ impl Printer {
             }
             FnBody::Block(ref stm) => {
                 self.print_str(" ");
-                self.dart_statement(stm);
+                self.dart_statement(&stm.filled());
             }
             FnBody::Native(ref lit) => {
                 self.print_str("native");
@@ -918,7 +1286,7 @@ impl Printer {
         }
     }
 
-    fn dart_fn_args(&mut self, args: &FnSig) {
+    pub fn dart_fn_args(&mut self, args: &FnSig) {
         self.print_str("(");
         self.enter(BoxKind::CommaDelim);
         for it in &args.required {
@@ -1055,6 +1423,27 @@ impl Printer {
         }
     }
 
+    fn dart_dotted_name(&mut self, path: &[Symbol]) {
+        self.print_ident(path[0]);
+        for it in &path[1..] {
+            self.print_str(".");
+            self.print_ident(*it);
+        }
+    }
+
+    fn dart_import_configs(&mut self, configs: &[ImportConfig]) {
+        for config in configs {
+            self.print_str(" if (");
+            self.dart_dotted_name(&config.name);
+            if let Some(ref value) = config.value {
+                self.print_str(" == ");
+                self.dart_string_lit(value);
+            }
+            self.print_str(") ");
+            self.dart_string_lit(&config.uri);
+        }
+    }
+
     fn dart_combinator(&mut self, comb: &ImportFilter) {
         if comb.hide == true {
             self.print_str("hide ");
@@ -1079,9 +1468,10 @@ impl Printer {
         }
     }
 
-    pub fn dart_class_member(&mut self, member: &ClassMember, class_name: Symbol) {
+    pub fn dart_class_member(&mut self, member: &Node<ClassMember>, class_name: Symbol) {
+        self.mark(member);
         self.enter_block();
-        match *member {
+        match **member {
             ClassMember::Redirect {
                 ref meta,
                 ref method_qualifiers,
@@ -1391,7 +1781,12 @@ impl LayoutBox {
             }
         }
     }
-    fn print_into_lines(&self, mut depth: usize, lines: &mut Vec<String>) {
+    fn print_into_lines(
+        &self,
+        mut depth: usize,
+        lines: &mut Vec<String>,
+        marks: &mut Vec<(usize, usize, Span)>,
+    ) {
         fn indent(string: &mut String, depth: usize) {
             if !string.is_empty() {
                 return;
@@ -1404,6 +1799,11 @@ impl LayoutBox {
             if !s.is_empty() {
                 indent(lines.last_mut().unwrap(), depth);
             }
+            let line = lines.len() - 1;
+            let column = lines[line].len();
+            for &(offset, span) in &self.marks {
+                marks.push((line, column + offset, span));
+            }
             lines.last_mut().unwrap().push_str(s);
             return;
         }
@@ -1417,7 +1817,7 @@ impl LayoutBox {
             if self.kind == BoxKind::CommaDelim && self.block && !lines.last().unwrap().is_empty() {
                 lines.push(String::new());
             }
-            child.print_into_lines(depth, lines);
+            child.print_into_lines(depth, lines, marks);
             if self.kind == BoxKind::CommaDelim {
                 if self.block {
                     lines.last_mut().unwrap().push_str(",");
@@ -1431,17 +1831,33 @@ impl LayoutBox {
         }
     }
 
-    pub fn pretty_print(&mut self) -> String {
+    pub fn pretty_print(&mut self) -> (String, SourceMap) {
         self.compute_sizes();
 
         let mut lines = vec![String::new()];
-        self.print_into_lines(0, &mut lines);
+        let mut marks = vec![];
+        self.print_into_lines(0, &mut lines, &mut marks);
 
+        // Text may span several lines (e.g. block comments), so the
+        // positions of marks are only known once everything is printed.
+        let mut source_map = SourceMap::default();
+        let mut first_line = vec![];
         let mut result = String::new();
-        for line in lines {
-            result.push_str(&line);
+        let mut output_line = 0;
+        for line in &lines {
+            first_line.push(output_line);
+            output_line += line.matches('\n').count() + 1;
+            result.push_str(line);
             result.push('\n');
         }
-        result
+        for (line, column, span) in marks {
+            let before = &lines[line][..column];
+            source_map.mappings.push(Mapping {
+                line: first_line[line] + before.matches('\n').count(),
+                column: before.rfind('\n').map_or(column, |i| column - i - 1),
+                span,
+            });
+        }
+        (result, source_map)
     }
 }
@@ -1,11 +1,18 @@
-use dart::ast::{ClassMember, Expr, FnName, ForLoop, Function, ImportFilter, Item, Module,
-                Qualified, Statement, TryPart, TypeParameter, VarDef};
+use dart::ast::{ArgDef, Args, ClassMember, Expr, FnName, FnSig, ForLoop, Function, ImportConfig,
+                ImportFilter, Item, LibraryRef, Module, OptionalArgKind, OverloadedOp, Pattern,
+                Qualified, Statement, StringLiteral, Suffix, SwitchExprCase, TryPart, Type,
+                TypeParameter, VarDef};
 use dart::visit::{Visit, VisitNode, Visitor};
 use dart::sdk;
+use dart::summary;
 use dsl;
 use node::Node;
 use std::any::Any;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use syntax::symbol::Symbol;
 
@@ -15,10 +22,13 @@ pub enum Res {
     // Types.
     Void,
     Dynamic,
-    Module(Node<Module>),
+    /// A library prefix (`import 'library' as prefix;`), with the names
+    /// imported through it.
+    Prefix(Rc<ScopeChain>),
     Class(Node<Item>),
     Enum(Node<Item>),
     TypeAlias(Node<Item>),
+    Extension(Node<Item>),
     TypeParameter(Node<TypeParameter>),
 
     // Values.
@@ -34,50 +44,195 @@ pub enum Res {
     Setter(Node<Function>),
     GetterSetter(Node<Function>, Node<Function>),
     Constructor(Node<ClassMember>),
+    /// A value of an enum (`Color.red`), with the enum it belongs to.
+    EnumValue(Node<Item>, Symbol),
+    /// The list of all the values of an enum (`Color.values`).
+    EnumValues(Node<Item>),
 
     Dsl(dsl::resolve::Res),
 
+    /// An export of the module at the given path, known from its summary
+    /// but not loaded yet.
+    Unloaded(Rc<PathBuf>, Symbol),
+
+    /// A name imported from several libraries, which is an error to use.
+    Ambiguous(Vec<Res>),
+
     Error,
 }
 
 impl Res {
     pub fn lookup_member(&self, name: Symbol) -> Res {
         match *self {
-            Res::Class(ref item) => item.exports().lookup(name),
-            Res::Module(ref module) => module.exports().lookup(name),
+            Res::Class(ref item) | Res::Enum(ref item) => item.exports().lookup(name),
+            Res::Prefix(ref scope) => scope.lookup(name),
             _ => Res::Error,
         }
     }
+
+    /// Loads the module an `Unloaded` export is defined in, to get at the
+    /// definition itself.
+    pub fn load(&self) -> Res {
+        match *self {
+            Res::Unloaded(ref path, name) => Module::load_exports(path).exports().lookup(name),
+            ref res => res.clone(),
+        }
+    }
+
+    /// Combines `self` with `other`, imported under the same name, which
+    /// is ambiguous unless both refer to the same declaration.
+    fn imported_with(&self, other: &Res) -> Res {
+        let same = |a: &Res, b: &Res| a == b || a.load() == b.load();
+        match *self {
+            Res::Ambiguous(ref all) => if all.iter().any(|res| same(res, other)) {
+                self.clone()
+            } else {
+                let mut all = all.clone();
+                all.push(other.clone());
+                Res::Ambiguous(all)
+            },
+            _ => if same(self, other) {
+                self.clone()
+            } else {
+                Res::Ambiguous(vec![self.clone(), other.clone()])
+            },
+        }
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct ScopeChain {
     parent: Option<Rc<ScopeChain>>,
     map: HashMap<Symbol, Res>,
+    /// The extensions in scope, once `extensions` collected them.
+    extensions: RefCell<Option<Rc<Vec<Node<Item>>>>>,
+}
+
+impl PartialEq for ScopeChain {
+    fn eq(&self, other: &ScopeChain) -> bool {
+        self.parent == other.parent && self.map == other.map
+    }
 }
 
+impl Eq for ScopeChain {}
+
 impl ScopeChain {
     pub fn new(parent: Option<Rc<ScopeChain>>) -> Rc<ScopeChain> {
         Rc::new(ScopeChain {
             parent: parent,
             map: HashMap::new(),
+            extensions: RefCell::new(None),
         })
     }
     pub fn lookup(&self, name: Symbol) -> Res {
         if let Some(res) = self.map.get(&name) {
-            res.clone()
+            res.load()
         } else if let Some(ref parent) = self.parent {
             parent.lookup(name)
         } else {
             Res::Error
         }
     }
+    /// Looks up `name` as a member of `target`, or else as a member added
+    /// to it by one of the extensions in scope. Extensions on supertypes of
+    /// `target` apply too, and when several do, the one on the most
+    /// specific type wins.
+    pub fn lookup_member(&self, target: &Res, name: Symbol) -> Res {
+        let res = target.lookup_member(name);
+        if res != Res::Error {
+            return res;
+        }
+        let ty = match *target {
+            Res::Class(ref class) | Res::Enum(ref class) => StaticType {
+                class: class.clone(),
+                args: vec![],
+            },
+            _ => return Res::Error,
+        };
+        let mut found: Option<(StaticType, Res)> = None;
+        for extension in self.extensions().iter() {
+            let on = match extension_target(extension) {
+                Some(on) => on,
+                None => continue,
+            };
+            let on = match ty.as_instance_of(&on) {
+                Some(on) => on,
+                None => continue,
+            };
+            if let Some((ref best, _)) = found {
+                if on.as_instance_of(&best.class).is_none() {
+                    continue;
+                }
+            }
+            let res = extension.exports().lookup(name);
+            if res != Res::Error {
+                found = Some((on, res));
+            }
+        }
+        found.map_or(Res::Error, |(_, res)| res)
+    }
+    /// Returns the extensions in scope, including those imported with a
+    /// prefix, which apply all the same. They're collected once, on the
+    /// first member lookup that needs them, until the scope changes.
+    fn extensions(&self) -> Rc<Vec<Node<Item>>> {
+        if let Some(ref extensions) = *self.extensions.borrow() {
+            return extensions.clone();
+        }
+        let mut extensions = match self.parent {
+            Some(ref parent) => (*parent.extensions()).clone(),
+            None => vec![],
+        };
+        for res in self.map.values() {
+            match *res {
+                Res::Extension(ref extension) => extensions.push(extension.clone()),
+                Res::Prefix(ref prefix) => extensions.extend(prefix.extensions().iter().cloned()),
+                _ => {}
+            }
+        }
+        let extensions = Rc::new(extensions);
+        *self.extensions.borrow_mut() = Some(extensions.clone());
+        extensions
+    }
+    pub fn insert(&mut self, name: Symbol, res: Res) {
+        self.map.insert(name, res);
+        *self.extensions.get_mut() = None;
+    }
+    /// Returns the entries of this scope, if it has no parent to inherit from.
+    pub fn local_entries(&self) -> Option<&HashMap<Symbol, Res>> {
+        if self.parent.is_some() {
+            None
+        } else {
+            Some(&self.map)
+        }
+    }
+    /// Adds `namespace`, the names imported from a library, to this scope.
+    ///
+    /// Names from platform (`dart:`) libraries go in the parent scope, so
+    /// that those from other libraries shadow them, as the spec requires.
+    fn import(&mut self, namespace: &ScopeChain, platform: bool) {
+        {
+            let scope = if platform {
+                Rc::make_mut(self.parent.get_or_insert_with(|| ScopeChain::new(None)))
+            } else {
+                &mut *self
+            };
+            for (&name, res) in &namespace.map {
+                let res = match scope.map.get(&name) {
+                    Some(existing) => existing.imported_with(res),
+                    None => res.clone(),
+                };
+                scope.insert(name, res);
+            }
+        }
+        *self.extensions.get_mut() = None;
+    }
     pub fn extend(&mut self, scope: &ScopeChain) {
         if let Some(ref parent) = scope.parent {
             self.extend(parent);
         }
         self.map
             .extend(scope.map.iter().map(|(&k, v)| (k, v.clone())));
+        *self.extensions.get_mut() = None;
     }
 }
 
@@ -97,7 +252,9 @@ impl<T: VisitNode> Node<T> {
             if let Item::Class { ref superclass, .. } = *item {
                 if let Some(ref superclass) = *superclass {
                     if superclass.res().get().is_none() {
-                        resolve(item.root_module().unwrap(), false);
+                        if let Some(module) = item.root_module() {
+                            resolve(module, false);
+                        }
                     }
                     if let Some(Res::Class(superclass)) = superclass.res().get() {
                         collector.scope_mut().parent = Some(superclass.exports());
@@ -106,7 +263,7 @@ impl<T: VisitNode> Node<T> {
                     }
                 }
             }
-            match *item {
+            let supertypes: Vec<_> = match *item {
                 Item::Class {
                     ref mixins,
                     ref interfaces,
@@ -116,17 +273,36 @@ impl<T: VisitNode> Node<T> {
                     ref mixins,
                     ref interfaces,
                     ..
-                } => for class in mixins.iter().chain(interfaces) {
-                    if class.res().get().is_none() {
-                        resolve(item.root_module().unwrap(), false);
-                    }
-                    if let Some(Res::Class(class)) = class.res().get() {
-                        collector.scope_mut().extend(&class.exports());
-                    } else {
-                        println!("unknown class {:?}", class);
+                } => mixins.iter().chain(interfaces).collect(),
+                Item::Mixin {
+                    ref constraints,
+                    ref interfaces,
+                    ..
+                } => constraints.iter().chain(interfaces).collect(),
+                Item::Enum {
+                    ref mixins,
+                    ref interfaces,
+                    ..
+                } => mixins.iter().chain(interfaces).collect(),
+                _ => vec![],
+            };
+            for class in supertypes {
+                if class.res().get().is_none() {
+                    if let Some(module) = item.root_module() {
+                        resolve(module, false);
                     }
-                },
-                _ => {}
+                }
+                if let Some(Res::Class(class)) = class.res().get() {
+                    collector.scope_mut().extend(&class.exports());
+                } else {
+                    println!("unknown class {:?}", class);
+                }
+            }
+            if let Item::Enum { ref values, .. } = *item {
+                for value in values {
+                    collector.record(value.name, Res::EnumValue(item.clone(), value.name));
+                }
+                collector.record("values", Res::EnumValues(item.clone()));
             }
         }
         self.super_visit(&mut collector);
@@ -137,22 +313,711 @@ impl<T: VisitNode> Node<T> {
 
 node_field!(res: Res);
 
-pub fn resolve(module: Node<Module>, fully_resolve: bool) {
-    let collector = &mut Collector::new();
+/// The class an extension adds members to, once `extension_target` found
+/// it, or `None` if it doesn't extend a class (`extension<T> on T`).
+node_field!(cached_extension_target: Option<Node<Item>>);
+
+/// Returns the class `extension` adds members to, if it extends a class.
+fn extension_target(extension: &Node<Item>) -> Option<Node<Item>> {
+    if let Some(target) = extension.cached_extension_target().get() {
+        return target;
+    }
+    let target = match **extension {
+        Item::Extension { ref on, .. } => match **on {
+            Type::Path(ref qualified) => match qualified_res(qualified) {
+                Some(Res::Class(class)) | Some(Res::Enum(class)) => Some(class),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    };
+    extension.cached_extension_target().set(target.clone());
+    target
+}
+
+/// Set on modules once `resolve` started going through them.
+node_field!(resolved: bool);
+
+/// The static type of an expression, when it's an instance of a class,
+/// along with the type arguments of the class, as far as they are known.
+#[derive(Clone, Debug)]
+pub struct StaticType {
+    pub class: Node<Item>,
+    pub args: Vec<Option<StaticType>>,
+}
+
+/// Type arguments, by the names of the type parameters they are bound to.
+type Bindings = Vec<(Symbol, Option<StaticType>)>;
+
+/// How deep computing a static type may go through initializers and
+/// supertypes before giving up, which it would otherwise never do for
+/// cyclic declarations, like `var a = b, b = a;` or `class A extends A {}`.
+const MAX_TYPE_DEPTH: usize = 32;
+
+/// Returns what `qualified` refers to, resolving the types of the module
+/// it's in first, if that wasn't done yet.
+pub fn qualified_res(qualified: &Node<Qualified>) -> Option<Res> {
+    if qualified.res().get().is_none() {
+        let module = qualified.root_module()?;
+        if module.resolved().get().is_none() {
+            resolve(module, false);
+        }
+    }
+    qualified.res().get()
+}
+
+fn type_params(item: &Item) -> &[Node<TypeParameter>] {
+    match *item {
+        Item::Class { ref generics, .. } |
+        Item::MixinClass { ref generics, .. } |
+        Item::Mixin { ref generics, .. } |
+        Item::Enum { ref generics, .. } => generics,
+        _ => &[],
+    }
+}
+
+/// Returns the classes `item` directly inherits members from.
+fn supertypes(item: &Item) -> Vec<Node<Qualified>> {
+    match *item {
+        Item::Class {
+            ref superclass,
+            ref mixins,
+            ref interfaces,
+            ..
+        } => superclass.iter().chain(mixins).chain(interfaces).cloned().collect(),
+        Item::MixinClass {
+            ref mixins,
+            ref interfaces,
+            ..
+        } |
+        Item::Enum {
+            ref mixins,
+            ref interfaces,
+            ..
+        } => mixins.iter().chain(interfaces).cloned().collect(),
+        Item::Mixin {
+            ref constraints,
+            ref interfaces,
+            ..
+        } => constraints.iter().chain(interfaces).cloned().collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns the class, mixin or enum `node` is in, or, in an extension,
+/// the class it extends.
+fn enclosing_class<T: 'static>(node: &Node<T>) -> Option<Node<Item>> {
+    let mut parent = node.parent_any().get();
+    while let Some(node) = parent {
+        if let Some(item) = node.downcast::<Item>() {
+            return match *item {
+                Item::Class { .. } |
+                Item::MixinClass { .. } |
+                Item::Mixin { .. } |
+                Item::Enum { .. } => Some(item.clone()),
+                Item::Extension { .. } => extension_target(&item),
+                _ => None,
+            };
+        }
+        parent = node.parent_any().get();
+    }
+    None
+}
+
+fn class_res(class: &Node<Item>) -> Res {
+    match **class {
+        Item::Enum { .. } => Res::Enum(class.clone()),
+        _ => Res::Class(class.clone()),
+    }
+}
+
+/// Returns the type of instances of the `dart:core` class `name`.
+fn core_type(name: &str, args: Vec<Option<StaticType>>) -> Option<StaticType> {
+    let core = Module::load_exports(&import_path(None, "dart:core"));
+    match core.exports().lookup(Symbol::intern(name)) {
+        Res::Class(class) => Some(StaticType { class, args }),
+        _ => None,
+    }
+}
+
+impl StaticType {
+    fn bindings(&self) -> Bindings {
+        type_params(&self.class)
+            .iter()
+            .enumerate()
+            .map(|(i, param)| (param.name, self.args.get(i).cloned().and_then(|arg| arg)))
+            .collect()
+    }
+
+    /// Returns this type as an instance of `class`, one of its supertypes.
+    fn as_instance_of(&self, class: &Node<Item>) -> Option<StaticType> {
+        self.as_instance_of_at(class, 0)
+    }
+
+    fn as_instance_of_at(&self, class: &Node<Item>, depth: usize) -> Option<StaticType> {
+        if self.class == *class {
+            return Some(self.clone());
+        }
+        if depth >= MAX_TYPE_DEPTH {
+            return None;
+        }
+        let bindings = self.bindings();
+        supertypes(&self.class).iter().filter_map(|supertype| {
+            eval_qualified(supertype, &bindings)?.as_instance_of_at(class, depth + 1)
+        }).next()
+    }
+
+    /// Looks up the instance member `name`, falling back to the members
+    /// of `Object`, which all classes have.
+    fn lookup_member(&self, scope: &ScopeChain, name: Symbol) -> Res {
+        let res = scope.lookup_member(&class_res(&self.class), name);
+        if res != Res::Error {
+            return res;
+        }
+        match core_type("Object", vec![]) {
+            Some(object) => scope.lookup_member(&Res::Class(object.class), name),
+            None => Res::Error,
+        }
+    }
+}
+
+fn eval_type(ty: &Node<Type>, bindings: &Bindings) -> Option<StaticType> {
+    match **ty {
+        Type::Path(ref qualified) => eval_qualified(qualified, bindings),
+        _ => None,
+    }
+}
+
+fn eval_qualified(qualified: &Node<Qualified>, bindings: &Bindings) -> Option<StaticType> {
+    if qualified.prefix.is_none() {
+        if let Some(&(_, ref arg)) = bindings.iter().find(|&&(name, _)| name == qualified.name) {
+            return arg.clone();
+        }
+    }
+    match qualified_res(qualified)? {
+        Res::Class(class) | Res::Enum(class) => Some(StaticType {
+            class,
+            args: qualified
+                .params
+                .iter()
+                .map(|param| eval_type(param, bindings))
+                .collect(),
+        }),
+        Res::TypeParameter(param) => eval_qualified(param.extends.as_ref()?, bindings),
+        _ => None,
+    }
+}
+
+/// Returns the type arguments `receiver` has as an instance of the class
+/// `member` is declared in.
+fn member_bindings<T: 'static>(member: &Node<T>, receiver: Option<&StaticType>) -> Bindings {
+    let receiver = enclosing_class(member)
+        .and_then(|class| receiver?.as_instance_of(&class));
+    receiver.map_or(vec![], |receiver| receiver.bindings())
+}
+
+/// Returns the type of the value `res` refers to, accessed on `receiver`
+/// if it's a member, or of the result of calling it, if `called`.
+fn res_type(
+    res: &Res,
+    receiver: Option<&StaticType>,
+    called: bool,
+    depth: usize,
+) -> Option<StaticType> {
+    match *res {
+        Res::Var(ref var) if !called => var_type(var, &member_bindings(var, receiver), depth),
+        Res::Getter(ref getter) | Res::GetterSetter(ref getter, _) if !called => {
+            eval_type(&getter.sig.return_type, &member_bindings(getter, receiver))
+        }
+        Res::Function(ref function) if called => {
+            eval_type(&function.sig.return_type, &member_bindings(function, receiver))
+        }
+        Res::Constructor(ref constructor) if called => Some(StaticType {
+            class: enclosing_class(constructor)?,
+            args: vec![],
+        }),
+        Res::Class(ref class) if called => Some(StaticType {
+            class: class.clone(),
+            args: vec![],
+        }),
+        Res::EnumValue(ref item, _) if !called => Some(StaticType {
+            class: item.clone(),
+            args: vec![],
+        }),
+        Res::EnumValues(ref item) if !called => core_type(
+            "List",
+            vec![Some(StaticType {
+                class: item.clone(),
+                args: vec![],
+            })],
+        ),
+        Res::Dsl(dsl::resolve::Res::Field(ref field)) if !called => match **field.ty.as_ref()? {
+            dsl::ast::Type::Dart(ref ty) => eval_type(ty, &vec![]),
+        },
+        _ => None,
+    }
+}
+
+/// Returns the type `var` is declared with, or else that of its initializer.
+fn var_type(var: &Node<VarDef>, bindings: &Bindings, depth: usize) -> Option<StaticType> {
+    if let Some(ty) = declared_type(var) {
+        match *ty {
+            Type::Infer => {}
+            _ => return eval_type(&ty, bindings),
+        }
+    }
+    expr_type_at(var.init.as_ref()?, depth + 1)
+}
+
+/// Returns the type `var` is declared with, if it's declared with one,
+/// which for field parameters (`this.x`) without one is that of the field.
+pub fn declared_type(var: &Node<VarDef>) -> Option<Node<Type>> {
+    let parent = var.parent_any().get()?;
+    let in_sig = |sig: &FnSig| {
+        let arg = sig.required.iter().chain(&sig.optional).find(|arg| arg.var == *var)?;
+        match (&*arg.ty.ty, arg.field) {
+            (&Type::Infer, true) => field_type(&enclosing_class(var)?, var.name),
+            _ => Some(arg.ty.ty.clone()),
+        }
+    };
+    if let Some(item) = parent.downcast::<Item>() {
+        if let Item::Vars(_, ref var_type, _) = *item {
+            return Some(var_type.ty.clone());
+        }
+    } else if let Some(member) = parent.downcast::<ClassMember>() {
+        match *member {
+            ClassMember::Fields { ref var_type, .. } => return Some(var_type.ty.clone()),
+            ClassMember::Constructor { ref sig, .. } | ClassMember::Redirect { ref sig, .. } => {
+                return in_sig(sig);
+            }
+            _ => {}
+        }
+    } else if let Some(function) = parent.downcast::<Function>() {
+        return in_sig(&function.sig);
+    } else if let Some(statement) = parent.downcast::<Statement>() {
+        match *statement {
+            Statement::Vars(ref var_type, _) |
+            Statement::For(_, ForLoop::InVar(ref var_type, ..), _) => {
+                return Some(var_type.ty.clone());
+            }
+            _ => {}
+        }
+    } else if let Some(expr) = parent.downcast::<Expr>() {
+        if let Expr::Closure(ref sig, _) = *expr {
+            return in_sig(sig);
+        }
+    } else if let Some(pattern) = parent.downcast::<Pattern>() {
+        if let Pattern::Var(ref var_type, _) = *pattern {
+            return Some(var_type.ty.clone());
+        }
+    }
+    None
+}
+
+/// Returns the type of `this` where `node` is.
+fn this_type<T: 'static>(node: &Node<T>) -> Option<StaticType> {
+    Some(StaticType {
+        class: enclosing_class(node)?,
+        args: vec![],
+    })
+}
+
+/// Returns the type the field `name` of `class` is declared with.
+fn field_type(class: &Item, name: Symbol) -> Option<Node<Type>> {
+    let members = match *class {
+        Item::Class { ref members, .. } => members,
+        _ => return None,
+    };
+    members.iter().filter_map(|member| match **member {
+        ClassMember::Fields {
+            ref var_type,
+            ref initializers,
+            ..
+        } if initializers.iter().any(|var| var.name == name) => Some(var_type.ty.clone()),
+        _ => None,
+    }).next()
+}
+
+/// Returns the static type of `expr`, from the declared types of what it
+/// refers to, without inferring any types beyond those of initializers.
+pub fn expr_type(expr: &Node<Expr>) -> Option<StaticType> {
+    expr_type_at(expr, 0)
+}
+
+fn expr_type_at(expr: &Node<Expr>, depth: usize) -> Option<StaticType> {
+    if depth >= MAX_TYPE_DEPTH {
+        return None;
+    }
+    let depth = depth + 1;
+    match **expr {
+        Expr::Comments(_, ref expr) | Expr::Paren(ref expr) | Expr::Cascade(ref expr, _) => {
+            expr_type_at(expr, depth)
+        }
+        Expr::As(_, ref ty) => eval_type(ty, &vec![]),
+        Expr::Identifier(_) => match expr.res().get()? {
+            Res::This => this_type(expr),
+            Res::Super => {
+                let class = enclosing_class(expr)?;
+                let superclass = match *class {
+                    Item::Class { ref superclass, .. } => superclass.clone()?,
+                    _ => return None,
+                };
+                eval_qualified(&superclass, &vec![])
+            }
+            // Members accessed without `this.` are still accessed on it.
+            res => res_type(&res, this_type(expr).as_ref(), false, depth),
+        },
+        Expr::Suffix(ref target, Suffix::Field(_)) |
+        Expr::Suffix(ref target, Suffix::FieldIfNotNull(_)) => {
+            res_type(&expr.res().get()?, expr_type_at(target, depth).as_ref(), false, depth)
+        }
+        Expr::Suffix(ref callee, Suffix::Call(..)) => {
+            let receiver = match **callee {
+                Expr::Suffix(ref target, Suffix::Field(_)) |
+                Expr::Suffix(ref target, Suffix::FieldIfNotNull(_)) => {
+                    expr_type_at(target, depth)
+                }
+                Expr::Identifier(_) => this_type(callee),
+                _ => None,
+            };
+            res_type(&callee.res().get()?, receiver.as_ref(), true, depth)
+        }
+        Expr::Suffix(ref target, Suffix::Index(_)) => {
+            let target = expr_type_at(target, depth)?;
+            let index = target.lookup_member(&ScopeChain::new(None), Symbol::intern("[]"));
+            res_type(&index, Some(&target), true, depth)
+        }
+        Expr::New { ref path, .. } => match qualified_res(path)? {
+            Res::Class(class) => Some(StaticType {
+                class,
+                args: path.params.iter().map(|param| eval_type(param, &vec![])).collect(),
+            }),
+            Res::Constructor(constructor) => {
+                res_type(&Res::Constructor(constructor), None, true, depth)
+            }
+            _ => None,
+        },
+        Expr::Int(_) => core_type("int", vec![]),
+        Expr::Double(_) => core_type("double", vec![]),
+        Expr::String(_) => core_type("String", vec![]),
+        Expr::List { ref element_ty, .. } => core_type(
+            "List",
+            vec![element_ty.as_ref().and_then(|ty| eval_type(ty, &vec![]))],
+        ),
+        _ => None,
+    }
+}
+
+/// How the arguments of a call or instance creation bind to the parameters
+/// of the function, constructor or component called.
+#[derive(Clone, Debug)]
+pub struct CallBinding {
+    /// The function, constructor or component called, or the class for the
+    /// implicit constructor of classes that don't declare any.
+    pub callee: Res,
+    /// The parameter each positional argument binds to, `None` if extra.
+    /// Parameters are `Res::Var`s, or `Res::Dsl(Field)`s for components.
+    pub unnamed: Vec<Option<Res>>,
+    /// The parameter each named argument binds to, `None` if extra.
+    pub named: Vec<Option<Res>>,
+    /// The required parameters no argument binds to.
+    pub missing: Vec<Res>,
+}
 
-    if module != Module::load(&sdk::resolve_import("dart:core")) {
-        collector.import(None, "dart:core", &[], None);
+node_field!(binding: CallBinding);
+
+/// A parameter arguments can bind to.
+pub struct Param {
+    pub name: Symbol,
+    pub res: Res,
+    pub required: bool,
+}
+
+impl CallBinding {
+    /// Binds `unnamed` positional arguments to `positional`, and arguments
+    /// named `named` to `named_params`.
+    pub fn bind(
+        callee: Res,
+        positional: &[Param],
+        named_params: &[Param],
+        unnamed: usize,
+        named: &[Symbol],
+    ) -> CallBinding {
+        let missing = positional
+            .iter()
+            .skip(unnamed)
+            .chain(named_params.iter().filter(|param| !named.contains(&param.name)))
+            .filter(|param| param.required)
+            .map(|param| param.res.clone())
+            .collect();
+        CallBinding {
+            callee,
+            unnamed: (0..unnamed)
+                .map(|i| positional.get(i).map(|param| param.res.clone()))
+                .collect(),
+            named: named
+                .iter()
+                .map(|&name| {
+                    let param = named_params.iter().find(|param| param.name == name);
+                    param.map(|param| param.res.clone())
+                })
+                .collect(),
+            missing,
+        }
     }
 
-    module.super_visit(collector);
+    /// Binds arguments to the parameters of `sig`, like `bind`.
+    fn bind_sig(callee: Res, sig: &FnSig, unnamed: usize, named: &[Symbol]) -> CallBinding {
+        let param = |arg: &ArgDef, required: bool| Param {
+            name: arg.var.name,
+            res: Res::Var(arg.var.clone()),
+            required,
+        };
+        let mut positional: Vec<_> = sig.required.iter().map(|arg| param(arg, true)).collect();
+        let mut named_params = vec![];
+        match sig.optional_kind {
+            OptionalArgKind::Positional => {
+                positional.extend(sig.optional.iter().map(|arg| param(arg, false)));
+            }
+            OptionalArgKind::Named => {
+                named_params.extend(sig.optional.iter().map(|arg| param(arg, arg.is_required())));
+            }
+        }
+        CallBinding::bind(callee, &positional, &named_params, unnamed, named)
+    }
+}
+
+/// Returns the unnamed constructor of `class`, if it declares one.
+pub fn unnamed_constructor(class: &Item) -> Option<Node<ClassMember>> {
+    let members = match *class {
+        Item::Class { ref members, .. } => members,
+        _ => return None,
+    };
+    members
+        .iter()
+        .find(|member| match ***member {
+            ClassMember::Constructor { name: None, .. } |
+            ClassMember::Redirect { name: None, .. } => true,
+            _ => false,
+        })
+        .cloned()
+}
+
+/// Returns the function type `ty` is, looking through a type alias
+/// (`typedef void ValueChanged<T>(T value);`).
+pub fn function_type(ty: &Node<Type>) -> Option<Node<Type>> {
+    let alias = match **ty {
+        Type::Function(_) | Type::FunctionOld(_) => return Some(ty.clone()),
+        Type::Path(ref qualified) => match qualified_res(qualified)? {
+            Res::TypeAlias(alias) => alias,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    match *alias {
+        Item::TypeAlias { ref ty, .. } => match **ty {
+            Type::Function(_) | Type::FunctionOld(_) => Some(ty.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Binds the arguments of a call or instance creation to the parameters of
+/// `callee`, like `bind_call`.
+fn bind_args(callee: Res, args: &Args) -> Option<CallBinding> {
+    let named: Vec<_> = args.named.iter().map(|arg| arg.name).collect();
+    bind_call(callee, args.unnamed.len(), &named)
+}
+
+/// Binds `unnamed` positional arguments and arguments named `named` to the
+/// parameters of `callee`, as long as it's something with a known signature
+/// that can be called.
+pub fn bind_call(callee: Res, unnamed: usize, named: &[Symbol]) -> Option<CallBinding> {
+    let callee = match callee {
+        Res::Class(class) => match unnamed_constructor(&class) {
+            Some(constructor) => Res::Constructor(constructor),
+            None => {
+                let declares_constructors = match *class {
+                    Item::Class { ref members, .. } => members.iter().any(|member| match **member {
+                        ClassMember::Constructor { .. } | ClassMember::Redirect { .. } => true,
+                        _ => false,
+                    }),
+                    _ => true,
+                };
+                if declares_constructors {
+                    return None;
+                }
+                return Some(CallBinding::bind(Res::Class(class), &[], &[], unnamed, named));
+            }
+        },
+        Res::Dsl(dsl::resolve::Res::Component(component)) => {
+            return dsl::resolve::bind_component(&component, unnamed, named);
+        }
+        callee => callee,
+    };
+    let binding = match callee {
+        Res::Function(ref function) => {
+            CallBinding::bind_sig(callee.clone(), &function.sig, unnamed, named)
+        }
+        Res::Constructor(ref constructor) => match **constructor {
+            ClassMember::Constructor { ref sig, .. } | ClassMember::Redirect { ref sig, .. } => {
+                CallBinding::bind_sig(callee.clone(), sig, unnamed, named)
+            }
+            _ => return None,
+        },
+        Res::Var(ref var) => match *function_type(&declared_type(var)?)? {
+            Type::Function(ref sig) | Type::FunctionOld(ref sig) => {
+                CallBinding::bind_sig(callee.clone(), sig, unnamed, named)
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(binding)
+}
+
+/// Resolves the names in `module`, and, if `fully_resolve`, those in the
+/// bodies of its functions too. Returns false if any name was unknown or
+/// ambiguous, which has been reported.
+pub fn resolve(module: Node<Module>, fully_resolve: bool) -> bool {
+    module.resolved().set(true);
+    let collector = &mut Collector::new();
+
+    collector.library(&module);
     if collector.has_error {
-        return;
+        return false;
     }
 
     module.super_visit(&mut TopLevelResolver { collector });
     if fully_resolve {
         module.super_visit(&mut Resolver { collector });
     }
+    !collector.has_error
+}
+
+/// Returns the canonical path of the module `uri` refers to, when imported
+/// or exported from the file at `importer`.
+pub fn import_path(importer: Option<&Path>, uri: &str) -> PathBuf {
+    let mut path = sdk::resolve_import(uri);
+    if path.is_relative() {
+        if let Some(dir) = importer.and_then(|importer| importer.parent()) {
+            path = dir.join(&path);
+        }
+    }
+    path.canonicalize().unwrap_or(path)
+}
+
+/// Returns the path of the file `node` was parsed from, be it a Dart
+/// module or a `.lyk` one.
+pub fn module_path<T: 'static>(node: &Node<T>) -> Option<PathBuf> {
+    let root = node.root();
+    if let Some(module) = root.downcast::<Module>() {
+        return Some(module.path.clone());
+    }
+    root.downcast::<dsl::ast::Module>().map(|module| module.path.clone())
+}
+
+/// Returns the URI an import or export refers to on the target platform:
+/// that of the first configuration whose condition holds, otherwise `uri`.
+pub fn configured_uri(uri: &StringLiteral, configs: &[ImportConfig]) -> String {
+    for config in configs {
+        let name = config
+            .name
+            .iter()
+            .map(|segment| segment.as_str().to_string())
+            .collect::<Vec<_>>()
+            .join(".");
+        let value = config
+            .value
+            .as_ref()
+            .map_or("true".to_string(), |value| value.get_simple_string());
+        if sdk::PLATFORM.with(|p| p.declared(&name)) == Some(&value[..]) {
+            return config.uri.get_simple_string();
+        }
+    }
+    uri.get_simple_string()
+}
+
+/// Returns the library `module` is a part of, if it's a part.
+fn owning_library(module: &Node<Module>) -> Option<Node<Module>> {
+    let library = module
+        .items
+        .iter()
+        .filter_map(|item| match **item {
+            Item::PartOf { ref library, .. } => Some(library),
+            _ => None,
+        })
+        .next()?;
+    let library = match *library {
+        LibraryRef::Uri(ref uri) => {
+            Module::load_exports(&import_path(Some(&module.path), &uri.get_simple_string()))
+        }
+        LibraryRef::Name(ref name) => find_library(module, name)?,
+    };
+    // A library that is itself a part would have us go around in circles.
+    let is_part = library.items.iter().any(|item| match **item {
+        Item::PartOf { .. } => true,
+        _ => false,
+    });
+    if is_part {
+        return None;
+    }
+    Some(library)
+}
+
+/// How many directories `find_library` looks through, starting with the
+/// part's own, when it doesn't reach the root of a package first.
+const LIBRARY_SEARCH_DEPTH: usize = 8;
+
+/// Finds the library named `name` which has `part` as one of its parts,
+/// among the Dart files next to it and in the directories above, up to
+/// the root of its package.
+fn find_library(part: &Node<Module>, name: &[Symbol]) -> Option<Node<Module>> {
+    let mut dir = part.path.parent();
+    for _ in 0..LIBRARY_SEARCH_DEPTH {
+        let current = dir?;
+        let entries = match fs::read_dir(current) {
+            Ok(entries) => entries,
+            Err(_) => {
+                dir = current.parent();
+                continue;
+            }
+        };
+        for entry in entries {
+            let path = match entry {
+                Ok(entry) => entry.path(),
+                Err(_) => continue,
+            };
+            if path == part.path || !path.extension().map_or(false, |x| x == "dart") {
+                continue;
+            }
+            // Avoid parsing files that can't be named libraries.
+            let mut text = String::new();
+            let read = File::open(&path).and_then(|mut f| f.read_to_string(&mut text));
+            if read.is_err() || !text.contains("library") {
+                continue;
+            }
+            let library = Module::load_exports(&path);
+            let named = library.items.iter().any(|item| match **item {
+                Item::LibraryName { path: ref library_name, .. } => library_name[..] == name[..],
+                _ => false,
+            });
+            let owns_part = library.items.iter().any(|item| match **item {
+                Item::Part { ref module, .. } => module.path == part.path,
+                _ => false,
+            });
+            if named && owns_part {
+                return Some(library);
+            }
+        }
+        if current.join("pubspec.yaml").exists() {
+            break;
+        }
+        dir = current.parent();
+    }
+    None
 }
 
 pub struct Collector {
@@ -181,50 +1046,82 @@ impl Collector {
     }
 
     pub fn record<S: ::IntoSymbol>(&mut self, name: S, res: Res) {
-        self.scope_mut().map.insert(name.into_symbol(), res);
+        self.scope_mut().insert(name.into_symbol(), res);
+    }
+
+    /// Collects the scope of the library `module` is. A part loaded on its
+    /// own gets the scope of the library it belongs to, with its own items.
+    pub fn library(&mut self, module: &Node<Module>) {
+        match owning_library(module) {
+            Some(library) => self.library(&library),
+            None => if module.path != import_path(None, "dart:core") {
+                self.import(None, "dart:core", &[], None);
+            },
+        }
+        module.super_visit(self);
     }
 
     pub fn import(
         &mut self,
-        root_module: Option<Node<Module>>,
+        importer: Option<&Path>,
         uri: &str,
         filters: &[ImportFilter],
         alias: Option<Symbol>,
     ) {
-        let mut path = sdk::resolve_import(uri);
-        if path.is_relative() {
-            path = root_module.unwrap().path.parent().unwrap().join(&path);
-        }
-        let module = Module::load(&path);
-        if module.has_error {
-            self.has_error = true;
-        }
-        if let Some(alias) = alias {
-            self.record(alias, Res::Module(module.clone()));
-        }
-        let mut scope = module.exports();
-        for filter in filters {
-            if filter.hide {
-                for &name in &filter.names {
-                    Rc::make_mut(&mut scope).map.remove(&name);
+        let path = import_path(importer, uri);
+        let exports = match summary::load(&path) {
+            Some(exports) => exports,
+            None => {
+                let module = Module::load_exports(&path);
+                if module.has_error {
+                    self.has_error = true;
                 }
-            } else {
-                for &name in &filter.names {
-                    self.record(name, scope.lookup(name));
+                let exports = module.exports();
+                if !module.has_error {
+                    summary::store(&module, &exports);
                 }
-                return;
+                exports
+            }
+        };
+
+        // Each combinator narrows down what the previous ones left.
+        let mut namespace = ScopeChain::new(None);
+        Rc::make_mut(&mut namespace).extend(&exports);
+        for filter in filters {
+            Rc::make_mut(&mut namespace)
+                .map
+                .retain(|name, _| filter.names.contains(name) != filter.hide);
+        }
+
+        // Conflicting exports are errors, platform libraries or not.
+        let platform = uri.starts_with("dart:") && !self.exports_only;
+        match alias {
+            Some(alias) => {
+                let mut prefix = match self.scope.map.get(&alias) {
+                    Some(&Res::Prefix(ref prefix)) => prefix.clone(),
+                    _ => ScopeChain::new(None),
+                };
+                Rc::make_mut(&mut prefix).import(&namespace, platform);
+                self.record(alias, Res::Prefix(prefix));
             }
+            None => self.scope_mut().import(&namespace, platform),
         }
-        self.scope_mut().extend(&scope);
     }
 }
 
 impl Visitor for Collector {
     fn dart_item(&mut self, item: Node<Item>) {
         match *item {
-            Item::Class { name, .. } | Item::MixinClass { name, .. } => {
+            Item::Class { name, .. } | Item::MixinClass { name, .. } | Item::Mixin { name, .. } => {
                 self.record(name, Res::Class(item.clone()));
             }
+            Item::Extension { name, .. } => match name {
+                Some(name) => self.record(name, Res::Extension(item.clone())),
+                // Unnamed extensions only apply in their own library.
+                None => if !self.exports_only {
+                    self.record(Symbol::gensym("extension"), Res::Extension(item.clone()));
+                },
+            },
             Item::Enum { name, .. } => {
                 self.record(name, Res::Enum(item.clone()));
             }
@@ -240,13 +1137,20 @@ impl Visitor for Collector {
             Item::Part { ref module, .. } => {
                 module.super_visit(self);
             }
-            Item::Export(_, ref uri, ref filters) => if self.exports_only {
-                self.import(item.root_module(), &uri.get_simple_string(), filters, None);
+            Item::Export(_, ref uri, ref configs, ref filters) => if self.exports_only {
+                let importer = module_path(&item);
+                self.import(
+                    importer.as_ref().map(PathBuf::as_path),
+                    &configured_uri(uri, configs),
+                    filters,
+                    None,
+                );
             },
             Item::Import(_, ref import) => if !import.deferred && !self.exports_only {
+                let importer = module_path(&item);
                 self.import(
-                    item.root_module(),
-                    &import.uri.get_simple_string(),
+                    importer.as_ref().map(PathBuf::as_path),
+                    &configured_uri(&import.uri, &import.configs),
                     &import.filters,
                     import.alias,
                 );
@@ -286,6 +1190,10 @@ impl Visitor for Collector {
             FnName::Setter(name) => {
                 self.record(name, Res::Setter(function.clone()));
             }
+            // Under a name no identifier can clash with, for `expr_type`.
+            FnName::Operator(OverloadedOp::Index) => {
+                self.record("[]", Res::Function(function.clone()));
+            }
             _ => {}
         }
     }
@@ -310,7 +1218,7 @@ impl<'a> Visitor for TopLevelResolver<'a> {
 
         let res = if let Some(ref prefix) = qualified.prefix {
             prefix.visit(self);
-            if let Some(prefix_res @ Res::Module(_)) = prefix.res().get() {
+            if let Some(prefix_res @ Res::Prefix(_)) = prefix.res().get() {
                 prefix_res.lookup_member(qualified.name)
             } else {
                 Res::Error
@@ -319,7 +1227,8 @@ impl<'a> Visitor for TopLevelResolver<'a> {
             self.collector.scope.lookup(qualified.name)
         };
 
-        if let Res::Error = res {
+        // Left for `Resolver` to report, if this is in a body it resolves.
+        if let Res::Error | Res::Ambiguous(_) = res {
             return;
         }
         qualified.res().set(res);
@@ -331,6 +1240,40 @@ pub struct Resolver<'a> {
 }
 
 impl<'a> Resolver<'a> {
+    /// Resolves `name` as a member of what `target` evaluates to: a static
+    /// member of a class, a name imported through a prefix, or else a
+    /// member of the static type of `target`, if that's known.
+    fn member(&mut self, target: &Node<Expr>, name: Symbol) -> Option<Res> {
+        let res = match target.res().get() {
+            Some(target_res @ Res::Class(_)) |
+            Some(target_res @ Res::Enum(_)) |
+            Some(target_res @ Res::Prefix(_)) => {
+                let res = self.collector.scope.lookup_member(&target_res, name);
+                if let Some(problem) = self.problem(&res) {
+                    println!("{} member {}", problem, name);
+                }
+                return Some(res);
+            }
+            _ => expr_type(target)?.lookup_member(&self.collector.scope, name),
+        };
+        match res {
+            Res::Error => None,
+            res => Some(res),
+        }
+    }
+
+    /// Describes what's wrong with `res`, if a name resolved to nothing or
+    /// to several declarations, and marks resolving as failed.
+    fn problem(&mut self, res: &Res) -> Option<&'static str> {
+        let problem = match *res {
+            Res::Error => "unknown",
+            Res::Ambiguous(_) => "ambiguous",
+            _ => return None,
+        };
+        self.collector.has_error = true;
+        Some(problem)
+    }
+
     pub fn in_lexical_scope<F: FnOnce(&mut Self) -> R, R>(&mut self, f: F) -> R {
         let parent = self.collector.scope.clone();
         self.collector.scope = ScopeChain::new(Some(parent.clone()));
@@ -343,18 +1286,29 @@ impl<'a> Resolver<'a> {
 impl<'a> Visitor for Resolver<'a> {
     fn dart_item(&mut self, item: Node<Item>) {
         self.in_lexical_scope(|this| {
-            if let Item::Class {
-                ref superclass,
-                ref mixins,
-                ref interfaces,
-                ..
-            } = *item
-            {
+            if let Item::Extension { ref on, .. } = *item {
+                this.collector.record("this", Res::This);
+                on.visit(&mut TopLevelResolver {
+                    collector: this.collector,
+                });
+                if let Some(class) = extension_target(&item) {
+                    this.collector.scope_mut().extend(&class.exports());
+                }
+                item.super_visit(this.collector);
+            }
+
+            let supertypes = match *item {
+                Item::Class { .. } | Item::Mixin { .. } | Item::Enum { .. } => {
+                    Some(supertypes(&item))
+                }
+                _ => None,
+            };
+            if let Some(supertypes) = supertypes {
                 this.collector.record("this", Res::This);
                 this.collector.record("super", Res::Super);
                 this.collector.record("runtimeType", Res::RuntimeType);
 
-                for class in superclass.iter().chain(mixins).chain(interfaces) {
+                for class in supertypes {
                     class.visit(&mut TopLevelResolver {
                         collector: this.collector,
                     });
@@ -377,6 +1331,9 @@ impl<'a> Visitor for Resolver<'a> {
     fn dart_try_part(&mut self, try_part: &TryPart) {
         self.in_lexical_scope(|this| try_part.super_visit(this));
     }
+    fn dart_switch_expr_case(&mut self, case: &SwitchExprCase) {
+        self.in_lexical_scope(|this| case.super_visit(this));
+    }
     fn dart_statement(&mut self, statement: Node<Statement>) {
         if let Statement::For(_, ForLoop::InVar(..), _) = *statement {
             self.in_lexical_scope(|this| statement.super_visit(this));
@@ -398,12 +1355,32 @@ impl<'a> Visitor for Resolver<'a> {
 
         if let Expr::Identifier(name) = *expr {
             let res = self.collector.scope.lookup(name);
-            if let Res::Error = res {
-                println!("unknown value {}", name);
+            if let Some(problem) = self.problem(&res) {
+                println!("{} value {}", problem, name);
             }
             expr.res().set(res);
         }
-        expr.super_visit(self)
+        expr.super_visit(self);
+
+        match *expr {
+            Expr::Suffix(ref target, Suffix::Field(name)) |
+            Expr::Suffix(ref target, Suffix::FieldIfNotNull(name)) => {
+                if let Some(res) = self.member(target, name) {
+                    expr.res().set(res);
+                }
+            }
+            Expr::New { ref path, ref args, .. } => {
+                if let Some(binding) = path.res().get().and_then(|res| bind_args(res, args)) {
+                    expr.binding().set(binding);
+                }
+            }
+            Expr::Suffix(ref callee, Suffix::Call(_, ref args)) => {
+                if let Some(binding) = callee.res().get().and_then(|res| bind_args(res, args)) {
+                    expr.binding().set(binding);
+                }
+            }
+            _ => {}
+        }
     }
     fn dart_qualified(&mut self, qualified: Node<Qualified>) {
         for ty in &qualified.params {
@@ -421,8 +1398,8 @@ impl<'a> Visitor for Resolver<'a> {
             self.collector.scope.lookup(qualified.name)
         };
 
-        if let Res::Error = res {
-            println!("unknown path {:?}", qualified);
+        if let Some(problem) = self.problem(&res) {
+            println!("{} path {:?}", problem, qualified);
         }
         qualified.res().set(res);
     }
@@ -1,4 +1,5 @@
 use git2::{Oid, Repository};
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::env;
 use std::io::prelude::*;
@@ -10,7 +11,8 @@ use url::Url;
 const FLUTTER_REPO: &str = "https://github.com/lykenware/flutter";
 const FLUTTER_REPO_REV: &str = "e16e4024164756de13d7ca1b3ffad385229b840b";
 
-thread_local!(static FLUTTER_PATH: PathBuf = {
+/// Returns the directory lyken keeps its caches in, creating it if needed.
+pub fn cache_dir() -> PathBuf {
     #[cfg(windows)]
     let cache_dir = PathBuf::from(env::var_os("APPDATA").unwrap()).join("Lyken/cache");
 
@@ -19,6 +21,11 @@ thread_local!(static FLUTTER_PATH: PathBuf = {
         .create_cache_directory("").unwrap();
 
     fs::create_dir_all(&cache_dir).unwrap();
+    cache_dir
+}
+
+thread_local!(static FLUTTER_PATH: PathBuf = {
+    let cache_dir = cache_dir();
     let flutter_dir = cache_dir.join("flutter");
     if !flutter_dir.exists() {
         let flutter_tmp_dir = cache_dir.join("flutter-tmp");
@@ -60,6 +67,52 @@ pub fn with_cmd<F: FnOnce(&mut Command) -> R, R>(f: F) -> R {
     })
 }
 
+/// A platform Dart code can run on, which decides the `dart:` libraries
+/// available, and with them the URIs configurable imports pick.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Target {
+    /// The Dart VM, e.g. Flutter apps on mobile (`dart.library.io`).
+    Native,
+    /// Browsers (`dart.library.html`).
+    Web,
+}
+
+impl Target {
+    pub fn from_name(name: &str) -> Option<Target> {
+        match name {
+            "native" => Some(Target::Native),
+            "web" => Some(Target::Web),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Target::Native => "native",
+            Target::Web => "web",
+        }
+    }
+
+    fn platform_file(self) -> &'static str {
+        match self {
+            Target::Native => "dart_server.platform",
+            Target::Web => "dart_client.platform",
+        }
+    }
+}
+
+thread_local!(static TARGET: Cell<Target> = Cell::new(Target::Native));
+
+/// Selects the platform imports are resolved for, which has to happen
+/// before anything is resolved, as `PLATFORM` is only loaded once.
+pub fn set_target(target: Target) {
+    TARGET.with(|t| t.set(target));
+}
+
+pub fn target() -> Target {
+    TARGET.with(|t| t.get())
+}
+
 thread_local!(pub static PLATFORM: Platform = Platform::load());
 
 pub struct Platform {
@@ -77,7 +130,7 @@ impl Platform {
                 }
             });
         }
-        let mut f = File::open(lib_path.join("dart_server.platform")).unwrap();
+        let mut f = File::open(lib_path.join(target().platform_file())).unwrap();
         let mut text = String::new();
         f.read_to_string(&mut text).unwrap();
         let mut platform = Platform {
@@ -110,6 +163,20 @@ impl Platform {
         );
         platform
     }
+
+    /// Returns the value of the environment variable `name`, as tested by
+    /// configurable imports. Only `dart.library.*` variables are declared,
+    /// as `"true"` for the libraries available on the target platform.
+    pub fn declared(&self, name: &str) -> Option<&'static str> {
+        if !name.starts_with("dart.library.") {
+            return None;
+        }
+        if self.libraries.contains_key(&name["dart.library.".len()..]) {
+            Some("true")
+        } else {
+            Some("false")
+        }
+    }
 }
 
 thread_local!(pub static FLUTTER_PACKAGES: Packages = Packages::load(Path::new(".")));
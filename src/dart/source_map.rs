@@ -0,0 +1,154 @@
+use std::fmt::Write;
+use Span;
+
+/// A position in printed code (both zero-based) and the source span it came from.
+#[derive(Copy, Clone, Debug)]
+pub struct Mapping {
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+}
+
+/// Mappings from printed code back to source spans, in output order.
+#[derive(Default, Debug)]
+pub struct SourceMap {
+    pub mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    /// Returns the source span of the closest mapping at or before the
+    /// given (zero-based) position, on the same line.
+    pub fn lookup(&self, line: usize, column: usize) -> Option<Span> {
+        self.mappings
+            .iter()
+            .take_while(|m| (m.line, m.column) <= (line, column))
+            .filter(|m| m.line == line)
+            .last()
+            .or_else(|| self.mappings.iter().find(|m| m.line == line))
+            .map(|m| m.span)
+    }
+
+    /// Replaces `file:line:column` locations in `text` that refer to the
+    /// printed code with the source locations they map to.
+    pub fn rewrite_locations(&self, text: &str, file: &str) -> String {
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(i) = rest.find(file) {
+            let (before, after) = rest.split_at(i + file.len());
+            result.push_str(before);
+            rest = after;
+            let location: Option<(Span, usize)> = do catch {
+                let mut numbers = rest.splitn(3, ':').skip(1);
+                let line = numbers.next()?;
+                let column = numbers.next()?;
+                let column = &column[..column.find(|c: char| !c.is_digit(10))
+                    .unwrap_or(column.len())];
+                let line_number = line.parse::<usize>().ok()?;
+                let column_number = column.parse::<usize>().ok()?;
+                let span = self.lookup(line_number.checked_sub(1)?, column_number.checked_sub(1)?)?;
+                (span, 1 + line.len() + 1 + column.len())
+            };
+            if let Some((span, len)) = location {
+                let loc = ::codemap().lookup_char_pos(span.lo);
+                // Replace the whole path or URI ending in `file`, already pushed.
+                let start = result.len() - file.len();
+                result.truncate(start);
+                while let Some(c) = result.chars().last() {
+                    if c.is_whitespace() || "('\"".contains(c) {
+                        break;
+                    }
+                    result.pop();
+                }
+                write!(result, "{}:{}:{}", loc.file.name, loc.line, loc.col.0 + 1).unwrap();
+                rest = &rest[len..];
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    /// Serializes the mappings in the Source Map v3 format.
+    pub fn to_json(&self, file: &str) -> String {
+        let codemap = ::codemap();
+        let mut sources: Vec<String> = vec![];
+        let mut mappings = String::new();
+        let (mut line, mut column) = (0, 0);
+        let (mut source, mut source_line, mut source_column) = (0, 0, 0);
+        for mapping in &self.mappings {
+            if mapping.line != line {
+                for _ in line..mapping.line {
+                    mappings.push(';');
+                }
+                line = mapping.line;
+                column = 0;
+            } else if !mappings.is_empty() && !mappings.ends_with(';') {
+                mappings.push(',');
+            }
+            let loc = codemap.lookup_char_pos(mapping.span.lo);
+            let name = loc.file.name.to_string();
+            let index = match sources.iter().position(|s| *s == name) {
+                Some(index) => index,
+                None => {
+                    sources.push(name);
+                    sources.len() - 1
+                }
+            };
+            vlq(mapping.column as i64 - column as i64, &mut mappings);
+            vlq(index as i64 - source as i64, &mut mappings);
+            vlq(loc.line as i64 - 1 - source_line as i64, &mut mappings);
+            vlq(loc.col.0 as i64 - source_column as i64, &mut mappings);
+            column = mapping.column;
+            source = index;
+            source_line = loc.line - 1;
+            source_column = loc.col.0;
+        }
+
+        let sources = sources
+            .iter()
+            .map(|s| json_string(s))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "{{\"version\":3,\"file\":{},\"sources\":[{}],\"names\":[],\"mappings\":{}}}\n",
+            json_string(file),
+            sources,
+            json_string(&mappings)
+        )
+    }
+}
+
+/// Appends `value` as a base64 VLQ, as used by source map segments.
+fn vlq(value: i64, out: &mut String) {
+    const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut value = if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    };
+    loop {
+        let mut digit = value & 0b11111;
+        value >>= 5;
+        if value != 0 {
+            digit |= 0b100000;
+        }
+        out.push(BASE64[digit as usize] as char);
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(result, "\\u{:04x}", c as u32).unwrap(),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
@@ -0,0 +1,170 @@
+use dart::ast::{Item, Module};
+use dart::resolve::{self, Res, ScopeChain};
+use dart::sdk;
+use node::Node;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write as FmtWrite;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::rc::Rc;
+use std::time::UNIX_EPOCH;
+use syntax::symbol::Symbol;
+
+/// Changes whenever the summary format or what goes into it does.
+const VERSION: &str = "lyken-summary 2";
+
+thread_local!(static DIR: RefCell<Option<PathBuf>> = RefCell::new(None));
+
+/// Makes this thread keep summaries in `dir` instead of lyken's cache, so
+/// resolving can be measured without any summaries to start from.
+pub fn set_dir(dir: PathBuf) {
+    DIR.with(|d| *d.borrow_mut() = Some(dir));
+}
+
+/// Returns the exports of the module at `path` recorded by `store`, as long
+/// as none of the files they were collected from changed since.
+///
+/// Exports come back as `Res::Unloaded`, so the modules defining them are
+/// only parsed once something is actually looked up. Extensions are the
+/// exception, as they apply to their target type without being looked up.
+pub fn load(path: &Path) -> Option<Rc<ScopeChain>> {
+    let text = read(path)?;
+    let mut exports = ScopeChain::new(None);
+    for line in text.lines().skip(2) {
+        let mut fields = line.splitn(3, ' ');
+        match (fields.next()?, fields.next()?, fields.next()?) {
+            ("dep", stamp, dep) => if modified(Path::new(dep))? != stamp {
+                return None;
+            },
+            (kind @ "export", name, origin) | (kind @ "extension", name, origin) => {
+                let mut res = Res::Unloaded(Rc::new(PathBuf::from(origin)), Symbol::intern(name));
+                if kind == "extension" {
+                    res = res.load();
+                }
+                Rc::make_mut(&mut exports).insert(Symbol::intern(name), res);
+            }
+            _ => return None,
+        }
+    }
+    Some(exports)
+}
+
+/// Records `exports`, the exports of `module`, for `load`.
+///
+/// Nothing is recorded if any export can't be loaded back by name, or if a
+/// module `module` re-exports has no summary of its own.
+pub fn store(module: &Node<Module>, exports: &ScopeChain) {
+    let _: Option<()> = do catch {
+        let mut text = format!("{}\n{}\n", VERSION, module.path.to_str()?);
+        for dep in deps(module)? {
+            writeln!(text, "dep {} {}", modified(&dep)?, dep.to_str()?).ok()?;
+        }
+        for (name, res) in exports.local_entries()? {
+            let kind = match *res {
+                Res::Extension(_) => "extension",
+                _ => "export",
+            };
+            writeln!(text, "{} {} {}", kind, name, origin(res)?.to_str()?).ok()?;
+        }
+        // Written next to its final path and renamed into place, so that
+        // `read` never sees a summary a crashed or concurrent run left
+        // half-written.
+        let path = summary_path(&module.path)?;
+        let temp_path = path.with_extension(format!("{}.tmp", process::id()));
+        let written = File::create(&temp_path).and_then(|mut file| file.write_all(text.as_bytes()));
+        if written.and_then(|_| fs::rename(&temp_path, &path)).is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+    };
+}
+
+/// Returns the summary of the module at `path`, if it's in the current format.
+fn read(path: &Path) -> Option<String> {
+    let mut text = String::new();
+    File::open(summary_path(path)?)
+        .ok()?
+        .read_to_string(&mut text)
+        .ok()?;
+    {
+        let mut lines = text.lines();
+        if lines.next()? != VERSION || lines.next()? != path.to_str()? {
+            return None;
+        }
+    }
+    Some(text)
+}
+
+/// Returns the files the exports of `module` depend on: its own files, and
+/// those of the modules it re-exports.
+fn deps(module: &Node<Module>) -> Option<Vec<PathBuf>> {
+    let mut deps = vec![module.path.clone()];
+    let parts = module.items.iter().filter_map(|item| match **item {
+        Item::Part { ref module, .. } => Some(module),
+        _ => None,
+    });
+    for part in Some(module).into_iter().chain(parts) {
+        if part != module {
+            deps.push(part.path.clone());
+        }
+        for item in &part.items {
+            if let Item::Export(_, ref uri, ref configs, _) = **item {
+                let uri = resolve::configured_uri(uri, configs);
+                let path = resolve::import_path(Some(&module.path), &uri);
+                let text = read(&path)?;
+                for line in text.lines().skip(2) {
+                    let mut fields = line.splitn(3, ' ');
+                    if let (Some("dep"), Some(_), Some(dep)) =
+                        (fields.next(), fields.next(), fields.next())
+                    {
+                        deps.push(PathBuf::from(dep));
+                    }
+                }
+            }
+        }
+    }
+    deps.sort();
+    deps.dedup();
+    Some(deps)
+}
+
+/// Returns the path of the module `res` is defined in.
+fn origin(res: &Res) -> Option<PathBuf> {
+    let module = match *res {
+        Res::Class(ref item) |
+        Res::Enum(ref item) |
+        Res::TypeAlias(ref item) |
+        Res::Extension(ref item) => item.root_module(),
+        Res::Var(ref var) => var.root_module(),
+        Res::Function(ref function) |
+        Res::Getter(ref function) |
+        Res::Setter(ref function) |
+        Res::GetterSetter(ref function, _) => function.root_module(),
+        Res::Unloaded(ref path, _) => return Some(PathBuf::clone(path)),
+        _ => None,
+    };
+    Some(module?.path.clone())
+}
+
+/// Returns a stamp that changes whenever the file at `path` is modified.
+fn modified(path: &Path) -> Option<String> {
+    let time = fs::metadata(path).ok()?.modified().ok()?;
+    let time = time.duration_since(UNIX_EPOCH).ok()?;
+    Some(format!("{}.{:09}", time.as_secs(), time.subsec_nanos()))
+}
+
+/// Returns where the summary of the module at `path` is kept, or `None` if
+/// the directory for it can't be created, in which case there's no summary.
+fn summary_path(path: &Path) -> Option<PathBuf> {
+    // Configurable imports make exports depend on the target platform.
+    let dir = DIR.with(|d| d.borrow().clone())
+        .unwrap_or_else(|| sdk::cache_dir().join("summaries"))
+        .join(sdk::target().name());
+    fs::create_dir_all(&dir).ok()?;
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    Some(dir.join(format!("{:016x}", hasher.finish())))
+}
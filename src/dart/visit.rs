@@ -1,6 +1,7 @@
-use dart::ast::{Args, ClassMember, ConstructorInitializer, Expr, FnBody, FnSig, ForLoop, Function,
-                Item, Meta, MetaItem, Module, Qualified, Statement, StringLiteral, Suffix,
-                TryPart, Type, TypeParameter, VarDef};
+use dart::ast::{Args, ClassMember, ConstructorInitializer, EnumValue, Expr, FnBody, FnSig, ForLoop,
+                Function, Item, LibraryRef, Meta, MetaItem, Module, Pattern, PatternField,
+                Qualified, Statement, StringLiteral, Suffix, SwitchExprCase, TryPart, Type,
+                TypeParameter, VarDef};
 use node::Node;
 
 pub trait Visitor: Sized {
@@ -13,6 +14,9 @@ pub trait Visitor: Sized {
     fn dart_item(&mut self, item: Node<Item>) {
         item.super_visit(self)
     }
+    fn dart_enum_value(&mut self, value: &EnumValue) {
+        value.super_visit(self)
+    }
     fn dart_class_member(&mut self, class_member: Node<ClassMember>) {
         class_member.super_visit(self)
     }
@@ -58,6 +62,15 @@ pub trait Visitor: Sized {
     fn dart_args(&mut self, args: &Args) {
         args.super_visit(self)
     }
+    fn dart_switch_expr_case(&mut self, case: &SwitchExprCase) {
+        case.super_visit(self)
+    }
+    fn dart_pattern(&mut self, pattern: Node<Pattern>) {
+        pattern.super_visit(self)
+    }
+    fn dart_pattern_field(&mut self, field: &PatternField) {
+        field.super_visit(self)
+    }
     fn dart_suffix(&mut self, suffix: &Suffix) {
         suffix.super_visit(self)
     }
@@ -103,16 +116,37 @@ impl VisitNode for Item {
     }
     fn super_visit<V: Visitor>(item: Node<Self>, visitor: &mut V) {
         match *item {
-            Item::LibraryName { ref meta, .. } | Item::PartOf { ref meta, .. } => {
+            Item::LibraryName { ref meta, .. } => {
+                meta.visit(visitor);
+            }
+            Item::PartOf {
+                ref meta,
+                ref library,
+            } => {
                 meta.visit(visitor);
+                if let LibraryRef::Uri(ref uri) = *library {
+                    uri.visit(visitor);
+                }
             }
             Item::Import(ref meta, ref import) => {
                 meta.visit(visitor);
                 import.uri.visit(visitor);
+                for config in &import.configs {
+                    if let Some(ref value) = config.value {
+                        value.visit(visitor);
+                    }
+                    config.uri.visit(visitor);
+                }
             }
-            Item::Export(ref meta, ref string_literal, _) => {
+            Item::Export(ref meta, ref string_literal, ref configs, _) => {
                 meta.visit(visitor);
                 string_literal.visit(visitor);
+                for config in configs {
+                    if let Some(ref value) = config.value {
+                        value.visit(visitor);
+                    }
+                    config.uri.visit(visitor);
+                }
             }
             Item::Part {
                 ref meta,
@@ -163,14 +197,56 @@ impl VisitNode for Item {
                     interface.visit(visitor);
                 }
             }
+            Item::Mixin {
+                ref meta,
+                ref generics,
+                ref constraints,
+                ref interfaces,
+                ref members,
+                ..
+            } => {
+                meta.visit(visitor);
+                generics.visit(visitor);
+                for class in constraints.iter().chain(interfaces) {
+                    class.visit(visitor);
+                }
+                for member in members {
+                    member.visit(visitor);
+                }
+            }
+            Item::Extension {
+                ref meta,
+                ref generics,
+                ref on,
+                ref members,
+                ..
+            } => {
+                meta.visit(visitor);
+                generics.visit(visitor);
+                on.visit(visitor);
+                for member in members {
+                    member.visit(visitor);
+                }
+            }
             Item::Enum {
                 ref meta,
+                ref generics,
+                ref mixins,
+                ref interfaces,
                 ref values,
+                ref members,
                 ..
             } => {
                 meta.visit(visitor);
-                for &(ref meta, _) in values {
-                    meta.visit(visitor);
+                generics.visit(visitor);
+                for class in mixins.iter().chain(interfaces) {
+                    class.visit(visitor);
+                }
+                for value in values {
+                    value.visit(visitor);
+                }
+                for member in members {
+                    member.visit(visitor);
                 }
             }
             Item::TypeAlias {
@@ -202,6 +278,21 @@ impl VisitNode for Item {
     }
 }
 
+impl Visit for EnumValue {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        visitor.dart_enum_value(self);
+    }
+    fn super_visit<V: Visitor>(&self, visitor: &mut V) {
+        self.meta.visit(visitor);
+        for ty in &self.generics {
+            ty.visit(visitor);
+        }
+        if let Some(ref args) = self.args {
+            args.visit(visitor);
+        }
+    }
+}
+
 impl VisitNode for ClassMember {
     fn visit<V: Visitor>(class_member: Node<Self>, visitor: &mut V) {
         visitor.dart_class_member(class_member);
@@ -333,6 +424,17 @@ impl VisitNode for Type {
             Type::FunctionOld(ref sig) | Type::Function(ref sig) => {
                 sig.visit(visitor);
             }
+            Type::Record {
+                ref positional,
+                ref named,
+            } => {
+                for &(ref ty, _) in positional {
+                    ty.visit(visitor);
+                }
+                for &(ref ty, _) in named {
+                    ty.visit(visitor);
+                }
+            }
             Type::Infer => {}
         }
     }
@@ -378,7 +480,7 @@ impl Visit for FnBody {
                 expr.visit(visitor);
             }
             FnBody::Block(ref statement) => {
-                statement.visit(visitor);
+                statement.filled().visit(visitor);
             }
             FnBody::Native(ref string_literal) => if let Some(ref string_literal) = *string_literal
             {
@@ -584,7 +686,7 @@ impl VisitNode for Expr {
                     v.visit(visitor);
                 }
             }
-            Expr::Number(_) => {}
+            Expr::Int(_) | Expr::Double(_) => {}
             Expr::String(ref string_literals) => for sl in string_literals {
                 sl.visit(visitor);
             },
@@ -592,6 +694,15 @@ impl VisitNode for Expr {
             Expr::Paren(ref expr) => {
                 expr.visit(visitor);
             }
+            Expr::Record(ref args) => {
+                args.visit(visitor);
+            }
+            Expr::Switch(ref expr, ref cases) => {
+                expr.visit(visitor);
+                for case in cases {
+                    case.visit(visitor);
+                }
+            }
             Expr::Throw(ref expr) => {
                 expr.visit(visitor);
             }
@@ -626,6 +737,82 @@ impl Visit for Args {
     }
 }
 
+impl Visit for SwitchExprCase {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        visitor.dart_switch_expr_case(self);
+    }
+    fn super_visit<V: Visitor>(&self, visitor: &mut V) {
+        self.pattern.visit(visitor);
+        if let Some(ref guard) = self.guard {
+            guard.visit(visitor);
+        }
+        self.body.visit(visitor);
+    }
+}
+
+impl VisitNode for Pattern {
+    fn visit<V: Visitor>(pattern: Node<Self>, visitor: &mut V) {
+        visitor.dart_pattern(pattern);
+    }
+    fn super_visit<V: Visitor>(pattern: Node<Self>, visitor: &mut V) {
+        match *pattern {
+            Pattern::Wildcard => {}
+            Pattern::Var(ref var_type, ref var) => {
+                var_type.ty.visit(visitor);
+                var.visit(visitor);
+            }
+            Pattern::Const(ref expr) | Pattern::Relational(_, ref expr) => {
+                expr.visit(visitor);
+            }
+            Pattern::Or(ref a, ref b) | Pattern::And(ref a, ref b) => {
+                a.visit(visitor);
+                b.visit(visitor);
+            }
+            Pattern::Cast(ref pattern, ref ty) => {
+                pattern.visit(visitor);
+                ty.visit(visitor);
+            }
+            Pattern::NullCheck(ref pattern) |
+            Pattern::NullAssert(ref pattern) |
+            Pattern::Paren(ref pattern) => {
+                pattern.visit(visitor);
+            }
+            Pattern::List(ref elements) => for element in elements {
+                element.visit(visitor);
+            },
+            Pattern::Rest(ref rest) => if let Some(ref rest) = *rest {
+                rest.visit(visitor);
+            },
+            Pattern::Map(ref kv) => for &(ref k, ref v) in kv {
+                k.visit(visitor);
+                v.visit(visitor);
+            },
+            Pattern::Record(ref fields) => for field in fields {
+                field.visit(visitor);
+            },
+            Pattern::Object(ref qualified, ref fields) => {
+                qualified.visit(visitor);
+                for field in fields {
+                    field.visit(visitor);
+                }
+            }
+        }
+    }
+}
+
+impl Visit for PatternField {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        visitor.dart_pattern_field(self);
+    }
+    fn super_visit<V: Visitor>(&self, visitor: &mut V) {
+        match *self {
+            PatternField::Positional(ref pattern) | PatternField::Named(_, ref pattern) => {
+                pattern.visit(visitor);
+            }
+        }
+    }
+}
+
 impl Visit for Suffix {
     fn visit<V: Visitor>(&self, visitor: &mut V) {
         visitor.dart_suffix(self);
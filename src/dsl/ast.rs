@@ -1,10 +1,22 @@
 use syntax::symbol::Symbol;
 use dart;
 use node::Node;
+use std::path::PathBuf;
+use Span;
+
+/// A `.lyk` source file.
+#[derive(Debug)]
+pub struct Module {
+    /// The file path where the module is located.
+    pub path: PathBuf,
+    /// The contents of the module.
+    pub items: Vec<Node<Item>>,
+}
 
 #[derive(Debug)]
 pub enum Item {
     ComponentDef {
+        comments: Vec<Span>,
         name: Symbol,
         fields: Vec<Node<FieldDef>>,
         dart_members: Vec<Node<dart::ast::ClassMember>>,
@@ -15,6 +27,7 @@ pub enum Item {
 
 #[derive(Debug)]
 pub struct FieldDef {
+    pub comments: Vec<Span>,
     pub mutable: bool,
     pub name: Symbol,
     pub ty: Option<Node<Type>>,
@@ -23,10 +36,20 @@ pub struct FieldDef {
 
 #[derive(Debug)]
 pub enum Config {
+    /// Comments before a config entry, or before the closing `}` if `None`.
+    Comments(Vec<Span>, Option<Box<Config>>),
     Field { name: Symbol, value: Node<Expr> },
     EventHandler {
         name: Symbol,
-        block: Node<dart::ast::Statement>,
+        /// Where `name` was parsed from, if it was.
+        name_span: Option<Span>,
+        sig: Option<dart::ast::FnSig>,
+        body: dart::ast::FnBody,
+    },
+    /// Two-way binding to a `mut` field (`value: bind _checked`).
+    Binding {
+        name: Symbol,
+        field: Node<dart::ast::Expr>,
     },
 }
 
@@ -37,6 +60,7 @@ pub enum Type {
 
 #[derive(Debug)]
 pub enum Expr {
+    Comments(Vec<Span>, Node<Expr>),
     Instance {
         path: Node<dart::ast::Qualified>,
         unnamed: Vec<Node<Expr>>,
@@ -1,5 +1,5 @@
 use dart;
-use dart::fold::Fold as DartFold;
+use dart::fold::{keep_span, Fold as DartFold};
 use dsl::ast::{Config, Expr, FieldDef, Item, Type};
 use node::Node;
 
@@ -40,16 +40,18 @@ impl Fold for Vec<Node<Item>> {
 
 impl Fold for Node<Item> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dsl_item(self.clone())
+        keep_span(self, folder.dsl_item(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         match **self {
             Item::ComponentDef {
+                ref comments,
                 name,
                 ref fields,
                 ref dart_members,
                 ref body,
             } => Node::new(Item::ComponentDef {
+                comments: comments.clone(),
                 name,
                 fields: fields.iter().map(|field| field.fold(folder)).collect(),
                 dart_members: dart_members
@@ -65,16 +67,18 @@ impl Fold for Node<Item> {
 
 impl Fold for Node<FieldDef> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dsl_field_def(self.clone())
+        keep_span(self, folder.dsl_field_def(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         let FieldDef {
+            ref comments,
             mutable,
             name,
             ref ty,
             ref default,
         } = **self;
         Node::new(FieldDef {
+            comments: comments.clone(),
             mutable,
             name,
             ty: ty.as_ref().map(|ty| ty.fold(folder)),
@@ -89,13 +93,28 @@ impl Fold for Config {
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         match *self {
+            Config::Comments(ref comments, ref config) => Config::Comments(
+                comments.clone(),
+                config.as_ref().map(|config| Box::new(config.fold(folder))),
+            ),
             Config::Field { name, ref value } => Config::Field {
                 name,
                 value: value.fold(folder),
             },
-            Config::EventHandler { name, ref block } => Config::EventHandler {
+            Config::EventHandler {
                 name,
-                block: block.fold(folder),
+                name_span,
+                ref sig,
+                ref body,
+            } => Config::EventHandler {
+                name,
+                name_span,
+                sig: sig.as_ref().map(|sig| sig.fold(folder)),
+                body: body.fold(folder),
+            },
+            Config::Binding { name, ref field } => Config::Binding {
+                name,
+                field: field.fold(folder),
             },
         }
     }
@@ -103,7 +122,7 @@ impl Fold for Config {
 
 impl Fold for Node<Type> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dsl_type(self.clone())
+        keep_span(self, folder.dsl_type(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         match **self {
@@ -114,10 +133,13 @@ impl Fold for Node<Type> {
 
 impl Fold for Node<Expr> {
     fn fold<F: Folder>(&self, folder: &mut F) -> Self {
-        folder.dsl_expr(self.clone())
+        keep_span(self, folder.dsl_expr(self.clone()))
     }
     fn super_fold<F: Folder>(&self, folder: &mut F) -> Self {
         match **self {
+            Expr::Comments(ref comments, ref expr) => {
+                Node::new(Expr::Comments(comments.clone(), expr.fold(folder)))
+            }
             Expr::Instance {
                 ref path,
                 ref unnamed,
@@ -0,0 +1,495 @@
+use dart;
+use dart::lex::{Lexer, Token};
+use dart::parse::{ParseResult, Parser};
+use dart::resolve::Res;
+use dart::visit::Visit as DartVisit;
+use dsl;
+use dsl::ast::{Config, Expr, FieldDef, Item};
+use dsl::visit::Visit;
+use node::Node;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+use std::rc::Rc;
+use syntax::codemap::{BytePos, FileName, Pos};
+use syntax::symbol::Symbol;
+use Span;
+
+/// How a range of source is highlighted.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Highlight {
+    Keyword,
+    Comment,
+    String,
+    /// The `${`/`}` around, or the `$name` of, an interpolation in a string.
+    Interpolation,
+    Number,
+    Component,
+    /// A field of a component, where it's defined or used.
+    Field,
+    /// A property of a widget or component being configured.
+    Property,
+    Event,
+    Class,
+}
+
+impl Highlight {
+    /// All highlights, in the order of their LSP token type indices.
+    pub const ALL: &'static [Highlight] = &[
+        Highlight::Keyword,
+        Highlight::Comment,
+        Highlight::String,
+        Highlight::Interpolation,
+        Highlight::Number,
+        Highlight::Component,
+        Highlight::Field,
+        Highlight::Property,
+        Highlight::Event,
+        Highlight::Class,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Highlight::Keyword => "keyword",
+            Highlight::Comment => "comment",
+            Highlight::String => "string",
+            Highlight::Interpolation => "interpolation",
+            Highlight::Number => "number",
+            Highlight::Component => "component",
+            Highlight::Field => "field",
+            Highlight::Property => "property",
+            Highlight::Event => "event",
+            Highlight::Class => "class",
+        }
+    }
+
+    /// The standard LSP semantic token type closest to this highlight.
+    pub fn lsp_token_type(self) -> &'static str {
+        match self {
+            Highlight::Keyword => "keyword",
+            Highlight::Comment => "comment",
+            Highlight::String => "string",
+            Highlight::Interpolation => "operator",
+            Highlight::Number => "number",
+            Highlight::Component => "struct",
+            Highlight::Field => "variable",
+            Highlight::Property => "property",
+            Highlight::Event => "event",
+            Highlight::Class => "class",
+        }
+    }
+
+    fn ansi_color(self) -> &'static str {
+        match self {
+            Highlight::Keyword => "35",
+            Highlight::Comment => "90",
+            Highlight::String => "32",
+            Highlight::Interpolation => "33",
+            Highlight::Number => "36",
+            Highlight::Component => "1;33",
+            Highlight::Field => "34",
+            Highlight::Property => "36",
+            Highlight::Event => "31",
+            Highlight::Class => "1;34",
+        }
+    }
+}
+
+const DART_KEYWORDS: &[&str] = &[
+    "abstract", "as", "assert", "async", "await", "base", "break", "case", "catch", "class",
+    "const", "continue", "covariant", "default", "deferred", "do", "dynamic", "else", "enum",
+    "export", "extends", "extension", "external", "factory", "false", "final", "finally", "for",
+    "get", "hide", "if", "implements", "import", "in", "interface", "is", "library", "mixin",
+    "new", "null", "on", "operator", "part", "rethrow", "return", "sealed", "set", "show",
+    "static", "super", "switch", "sync", "this", "throw", "true", "try", "typedef", "var",
+    "void", "when", "while", "with", "yield",
+];
+
+const DSL_KEYWORDS: &[&str] = &["def", "mut", "on", "bind"];
+
+/// A source file, split into highlighted ranges.
+pub struct Highlighted {
+    src: Rc<String>,
+    /// The position of the start of `src`.
+    start_pos: BytePos,
+    /// Non-overlapping ranges, in source order. Text outside of them
+    /// isn't highlighted.
+    pub ranges: Vec<(Span, Highlight)>,
+}
+
+/// Highlights the `.lyk` file at `path`. If it doesn't parse, only what
+/// can be told from its tokens alone is highlighted.
+pub fn highlight_file(path: &Path) -> ParseResult<Highlighted> {
+    let tokens = Lexer::from_file(path)?.tokenize()?;
+    let file = ::codemap()
+        .get_filemap(&FileName::Real(path.to_path_buf()))
+        .unwrap();
+    let module = Parser::new(path, &tokens).dsl_module().ok();
+    if let Some(ref module) = module {
+        // Unknown names are fine here, they're just highlighted as such.
+        dsl::resolve::resolve(&module.items, true);
+    }
+    Ok(highlight(
+        file.src.clone().unwrap(),
+        file.start_pos,
+        &tokens,
+        module.as_ref().map(|module| &module.items[..]),
+    ))
+}
+
+/// Highlights `tokens`, lexed from `src` (starting at `start_pos`), using
+/// the parsed, and possibly resolved, `items` where available.
+pub fn highlight(
+    src: Rc<String>,
+    start_pos: BytePos,
+    tokens: &[(Span, Token)],
+    items: Option<&[Node<Item>]>,
+) -> Highlighted {
+    let mut classifier = Classifier {
+        tokens,
+        marks: HashMap::new(),
+    };
+    if let Some(items) = items {
+        items.visit(&mut classifier);
+    }
+
+    let mut highlighted = Highlighted {
+        src,
+        start_pos,
+        ranges: vec![],
+    };
+    for &(span, token) in tokens {
+        if let Some(&highlight) = classifier.marks.get(&span.lo) {
+            highlighted.ranges.push((span, highlight));
+            continue;
+        }
+        match token {
+            Token::WhiteSpace(_) | Token::Punctuation(_) => {}
+            Token::Comment(_) => highlighted.ranges.push((span, Highlight::Comment)),
+            Token::NumberLiteral(..) => highlighted.ranges.push((span, Highlight::Number)),
+            Token::Identifier(name) => {
+                let name = name.as_str();
+                if DART_KEYWORDS.contains(&&*name) || DSL_KEYWORDS.contains(&&*name) {
+                    highlighted.ranges.push((span, Highlight::Keyword));
+                }
+            }
+            Token::StringLiteral {
+                contents,
+                raw,
+                interpolation_before,
+                interpolation_after,
+                ..
+            } => {
+                if interpolation_before {
+                    let brace = ::mk_sp(span.lo, span.lo + BytePos(1));
+                    highlighted.ranges.push((brace, Highlight::Interpolation));
+                }
+                let lo = if interpolation_before {
+                    span.lo + BytePos(1)
+                } else {
+                    span.lo
+                };
+                let hi = if interpolation_after {
+                    contents.hi
+                } else {
+                    span.hi
+                };
+                highlighted.string(::mk_sp(lo, hi), contents, raw);
+                if interpolation_after {
+                    let brace = ::mk_sp(contents.hi, span.hi);
+                    highlighted.ranges.push((brace, Highlight::Interpolation));
+                }
+            }
+        }
+    }
+    highlighted
+}
+
+impl Highlighted {
+    /// Adds the part of a string token in `span`, splitting out the `$name`
+    /// interpolations in its `contents`.
+    fn string(&mut self, span: Span, contents: Span, raw: bool) {
+        let mut lo = span.lo;
+        if !raw {
+            let src = self.src.clone();
+            let text = &src[self.offset(contents.lo)..self.offset(contents.hi)];
+            let mut chars = text.char_indices().peekable();
+            while let Some((i, c)) = chars.next() {
+                if c == '\\' {
+                    chars.next();
+                    continue;
+                }
+                if c != '$' {
+                    continue;
+                }
+                let mut end = i + 1;
+                while let Some(&(j, c)) = chars.peek() {
+                    let continues = if j == i + 1 {
+                        c.is_alphabetic() || c == '_'
+                    } else {
+                        c.is_alphanumeric() || c == '_'
+                    };
+                    if !continues {
+                        break;
+                    }
+                    end = j + c.len_utf8();
+                    chars.next();
+                }
+                if end > i + 1 {
+                    let start = contents.lo + BytePos::from_usize(i);
+                    if lo < start {
+                        self.ranges.push((::mk_sp(lo, start), Highlight::String));
+                    }
+                    lo = contents.lo + BytePos::from_usize(end);
+                    self.ranges
+                        .push((::mk_sp(start, lo), Highlight::Interpolation));
+                }
+            }
+        }
+        if lo < span.hi {
+            self.ranges.push((::mk_sp(lo, span.hi), Highlight::String));
+        }
+    }
+
+    fn offset(&self, pos: BytePos) -> usize {
+        (pos - self.start_pos).to_usize()
+    }
+
+    /// Calls `f` with each piece of the source in order, and its highlight.
+    fn render<F: FnMut(&str, Option<Highlight>)>(&self, mut f: F) {
+        let mut pos = 0;
+        for &(span, highlight) in &self.ranges {
+            let (lo, hi) = (self.offset(span.lo), self.offset(span.hi));
+            if pos < lo {
+                f(&self.src[pos..lo], None);
+            }
+            f(&self.src[lo..hi], Some(highlight));
+            pos = hi;
+        }
+        if pos < self.src.len() {
+            f(&self.src[pos..], None);
+        }
+    }
+
+    /// Returns the source as a `<pre>` block, with a `lyk-<name>` class on
+    /// each highlighted range.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<pre class=\"lyk\">");
+        self.render(|text, highlight| {
+            let text = text.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+            match highlight {
+                Some(highlight) => {
+                    write!(html, "<span class=\"lyk-{}\">{}</span>", highlight.name(), text)
+                        .unwrap();
+                }
+                None => html.push_str(&text),
+            }
+        });
+        html.push_str("</pre>\n");
+        html
+    }
+
+    /// Returns the source with ANSI terminal colours.
+    pub fn to_ansi(&self) -> String {
+        let mut ansi = String::new();
+        self.render(|text, highlight| match highlight {
+            Some(highlight) => {
+                write!(ansi, "\x1b[{}m{}\x1b[0m", highlight.ansi_color(), text).unwrap();
+            }
+            None => ansi.push_str(text),
+        });
+        ansi
+    }
+
+    /// Returns the LSP semantic tokens for the source: for each token, the
+    /// line and UTF-16 column of its start relative to the previous token,
+    /// its length, its type as an index into `Highlight::ALL` and no
+    /// modifiers. Tokens spanning several lines are split at line ends.
+    pub fn to_lsp(&self) -> Vec<u32> {
+        let mut line_starts = vec![0];
+        line_starts.extend(self.src.match_indices('\n').map(|(i, _)| i + 1));
+
+        let mut data = vec![];
+        let (mut prev_line, mut prev_col) = (0, 0);
+        for &(span, highlight) in &self.ranges {
+            let token_type = Highlight::ALL
+                .iter()
+                .position(|&h| h == highlight)
+                .unwrap() as u32;
+            let lo = self.offset(span.lo);
+            let mut line = match line_starts.binary_search(&lo) {
+                Ok(line) => line,
+                Err(next) => next - 1,
+            };
+            let mut col = self.src[line_starts[line]..lo].encode_utf16().count();
+            for part in self.src[lo..self.offset(span.hi)].split('\n') {
+                let len = part.trim_right_matches('\r').encode_utf16().count();
+                if len > 0 {
+                    let delta_col = if line == prev_line { col - prev_col } else { col };
+                    data.extend(&[
+                        (line - prev_line) as u32,
+                        delta_col as u32,
+                        len as u32,
+                        token_type,
+                        0,
+                    ]);
+                    prev_line = line;
+                    prev_col = col;
+                }
+                line += 1;
+                col = 0;
+            }
+        }
+        data
+    }
+}
+
+/// Finds the tokens whose highlight depends on what the parser and the
+/// resolver know, e.g. whether an identifier names a field or a widget.
+struct Classifier<'a> {
+    tokens: &'a [(Span, Token)],
+    /// Highlights of the tokens starting at each position.
+    marks: HashMap<BytePos, Highlight>,
+}
+
+impl<'a> Classifier<'a> {
+    fn index_of(&self, pos: BytePos) -> usize {
+        match self.tokens.binary_search_by_key(&pos, |&(span, _)| span.lo) {
+            Ok(i) | Err(i) => i,
+        }
+    }
+
+    /// Marks the first `name` identifier in `span`.
+    fn mark_in(&mut self, span: Span, name: Symbol, highlight: Highlight) {
+        let start = self.index_of(span.lo);
+        for &(token_span, token) in &self.tokens[start..] {
+            if token_span.lo >= span.hi {
+                break;
+            }
+            if token.as_ident() == Some(name) {
+                self.marks.insert(token_span.lo, highlight);
+                return;
+            }
+        }
+    }
+
+    /// Marks the closest `name` identifier before `pos`.
+    fn mark_before(&mut self, pos: BytePos, name: Symbol, highlight: Highlight) {
+        let end = self.index_of(pos);
+        for &(token_span, token) in self.tokens[..end].iter().rev() {
+            if token.as_ident() == Some(name) {
+                self.marks.insert(token_span.lo, highlight);
+                return;
+            }
+        }
+    }
+
+    fn highlight_of(res: Res) -> Option<Highlight> {
+        match res {
+            Res::Class(_) |
+            Res::Enum(_) |
+            Res::TypeAlias(_) |
+            Res::Extension(_) |
+            Res::Constructor(_) => Some(Highlight::Class),
+            Res::Dsl(dsl::resolve::Res::Component(_)) => Some(Highlight::Component),
+            Res::Dsl(dsl::resolve::Res::Field(_)) => Some(Highlight::Field),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> dart::visit::Visitor for Classifier<'a> {
+    fn dart_qualified(&mut self, qualified: Node<dart::ast::Qualified>) {
+        if let (Some(span), Some(res)) = (qualified.span().get(), qualified.res().get()) {
+            if let Some(highlight) = Classifier::highlight_of(res) {
+                let lo = qualified
+                    .prefix
+                    .as_ref()
+                    .and_then(|prefix| prefix.span().get())
+                    .map_or(span.lo, |prefix| prefix.hi);
+                self.mark_in(::mk_sp(lo, span.hi), qualified.name, highlight);
+            }
+        }
+        qualified.super_visit(self)
+    }
+
+    fn dart_expr(&mut self, expr: Node<dart::ast::Expr>) {
+        if let dart::ast::Expr::Identifier(name) = *expr {
+            if let (Some(span), Some(res)) = (expr.span().get(), expr.res().get()) {
+                if let Some(highlight) = Classifier::highlight_of(res) {
+                    self.mark_in(span, name, highlight);
+                }
+            }
+        }
+        expr.super_visit(self)
+    }
+}
+
+impl<'a> dsl::visit::Visitor for Classifier<'a> {
+    fn dsl_item(&mut self, item: Node<Item>) {
+        if let Item::ComponentDef {
+            name, ref body, ..
+        } = *item
+        {
+            if let Some(span) = item.span().get() {
+                self.mark_in(span, name, Highlight::Component);
+            }
+            // The `..` before the body is part of the DSL, not a cascade.
+            if let Some(body_lo) = body.as_ref().and_then(|body| body.span().get()) {
+                let end = self.index_of(body_lo.lo);
+                let dots = self.tokens[..end]
+                    .iter()
+                    .rev()
+                    .filter(|&&(_, token)| !token.is_whitespace())
+                    .take(2)
+                    .filter(|&&(_, token)| token == Token::Punctuation('.'))
+                    .map(|&(span, _)| span.lo)
+                    .collect::<Vec<_>>();
+                if dots.len() == 2 {
+                    for lo in dots {
+                        self.marks.insert(lo, Highlight::Keyword);
+                    }
+                }
+            }
+        }
+        item.super_visit(self)
+    }
+
+    fn dsl_field_def(&mut self, field_def: Node<FieldDef>) {
+        if let Some(span) = field_def.span().get() {
+            self.mark_in(span, field_def.name, Highlight::Field);
+        }
+        field_def.super_visit(self)
+    }
+
+    fn dsl_config(&mut self, config: &Config) {
+        match *config {
+            Config::Field { name, ref value } => if let Some(span) = value.span().get() {
+                self.mark_before(span.lo, name, Highlight::Property);
+            },
+            Config::Binding { name, ref field } => if let Some(span) = field.span().get() {
+                self.mark_before(span.lo, name, Highlight::Property);
+            },
+            Config::EventHandler { name_span, .. } => if let Some(span) = name_span {
+                self.marks.insert(span.lo, Highlight::Event);
+            },
+            Config::Comments(..) => {}
+        }
+        config.super_visit(self)
+    }
+
+    fn dsl_expr(&mut self, expr: Node<Expr>) {
+        if let Expr::Instance { ref path, .. } = *expr {
+            let highlight = match path.res().get() {
+                Some(Res::Dsl(dsl::resolve::Res::Component(_))) => Highlight::Component,
+                _ => Highlight::Class,
+            };
+            if let Some(span) = expr.span().get() {
+                self.mark_in(span, path.name, highlight);
+            }
+        }
+        expr.super_visit(self)
+    }
+}
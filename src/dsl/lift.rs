@@ -1,9 +1,11 @@
 use dsl::ast;
 use dart::ast::*;
+use dart::fold::{keep_span, Fold, Folder};
 use dart::sdk;
 use dart::resolve::Res;
+use dart::visit::{Visit, Visitor};
 use node::Node;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::mem;
 use syntax::symbol::Symbol;
 
@@ -12,6 +14,9 @@ pub struct Lifter {
     stateful_widget_class: Res,
     state_class: Res,
     classes: HashMap<Node<Item>, Class>,
+    mut_fields: HashSet<Symbol>,
+    /// Classes kept as plain Dart, with the reason they couldn't be lifted.
+    pub kept_as_dart: Vec<(Symbol, String)>,
 }
 
 #[derive(Clone)]
@@ -31,14 +36,16 @@ struct Class {
 }
 
 impl Class {
-    fn lift_build_return(&mut self, lifter: &mut Lifter) -> Option<Node<ast::Expr>> {
+    fn lift_build_return(&mut self, lifter: &mut Lifter) -> Result<Node<ast::Expr>, String> {
         let mut build_return = None;
+        let mut failed = None;
         self.dart_members.retain(|member| {
             macro_rules! require {
-                ($($c:expr),+) => {
-                    if !($($c)&&+) {
+                ($($c:expr => $reason:expr),+) => {
+                    $(if !$c {
+                        failed = Some($reason.to_string());
                         return true;
-                    }
+                    })+
                 };
             }
             match *member.clone() {
@@ -49,27 +56,29 @@ impl Class {
                             return true;
                         }
                     }
-                    require![meta.len() == 1];
+                    require![meta.len() == 1 => "`build` isn't annotated with just `@override`"];
                     match meta[0] {
                         MetaItem::Attribute {
                             ref qualified,
                             ref arguments,
                         } => {
                             require![
-                                qualified.prefix.is_none(),
-                                qualified.name == "override",
-                                arguments.is_none(),
-                                qualif.is_empty(),
-                                function.generics.is_empty()
+                                qualified.prefix.is_none() &&
+                                    qualified.name == "override" &&
+                                    arguments.is_none()
+                                    => "`build` isn't annotated with just `@override`",
+                                qualif.is_empty() => "`build` has modifiers",
+                                function.generics.is_empty() => "`build` has type parameters"
                             ];
                         }
                         MetaItem::Comments(_) => {
-                            require![false];
+                            failed = Some("`build` has comments".to_string());
+                            return true;
                         }
                     }
 
                     if let Some(FnBody::Block(ref stm)) = function.body {
-                        if let Statement::Block(ref stm) = **stm {
+                        if let Statement::Block(ref stm) = *stm.filled() {
                             if stm.len() == 1 {
                                 if let Statement::Return(Some(ref expr)) = *stm[0] {
                                     build_return = Some(lifter.lift_expr(expr.clone()));
@@ -78,12 +87,15 @@ impl Class {
                             }
                         }
                     }
+                    failed = Some("`build` is not a single `return` statement".to_string());
                 }
                 _ => {}
             }
             true
         });
-        build_return
+        build_return.ok_or_else(|| {
+            failed.unwrap_or_else(|| "no `build` method".to_string())
+        })
     }
 }
 
@@ -92,7 +104,7 @@ impl Lifter {
         let mut stateless_widget_class = None;
         let mut stateful_widget_class = None;
         let mut state_class = None;
-        let module = Module::load(&sdk::resolve_import(
+        let module = Module::load_exports(&sdk::resolve_import(
             "package:flutter/src/widgets/framework.dart",
         ));
         for item in &module.items {
@@ -113,6 +125,8 @@ impl Lifter {
             stateful_widget_class: stateful_widget_class.unwrap(),
             state_class: state_class.unwrap(),
             classes: HashMap::new(),
+            mut_fields: HashSet::new(),
+            kept_as_dart: vec![],
         }
     }
 
@@ -141,22 +155,51 @@ impl Lifter {
             }
             if let ClassKind::State { ref widget_class } = kind {
                 let widget_class = self.classes.get_mut(widget_class).unwrap();
+                let props: HashSet<Symbol> = widget_class.fields.iter().map(|f| f.name).collect();
+                let mut unqualifier = WidgetUnqualifier { props: &props };
                 widget_class.fields.extend(fields);
-                widget_class.dart_members.extend(dart_members);
+                widget_class
+                    .dart_members
+                    .extend(dart_members.iter().map(|member| member.fold(&mut unqualifier)));
             }
         }
 
         let mut replacements: HashMap<_, _> = mem::replace(&mut self.classes, HashMap::new())
             .into_iter()
             .filter_map(|(item, mut class)| {
-                if let ClassKind::Remove = class.kind {
-                    return Some((item, vec![]));
+                match class.kind {
+                    ClassKind::Remove => return Some((item, vec![])),
+                    ClassKind::State { .. } => {
+                        self.kept_as_dart.push((
+                            class.name,
+                            "its `StatefulWidget` couldn't be lifted".to_string(),
+                        ));
+                        return None;
+                    }
+                    _ => {}
+                }
+                self.mut_fields.clear();
+                if let ClassKind::StatefulWidget = class.kind {
+                    self.mut_fields.extend(
+                        class.fields.iter().filter(|f| f.mutable).map(|f| f.name),
+                    );
+                    let mut stripper = SetStateStripper {
+                        mut_fields: &self.mut_fields,
+                    };
+                    class.dart_members = class
+                        .dart_members
+                        .iter()
+                        .map(|member| member.fold(&mut stripper))
+                        .collect();
                 }
                 let body = match class.kind {
                     ClassKind::StatelessWidget | ClassKind::StatefulWidget => {
                         match class.lift_build_return(self) {
-                            Some(build_return) => Some(build_return),
-                            None => return None,
+                            Ok(build_return) => Some(build_return),
+                            Err(reason) => {
+                                self.kept_as_dart.push((class.name, reason));
+                                return None;
+                            }
                         }
                     }
                     _ => None,
@@ -165,6 +208,7 @@ impl Lifter {
                     item,
                     vec![
                         Node::new(ast::Item::ComponentDef {
+                            comments: vec![],
                             name: class.name,
                             fields: class.fields.into_iter().map(Node::new).collect(),
                             dart_members: class.dart_members,
@@ -188,17 +232,27 @@ impl Lifter {
     }
 
     fn collect_item(&mut self, item: Node<Item>) {
+        if let Item::Class { name, .. } = *item {
+            if let Err(reason) = self.collect_class(item.clone()) {
+                self.kept_as_dart.push((name, reason));
+            }
+        }
+    }
+
+    fn collect_class(&mut self, item: Node<Item>) -> Result<(), String> {
         macro_rules! require {
-            ($($c:expr),+) => {
-                if !($($c)&&+) {
-                    return;
-                }
+            ($($c:expr => $reason:expr),+) => {
+                $(if !$c {
+                    return Err($reason.to_string());
+                })+
             };
         }
         match *item.clone() {
             Item::Class {
                 ref meta,
-                abstract_: false,
+                abstract_,
+                modifier,
+                mixin,
                 name,
                 ref generics,
                 ref superclass,
@@ -207,10 +261,13 @@ impl Lifter {
                 ref members,
             } => {
                 require![
-                    meta.is_empty(),
-                    generics.is_empty(),
-                    mixins.is_empty(),
-                    interfaces.is_empty()
+                    !abstract_ => "is abstract",
+                    modifier.is_none() => "has a class modifier",
+                    !mixin => "is a mixin class",
+                    meta.is_empty() => "has annotations",
+                    generics.is_empty() => "has type parameters",
+                    mixins.is_empty() => "has mixins",
+                    interfaces.is_empty() => "implements interfaces"
                 ];
                 let kind = match *superclass {
                     Some(ref superclass) => {
@@ -222,16 +279,23 @@ impl Lifter {
                         } else if class_kind == self.state_class {
                             let qualified = match *superclass.params[0] {
                                 Type::Path(ref qualified) => qualified,
-                                _ => return,
+                                _ => return Err("`State` isn't parametrized by a class".to_string()),
                             };
                             ClassKind::State {
                                 widget_class: match qualified.res().get().unwrap() {
                                     Res::Class(item) => item,
-                                    _ => return,
+                                    _ => {
+                                        return Err(
+                                            "`State` isn't parametrized by a class".to_string(),
+                                        )
+                                    }
                                 },
                             }
                         } else {
-                            return;
+                            return Err(format!(
+                                "`{}` isn't a widget or state class",
+                                superclass.name
+                            ));
                         }
                     }
                     None => ClassKind::Plain,
@@ -243,18 +307,20 @@ impl Lifter {
                     dart_members: vec![],
                 };
                 let mut pub_fields = 0;
-                let mut failed = false;
+                let mut failed = None;
                 let mut dart_members = members.clone();
                 dart_members.retain(|member| {
                     macro_rules! require {
-                        ($($c:expr),+) => {
-                            if !($($c)&&+) {
-                                failed = true;
+                        ($($c:expr => $reason:expr),+) => {
+                            $(if !$c {
+                                failed = Some($reason.to_string());
                                 return false;
-                            }
+                            })+
                         };
                     }
-                    require![!failed];
+                    if failed.is_some() {
+                        return false;
+                    }
                     match *member.clone() {
                         ClassMember::Fields {
                             ref meta,
@@ -263,9 +329,9 @@ impl Lifter {
                             ref initializers,
                         } => {
                             require![
-                                meta.is_empty(),
-                                var_type.fcv != Some(FinalConstVar::Const),
-                                initializers.len() >= 1
+                                meta.is_empty() => "has annotated fields",
+                                var_type.fcv != Some(FinalConstVar::Const) => "has const fields",
+                                initializers.len() >= 1 => "has a field declaration without names"
                             ];
                             let ty = match *var_type.ty {
                                 Type::Infer => None,
@@ -279,6 +345,7 @@ impl Lifter {
                                 pub_fields += 1;
                             }
                             class.fields.push(ast::FieldDef {
+                                comments: vec![],
                                 mutable: var_type.fcv != Some(FinalConstVar::Final),
                                 name: initializers[0].name,
                                 ty,
@@ -290,18 +357,22 @@ impl Lifter {
                     }
                 });
 
-                require![!failed];
+                if let Some(reason) = failed.take() {
+                    return Err(reason);
+                }
 
                 dart_members.retain(|member| {
                     macro_rules! require {
-                        ($($c:expr),+) => {
-                            if !($($c)&&+) {
-                                failed = true;
+                        ($($c:expr => $reason:expr),+) => {
+                            $(if !$c {
+                                failed = Some($reason.to_string());
                                 return false;
-                            }
+                            })+
                         };
                     }
-                    require![!failed];
+                    if failed.is_some() {
+                        return false;
+                    }
                     match *member.clone() {
                         ClassMember::Method(.., ref function) => {
                             if let ClassKind::StatefulWidget = class.kind {
@@ -322,21 +393,28 @@ impl Lifter {
                             function_body: None,
                         } => {
                             require![
-                                meta.is_empty(),
-                                method_qualifiers.len() == 1,
-                                method_qualifiers[0] == MethodQualifiers::Const,
+                                meta.is_empty() => "has an annotated constructor",
+                                method_qualifiers.len() == 1 &&
+                                    method_qualifiers[0] == MethodQualifiers::Const
+                                    => "has a constructor that isn't just `const`",
                                 match *sig.return_type {
                                     Type::Infer => true,
                                     _ => false,
-                                },
-                                sig.optional_kind == OptionalArgKind::Named,
-                                !sig.async,
-                                !sig.generator,
+                                } => "has a constructor with a return type",
+                                sig.optional_kind == OptionalArgKind::Named
+                                    => "has a constructor with optional positional parameters",
+                                !sig.async && !sig.generator
+                                    => "has an asynchronous or generator constructor",
                                 match class.kind {
                                     ClassKind::StatelessWidget | ClassKind::StatefulWidget => {
-                                        initializers.len() == 1 && match initializers[0] {
-                                            ConstructorInitializer::Super(ident, ref args) => {
-                                                ident.is_none() && args.unnamed.is_empty() &&
+                                        match initializers.split_last() {
+                                            Some((
+                                                &ConstructorInitializer::Super(ident, ref args),
+                                                asserts,
+                                            )) => {
+                                                asserts.iter().all(is_null_assert) &&
+                                                    ident.is_none() &&
+                                                    args.unnamed.is_empty() &&
                                                     args.named.len() == 1 &&
                                                     args.named[0].name == "key" &&
                                                     match *args.named[0].expr {
@@ -347,10 +425,11 @@ impl Lifter {
                                             _ => false,
                                         }
                                     }
-                                    ClassKind::Plain => initializers.is_empty(),
+                                    ClassKind::Plain => initializers.iter().all(is_null_assert),
                                     ClassKind::State { .. } | ClassKind::Remove => false,
-                                },
-                                sig.required.is_empty(),
+                                } => "has constructor initializers other than `super(key: key)`",
+                                sig.required.is_empty()
+                                    => "has a constructor with required positional parameters",
                                 match class.kind {
                                     ClassKind::StatelessWidget => {
                                         sig.optional.len() == pub_fields + 1 &&
@@ -371,7 +450,7 @@ impl Lifter {
                                     ClassKind::StatefulWidget |
                                     ClassKind::State { .. } |
                                     ClassKind::Remove => false,
-                                }
+                                } => "has constructor parameters that don't match its fields"
                             ];
 
                             for (i, arg) in sig.optional.iter().enumerate() {
@@ -382,15 +461,20 @@ impl Lifter {
                                     _ => {}
                                 }
                                 require![
-                                    arg.meta.is_empty(),
-                                    !arg.covariant,
-                                    arg.field,
-                                    !arg.var.name.as_str().starts_with('_'),
-                                    arg.ty.fcv.is_none(),
-                                    match *arg.ty.ty {
+                                    // Only `@required`, which `Lowerer` puts on
+                                    // parameters of fields without a default.
+                                    arg.meta.is_empty() ||
+                                        (arg.meta.len() == 1 && arg.is_required())
+                                        => "has annotated constructor parameters",
+                                    !arg.covariant => "has covariant constructor parameters",
+                                    arg.field
+                                        => "has constructor parameters that aren't `this.field`",
+                                    !arg.var.name.as_str().starts_with('_')
+                                        => "has private constructor parameters",
+                                    arg.ty.fcv.is_none() && match *arg.ty.ty {
                                         Type::Infer => true,
                                         _ => false,
-                                    }
+                                    } => "has typed constructor parameters"
                                 ];
                                 let mut default = None;
                                 if let Some(ref expr) = arg.var.init {
@@ -408,10 +492,14 @@ impl Lifter {
                         _ => true,
                     }
                 });
-                require![!failed];
+                if let Some(reason) = failed.take() {
+                    return Err(reason);
+                }
 
                 if let ClassKind::StatefulWidget = class.kind {
-                    require![dart_members.is_empty()];
+                    require![
+                        dart_members.is_empty() => "has members besides fields and `createState`"
+                    ];
                 }
 
                 class.dart_members = dart_members;
@@ -419,11 +507,16 @@ impl Lifter {
             }
             _ => {}
         }
+        Ok(())
     }
 
 
     fn lift_expr(&mut self, expr: Node<Expr>) -> Node<ast::Expr> {
         match *expr.clone() {
+            Expr::Comments(ref comments, ref expr) => Node::new(ast::Expr::Comments(
+                comments.clone(),
+                self.lift_expr(expr.clone()),
+            )),
             Expr::List {
                 const_: _,
                 element_ty: _,
@@ -454,7 +547,15 @@ impl Lifter {
 
                 let mut config = vec![];
                 for arg in &args.named {
-                    config.push(self.lift_config(arg));
+                    let lifted = self.lift_config(arg);
+                    config.push(if arg.comments.is_empty() {
+                        lifted
+                    } else {
+                        ast::Config::Comments(arg.comments.clone(), Some(Box::new(lifted)))
+                    });
+                }
+                if !args.trailing_comments.is_empty() {
+                    config.push(ast::Config::Comments(args.trailing_comments.clone(), None));
                 }
                 Node::new(ast::Expr::Instance {
                     path: path.clone(),
@@ -467,9 +568,200 @@ impl Lifter {
     }
 
     fn lift_config(&mut self, field: &NamedArg) -> ast::Config {
+        if let Some(name) = event_name(field.name) {
+            if let Expr::Closure(ref sig, ref body) = *field.expr {
+                let simple_sig = sig.optional.is_empty() && !sig.async && !sig.generator &&
+                    match *sig.return_type {
+                        Type::Infer => true,
+                        _ => false,
+                    } &&
+                    sig.required.iter().all(|arg| {
+                        arg.meta.is_empty() && !arg.covariant && !arg.field &&
+                            arg.var.init.is_none()
+                    });
+                if simple_sig {
+                    let mut stripper = SetStateStripper {
+                        mut_fields: &self.mut_fields,
+                    };
+                    return ast::Config::EventHandler {
+                        name,
+                        name_span: None,
+                        sig: if sig.required.is_empty() {
+                            None
+                        } else {
+                            Some(sig.fold(&mut stripper))
+                        },
+                        body: body.fold(&mut stripper),
+                    };
+                }
+            }
+        }
         ast::Config::Field {
             name: field.name,
             value: self.lift_expr(field.expr.clone()),
         }
     }
 }
+
+/// Returns true for `assert(name != null)`, which `Lowerer` generates for
+/// fields without a default.
+fn is_null_assert(initializer: &ConstructorInitializer) -> bool {
+    let args = match *initializer {
+        ConstructorInitializer::Assert(ref args) => args,
+        _ => return false,
+    };
+    if args.unnamed.len() != 1 || !args.named.is_empty() {
+        return false;
+    }
+    match *args.unnamed[0] {
+        Expr::Binary(BinOp::Bool(BoolBinOp::Ne), ref left, ref right) => {
+            match (&**left, &**right) {
+                (&Expr::Identifier(_), &Expr::Identifier(null)) => null == "null",
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Turns the name of a handler parameter into its event name (`onPressed` -> `pressed`).
+fn event_name(name: Symbol) -> Option<Symbol> {
+    let name = name.as_str();
+    if !name.starts_with("on") {
+        return None;
+    }
+    let mut chars = name[2..].chars();
+    match chars.next() {
+        Some(c) if c.is_uppercase() => {
+            let mut event_name: String = c.to_lowercase().collect();
+            event_name.extend(chars);
+            Some(Symbol::intern(&event_name))
+        }
+        _ => None,
+    }
+}
+
+/// Removes the `setState(() { ... })` wrappers `Lowerer` puts around
+/// runs of statements mutating `mut` fields.
+struct SetStateStripper<'a> {
+    mut_fields: &'a HashSet<Symbol>,
+}
+
+impl<'a> SetStateStripper<'a> {
+    fn unwrap_set_state(&self, statement: &Statement) -> Option<Vec<Node<Statement>>> {
+        let expr = match *statement {
+            Statement::Expression(Some(ref expr)) => expr,
+            _ => return None,
+        };
+        let (callee, args) = match **expr {
+            Expr::Suffix(ref callee, Suffix::Call(ref generics, ref args)) if generics.is_empty() => {
+                (callee, args)
+            }
+            _ => return None,
+        };
+        match **callee {
+            Expr::Identifier(name) if name == "setState" => {}
+            _ => return None,
+        }
+        if !args.named.is_empty() || args.unnamed.len() != 1 {
+            return None;
+        }
+        let block = match *args.unnamed[0] {
+            Expr::Closure(ref sig, FnBody::Block(ref block)) => {
+                if !sig.required.is_empty() || !sig.optional.is_empty() {
+                    return None;
+                }
+                block
+            }
+            _ => return None,
+        };
+        let statements = match **block {
+            Statement::Block(ref statements) if !statements.is_empty() => statements,
+            _ => return None,
+        };
+        let all_mutate = statements.iter().all(|statement| {
+            let mut finder = MutationFinder {
+                mut_fields: self.mut_fields,
+                found: false,
+            };
+            statement.visit(&mut finder);
+            finder.found
+        });
+        if all_mutate {
+            Some(statements.clone())
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Folder for SetStateStripper<'a> {
+    fn dart_statement(&mut self, statement: Node<Statement>) -> Node<Statement> {
+        if let Statement::Block(ref statements) = *statement {
+            let mut stripped = vec![];
+            for statement in statements {
+                match self.unwrap_set_state(statement) {
+                    Some(inner) => stripped.extend(inner.iter().map(|s| s.fold(self))),
+                    None => stripped.push(statement.fold(self)),
+                }
+            }
+            return Node::new(Statement::Block(stripped));
+        }
+        // Outside of a block, only a single statement can take the place
+        // of its `setState` call.
+        match self.unwrap_set_state(&statement) {
+            Some(ref inner) if inner.len() == 1 => inner[0].fold(self),
+            _ => statement.super_fold(self),
+        }
+    }
+}
+
+/// Turns `widget.name` in a `State` class back into `name`, for the fields
+/// of its `StatefulWidget`, which `Lowerer` qualifies that way.
+struct WidgetUnqualifier<'a> {
+    props: &'a HashSet<Symbol>,
+}
+
+impl<'a> Folder for WidgetUnqualifier<'a> {
+    fn dart_expr(&mut self, expr: Node<Expr>) -> Node<Expr> {
+        if let Expr::Suffix(ref object, Suffix::Field(name)) = *expr {
+            if let Expr::Identifier(widget) = **object {
+                // Only the `State.widget` getter, not e.g. a local `widget`.
+                let is_state_widget = widget == "widget" && match object.res().get() {
+                    Some(Res::Getter(_)) | Some(Res::GetterSetter(..)) => true,
+                    _ => false,
+                };
+                if is_state_widget && self.props.contains(&name) {
+                    return keep_span(&expr, Node::new(Expr::Identifier(name)));
+                }
+            }
+        }
+        expr.super_fold(self)
+    }
+}
+
+/// Looks for assignments and increments of `mut` fields.
+struct MutationFinder<'a> {
+    mut_fields: &'a HashSet<Symbol>,
+    found: bool,
+}
+
+impl<'a> Visitor for MutationFinder<'a> {
+    fn dart_expr(&mut self, expr: Node<Expr>) {
+        match *expr {
+            Expr::Binary(BinOp::Assign(_), ref left, _) |
+            Expr::Unary(UnOp::PostDec, ref left) |
+            Expr::Unary(UnOp::PostInc, ref left) |
+            Expr::Unary(UnOp::PreDec, ref left) |
+            Expr::Unary(UnOp::PreInc, ref left) => {
+                if let Expr::Identifier(name) = **left {
+                    if self.mut_fields.contains(&name) {
+                        self.found = true;
+                    }
+                }
+            }
+            _ => {}
+        }
+        expr.super_visit(self)
+    }
+}
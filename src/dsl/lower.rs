@@ -3,48 +3,110 @@ use dsl::resolve::Res;
 use dart::{ast, resolve};
 use syntax::symbol::Symbol;
 use node::Node;
-use dart::fold::{Fold, Folder};
+use dart::fold::{keep_span, Fold, Folder};
+use dart::visit::{Visit, VisitNode, Visitor};
+use std::mem;
+use Span;
 
 pub struct Lowerer {
     pub needs_set_state: bool,
+    pub has_error: bool,
+    /// Whether the code being lowered goes into a `State` class, where the
+    /// immutable fields are only reachable through `widget`.
+    in_state_class: bool,
+    /// Whether the code being lowered is already inside a `setState` call.
+    in_set_state: bool,
 }
 
 #[derive(Copy, Clone)]
-enum Strategy {
+pub enum Strategy {
     Plain,
     StatelessWidget,
     StatefulWidget,
 }
 
+impl Strategy {
+    pub fn of(fields: &[Node<FieldDef>], body: &Option<Node<Expr>>) -> Strategy {
+        if body.is_none() {
+            Strategy::Plain
+        } else if fields.iter().any(|f| f.mutable) {
+            Strategy::StatefulWidget
+        } else {
+            Strategy::StatelessWidget
+        }
+    }
+
+    /// Returns true if `field` can be passed to the constructor of the
+    /// class a component with this strategy lowers to.
+    pub fn takes_param(self, field: &FieldDef) -> bool {
+        if field.name.as_str().starts_with('_') {
+            return false;
+        }
+        match self {
+            Strategy::StatefulWidget => !field.mutable,
+            Strategy::Plain | Strategy::StatelessWidget => true,
+        }
+    }
+}
+
 impl Lowerer {
     pub fn new() -> Self {
         Lowerer {
             needs_set_state: false,
+            has_error: false,
+            in_state_class: false,
+            in_set_state: false,
         }
     }
 
     pub fn lower_items(&mut self, items: &[Node<Item>]) -> Vec<Node<ast::Item>> {
-        items
-            .iter()
-            .flat_map(|item| self.lower_item(item))
-            .collect()
+        let mut lowered = vec![];
+        for item in items {
+            for dart_item in self.lower_item(item) {
+                dart_item.visit(&mut SpanInheritor {
+                    span: item.span().get(),
+                });
+                lowered.push(dart_item);
+            }
+        }
+        lowered
     }
 
-    fn lower_item(&mut self, item: &Item) -> Vec<Node<ast::Item>> {
-        match *item {
+    fn lower_item(&mut self, item: &Node<Item>) -> Vec<Node<ast::Item>> {
+        match **item {
             Item::ComponentDef {
+                ref comments,
                 name,
                 ref fields,
                 ref dart_members,
                 ref body,
             } => {
-                let strategy = if body.is_none() {
-                    Strategy::Plain
-                } else if fields.iter().any(|f| f.mutable) {
-                    Strategy::StatefulWidget
-                } else {
-                    Strategy::StatelessWidget
-                };
+                let strategy = Strategy::of(fields, body);
+
+                for field in fields {
+                    let private = field.name.as_str().starts_with('_');
+                    if private && !field.mutable && field.default.is_none() {
+                        println!(
+                            "`{}.{}` is never initialized: private fields need a default",
+                            name,
+                            field.name
+                        );
+                        self.has_error = true;
+                    } else if !private && !strategy.takes_param(field) && field.default.is_none() {
+                        println!(
+                            "`{}.{}` is never initialized: `mut` fields of stateful components \
+                             aren't parameters, so they need a default",
+                            name,
+                            field.name
+                        );
+                        self.has_error = true;
+                    }
+                }
+
+                let mut meta = vec![];
+                if !comments.is_empty() {
+                    meta.push(ast::MetaItem::Comments(comments.clone()));
+                }
 
                 let mut items = vec![];
                 let mut class_members = vec![];
@@ -58,9 +120,7 @@ impl Lowerer {
                             .map(|field| self.lower_field_def(field)),
                     );
                     let mut class_members = vec![];
-                    class_members.extend(
-                        self.lower_constructor(strategy, fields.iter().filter(|f| !f.mutable)),
-                    );
+                    class_members.extend(self.lower_constructor(strategy, fields));
 
                     class_members.extend(
                         fields
@@ -89,14 +149,17 @@ impl Lowerer {
                                 args: ast::Args {
                                     unnamed: vec![],
                                     named: vec![],
+                                    trailing_comments: vec![],
                                 },
                             }))),
                         }),
                     )));
 
                     items.push(Node::new(ast::Item::Class {
-                        meta: vec![],
+                        meta: meta.drain(..).collect(),
                         abstract_: false,
+                        modifier: None,
+                        mixin: false,
                         name,
                         generics: vec![],
                         superclass: Some(ast::Qualified::one("StatefulWidget", vec![])),
@@ -109,7 +172,15 @@ impl Lowerer {
                     class_members.extend(fields.iter().map(|field| self.lower_field_def(field)));
                 }
 
-                class_members.extend(dart_members.iter().cloned());
+                if let Strategy::StatefulWidget = strategy {
+                    self.in_state_class = true;
+                }
+                for dart_member in dart_members {
+                    class_members.push(match strategy {
+                        Strategy::StatefulWidget => dart_member.fold(self),
+                        Strategy::Plain | Strategy::StatelessWidget => dart_member.clone(),
+                    });
+                }
 
                 if let Some(ref body) = *body {
                     class_members.push(Node::new(ast::ClassMember::Method(
@@ -137,6 +208,7 @@ impl Lowerer {
                         }),
                     )));
                 }
+                self.in_state_class = false;
 
                 let superclass = match strategy {
                     Strategy::StatelessWidget => {
@@ -150,8 +222,10 @@ impl Lowerer {
                 };
 
                 items.push(Node::new(ast::Item::Class {
-                    meta: vec![],
+                    meta,
                     abstract_: false,
+                    modifier: None,
+                    mixin: false,
                     name: match strategy {
                         Strategy::StatefulWidget => Symbol::intern(
                             &format!("_{}State", name.as_str().trim_left_matches('_')),
@@ -185,13 +259,29 @@ impl Lowerer {
         }
 
         let mut has_fields = false;
+        let mut asserts = vec![];
         for field in fields {
-            if field.name.as_str().starts_with('_') {
+            if !strategy.takes_param(field) {
                 continue;
             }
             has_fields = true;
+            let mut meta = vec![];
+            if field.default.is_none() {
+                meta.push(ast::MetaItem::simple("required"));
+                asserts.push(ast::ConstructorInitializer::Assert(ast::Args {
+                    unnamed: vec![
+                        Node::new(ast::Expr::Binary(
+                            ast::BinOp::Bool(ast::BoolBinOp::Ne),
+                            Node::new(ast::Expr::Identifier(field.name)),
+                            Node::new(ast::Expr::Identifier(Symbol::intern("null"))),
+                        )),
+                    ],
+                    named: vec![],
+                    trailing_comments: vec![],
+                }));
+            }
             args.push(ast::ArgDef {
-                meta: vec![],
+                meta,
                 covariant: false,
                 ty: ast::VarType {
                     fcv: None,
@@ -199,13 +289,16 @@ impl Lowerer {
                 },
                 field: true,
                 default_uses_eq: false,
-                var: Node::new(ast::VarDef {
-                    name: field.name,
-                    init: field
-                        .default
-                        .as_ref()
-                        .map(|default| self.lower_expr(default)),
-                }),
+                var: derived(
+                    Node::new(ast::VarDef {
+                        name: field.name,
+                        init: field
+                            .default
+                            .as_ref()
+                            .map(|default| self.lower_expr(default)),
+                    }),
+                    field,
+                ),
             });
         }
 
@@ -213,6 +306,28 @@ impl Lowerer {
             return None;
         }
 
+        // Asserts go first, the superclass constructor has to be called last.
+        let mut initializers = asserts;
+        match strategy {
+            Strategy::Plain => {}
+            Strategy::StatefulWidget | Strategy::StatelessWidget => {
+                initializers.push(ast::ConstructorInitializer::Super(
+                    None,
+                    ast::Args {
+                        unnamed: vec![],
+                        named: vec![
+                            ast::NamedArg {
+                                comments: vec![],
+                                name: Symbol::intern("key"),
+                                expr: Node::new(ast::Expr::Identifier(Symbol::intern("key"))),
+                            },
+                        ],
+                        trailing_comments: vec![],
+                    },
+                ));
+            }
+        }
+
         let sig = ast::FnSig {
             return_type: Node::new(ast::Type::Infer),
             required: vec![],
@@ -226,29 +341,12 @@ impl Lowerer {
             method_qualifiers: vec![ast::MethodQualifiers::Const],
             name: None,
             sig,
-            initializers: match strategy {
-                Strategy::Plain => vec![],
-                Strategy::StatefulWidget | Strategy::StatelessWidget => vec![
-                    ast::ConstructorInitializer::Super(
-                        None,
-                        ast::Args {
-                            unnamed: vec![],
-                            named: vec![
-                                ast::NamedArg {
-                                    comments: vec![],
-                                    name: Symbol::intern("key"),
-                                    expr: Node::new(ast::Expr::Identifier(Symbol::intern("key"))),
-                                },
-                            ],
-                        },
-                    ),
-                ],
-            },
+            initializers,
             function_body: None,
         }))
     }
 
-    fn lower_field_def(&mut self, field: &FieldDef) -> Node<ast::ClassMember> {
+    fn lower_field_def(&mut self, field: &Node<FieldDef>) -> Node<ast::ClassMember> {
         let mut var_ty = Node::new(ast::Type::Infer);
         let mut var_expr = None;
         if let Some(ref ty) = field.ty {
@@ -257,8 +355,12 @@ impl Lowerer {
         if let Some(ref expr) = field.default {
             var_expr = Some(self.lower_expr(expr));
         }
-        Node::new(ast::ClassMember::Fields {
-            meta: vec![],
+        let mut meta = vec![];
+        if !field.comments.is_empty() {
+            meta.push(ast::MetaItem::Comments(field.comments.clone()));
+        }
+        derived(Node::new(ast::ClassMember::Fields {
+            meta,
             static_: false,
             var_type: ast::VarType {
                 fcv: if field.mutable {
@@ -269,55 +371,183 @@ impl Lowerer {
                 ty: var_ty,
             },
             initializers: vec![
-                Node::new(ast::VarDef {
-                    name: field.name,
-                    init: var_expr,
-                }),
+                derived(
+                    Node::new(ast::VarDef {
+                        name: field.name,
+                        init: var_expr,
+                    }),
+                    field,
+                ),
             ],
-        })
+        }), field)
     }
 
-    fn lower_config(&mut self, config: &Config) -> ast::NamedArg {
+    fn lower_config(
+        &mut self,
+        path: &Node<ast::Qualified>,
+        params: &BoundParams,
+        config: &Config,
+        args: &mut ast::Args,
+    ) {
         match *config {
-            Config::Field { name, ref value } => ast::NamedArg {
+            Config::Comments(ref comments, ref config) => match *config {
+                Some(ref config) => {
+                    let start = args.named.len();
+                    self.lower_config(path, params, config, args);
+                    if let Some(arg) = args.named.get_mut(start) {
+                        arg.comments = comments.iter().chain(&arg.comments).cloned().collect();
+                    }
+                }
+                // Trailing comments go before the closing parenthesis.
+                None => args.trailing_comments.extend(comments.iter().cloned()),
+            },
+            Config::Field { name, ref value } => args.named.push(ast::NamedArg {
                 comments: vec![],
                 name,
                 expr: self.lower_expr(value),
-            },
-            Config::EventHandler { name, ref block } => {
-                let mut field_name = String::from("on");
-                let name = name.as_str();
-                let mut name = name.chars();
-                field_name.extend(name.next().unwrap().to_uppercase());
-                field_name.extend(name);
-                ast::NamedArg {
+            }),
+            Config::EventHandler {
+                name: event_name,
+                ref sig,
+                ref body,
+                ..
+            } => {
+                let name = event_handler_name(event_name);
+                let sig = match *sig {
+                    Some(ref sig) => sig.fold(self),
+                    None => {
+                        let inferred = match bound_param(params, name) {
+                            Some(Some(ref param)) => callback_params(param),
+                            _ => None,
+                        };
+                        let params = match inferred {
+                            Some(params) => params,
+                            None => {
+                                println!(
+                                    "cannot infer the parameters of `{}` on `{}`: \
+                                     list them, as in `on {}(value) {{ ... }}`",
+                                    name,
+                                    path.name,
+                                    event_name
+                                );
+                                self.has_error = true;
+                                vec![]
+                            }
+                        };
+                        ast::FnSig {
+                            required: params
+                                .into_iter()
+                                .map(|param| {
+                                    ast::ArgDef::simple(Node::new(ast::Type::Infer), param)
+                                })
+                                .collect(),
+                            ..ast::FnSig::default()
+                        }
+                    }
+                };
+                args.named.push(ast::NamedArg {
+                    comments: vec![],
+                    name,
+                    expr: Node::new(ast::Expr::Closure(sig, body.fold(self))),
+                });
+            }
+            Config::Binding { name, ref field } => {
+                let field_name = match **field {
+                    ast::Expr::Identifier(field_name) => field_name,
+                    _ => unreachable!(),
+                };
+                match field.res().get() {
+                    Some(resolve::Res::Dsl(Res::Field(ref field_def))) if field_def.mutable => {}
+                    Some(resolve::Res::Dsl(Res::Field(_))) => {
+                        println!("cannot bind to `{}`: field is not `mut`", field_name);
+                        self.has_error = true;
+                    }
+                    _ => {
+                        println!("cannot bind to `{}`: not a component field", field_name);
+                        self.has_error = true;
+                    }
+                }
+
+                let on_changed = event_handler_name(Symbol::intern("changed"));
+                for &param in &[name, on_changed] {
+                    if let Some(None) = bound_param(params, param) {
+                        println!("`{}` has no `{}` parameter to bind", path.name, param);
+                        self.has_error = true;
+                    }
+                }
+
+                let value = Symbol::intern(if field_name == "value" {
+                    "newValue"
+                } else {
+                    "value"
+                });
+                let assign = Node::new(ast::Statement::Expression(Some(Node::new(
+                    ast::Expr::Binary(
+                        ast::BinOp::Assign(None),
+                        Node::new(ast::Expr::Identifier(field_name)),
+                        Node::new(ast::Expr::Identifier(value)),
+                    ),
+                ))));
+                args.named.push(ast::NamedArg {
+                    comments: vec![],
+                    name,
+                    expr: Node::new(ast::Expr::Identifier(field_name)),
+                });
+                args.named.push(ast::NamedArg {
                     comments: vec![],
-                    name: Symbol::intern(&field_name),
+                    name: on_changed,
                     expr: Node::new(ast::Expr::Closure(
-                        ast::FnSig::default(),
-                        ast::FnBody::Block(block.clone()),
+                        ast::FnSig {
+                            required: vec![ast::ArgDef::simple(Node::new(ast::Type::Infer), value)],
+                            ..ast::FnSig::default()
+                        },
+                        ast::FnBody::Block(Node::new(ast::Statement::Block(vec![
+                            set_state(vec![assign]),
+                        ]))),
                     )),
-                }
+                });
             }
         }
     }
 
-    fn lower_expr(&mut self, expr: &Expr) -> Node<ast::Expr> {
-        match *expr {
+    fn lower_expr(&mut self, expr: &Node<Expr>) -> Node<ast::Expr> {
+        let lowered = match **expr {
+            Expr::Comments(ref comments, ref expr) => {
+                Node::new(ast::Expr::Comments(comments.clone(), self.lower_expr(expr)))
+            }
             Expr::Instance {
                 ref path,
                 ref unnamed,
                 ref config,
             } => {
-                let unnamed = unnamed.iter().map(|expr| self.lower_expr(expr)).collect();
-                let named = config
-                    .iter()
-                    .map(|config| self.lower_config(config))
-                    .collect();
+                let binding = expr.binding().get();
+                if let Some(ref binding) = binding {
+                    for missing in &binding.missing {
+                        if let resolve::Res::Dsl(Res::Field(ref field)) = *missing {
+                            println!("`{}` is missing required field `{}`", path.name, field.name);
+                            self.has_error = true;
+                        }
+                    }
+                }
+                let params: BoundParams = binding.map(|binding| {
+                    let mut names = vec![];
+                    for config in config {
+                        config_names(config, &mut names);
+                    }
+                    names.into_iter().zip(binding.named).collect()
+                });
+                let mut args = ast::Args {
+                    unnamed: unnamed.iter().map(|expr| self.lower_expr(expr)).collect(),
+                    named: vec![],
+                    trailing_comments: vec![],
+                };
+                for config in config {
+                    self.lower_config(path, &params, config, &mut args);
+                }
                 Node::new(ast::Expr::New {
                     const_: false,
                     path: path.clone(),
-                    args: ast::Args { unnamed, named },
+                    args,
                 })
             }
             Expr::Array(ref exprs) => {
@@ -328,8 +558,15 @@ impl Lowerer {
                     elements,
                 })
             }
-            Expr::Dart(ref dart) => dart.clone(),
-        }
+            Expr::Dart(ref dart) => {
+                return if self.in_state_class {
+                    dart.fold(self)
+                } else {
+                    dart.clone()
+                };
+            }
+        };
+        derived(lowered, expr)
     }
 
     fn lower_type(&mut self, ty: &Type) -> Node<ast::Type> {
@@ -339,8 +576,62 @@ impl Lowerer {
     }
 }
 
+impl Lowerer {
+    /// Folds `statement` without wrapping it in `setState` itself.
+    fn lower_statement(&mut self, statement: &Node<ast::Statement>) -> Node<ast::Statement> {
+        match **statement {
+            ast::Statement::Block(ref statements) => {
+                Node::new(ast::Statement::Block(self.lower_block(statements)))
+            }
+            _ => statement.super_fold(self),
+        }
+    }
+
+    /// Folds `statements`, wrapping each run of them that mutates `mut`
+    /// fields in a single `setState` call.
+    fn lower_block(&mut self, statements: &[Node<ast::Statement>]) -> Vec<Node<ast::Statement>> {
+        let mut lowered = vec![];
+        let mut mutations = vec![];
+        for statement in statements {
+            let needs_set_state = mem::replace(&mut self.needs_set_state, false);
+            let folded = keep_span(statement, self.lower_statement(statement));
+            if mem::replace(&mut self.needs_set_state, needs_set_state) {
+                mutations.push(folded);
+            } else {
+                if !mutations.is_empty() {
+                    lowered.push(set_state(mem::replace(&mut mutations, vec![])));
+                }
+                lowered.push(folded);
+            }
+        }
+        if !mutations.is_empty() {
+            lowered.push(set_state(mutations));
+        }
+        lowered
+    }
+}
+
 impl Folder for Lowerer {
     fn dart_expr(&mut self, expr: Node<ast::Expr>) -> Node<ast::Expr> {
+        if self.in_state_class {
+            if let ast::Expr::Identifier(name) = *expr {
+                if let Some(resolve::Res::Dsl(Res::Field(ref field_def))) = expr.res().get() {
+                    if !field_def.mutable {
+                        let widget = Node::new(ast::Expr::Identifier(Symbol::intern("widget")));
+                        return derived(
+                            Node::new(ast::Expr::Suffix(widget, ast::Suffix::Field(name))),
+                            &expr,
+                        );
+                    }
+                }
+            }
+        }
+        if is_set_state_call(&expr) {
+            let in_set_state = mem::replace(&mut self.in_set_state, true);
+            let expr_folded = expr.super_fold(self);
+            self.in_set_state = in_set_state;
+            return expr_folded;
+        }
         let expr_folded = expr.super_fold(self);
         match *expr {
             ast::Expr::Binary(ast::BinOp::Assign(_), ref left, _) |
@@ -352,7 +643,7 @@ impl Folder for Lowerer {
                     if let Some(resolve::Res::Dsl(res)) = left.res().get() {
                         if let Res::Field(ref field_def) = res {
                             let FieldDef { mutable, .. } = **field_def;
-                            if mutable {
+                            if mutable && !self.in_set_state {
                                 self.needs_set_state = true;
                             }
                         }
@@ -364,30 +655,161 @@ impl Folder for Lowerer {
         expr_folded
     }
     fn dart_statement(&mut self, statement: Node<ast::Statement>) -> Node<ast::Statement> {
-        let statement = statement.super_fold(self);
-        if self.needs_set_state {
-            self.needs_set_state = false;
-            Node::new(ast::Statement::Expression(
-                Some(Node::new(ast::Expr::Suffix(
-                    Node::new(ast::Expr::Identifier(Symbol::intern("setState"))),
-                    ast::Suffix::Call(
-                        vec![],
-                        ast::Args {
-                            unnamed: vec![
-                                Node::new(ast::Expr::Closure(
-                                    ast::FnSig::default(),
-                                    ast::FnBody::Block(
-                                        Node::new(ast::Statement::Block(vec![statement])),
-                                    ),
-                                )),
-                            ],
-                            named: vec![],
-                        },
-                    ),
-                ))),
-            ))
+        let needs_set_state = mem::replace(&mut self.needs_set_state, false);
+        let statement = self.lower_statement(&statement);
+        if mem::replace(&mut self.needs_set_state, needs_set_state) {
+            set_state(vec![statement])
         } else {
             statement
         }
     }
+    fn dart_fn_body(&mut self, fn_body: &ast::FnBody) -> ast::FnBody {
+        let needs_set_state = mem::replace(&mut self.needs_set_state, false);
+        let fn_body = fn_body.super_fold(self);
+        if !mem::replace(&mut self.needs_set_state, needs_set_state) {
+            return fn_body;
+        }
+        // Arrow bodies have no statement to wrap, so they become blocks.
+        match fn_body {
+            ast::FnBody::Arrow(expr) => ast::FnBody::Block(Node::new(ast::Statement::Block(vec![
+                set_state(vec![Node::new(ast::Statement::Expression(Some(expr)))]),
+            ]))),
+            fn_body => fn_body,
+        }
+    }
+}
+
+/// Returns true if `expr` is a `setState(...)` call.
+fn is_set_state_call(expr: &ast::Expr) -> bool {
+    if let ast::Expr::Suffix(ref callee, ast::Suffix::Call(..)) = *expr {
+        if let ast::Expr::Identifier(name) = **callee {
+            return name == "setState";
+        }
+    }
+    false
+}
+
+/// Marks the nodes generated for a component as derived from the span
+/// of the closest enclosing node that has one.
+struct SpanInheritor {
+    span: Option<Span>,
+}
+
+impl Visitor for SpanInheritor {
+    fn visit_node<T: VisitNode>(&mut self, node: Node<T>) {
+        let span = self.span;
+        match node.origin_span() {
+            Some(span) => self.span = Some(span),
+            None => if let Some(span) = span {
+                node.derived_from().set(span);
+            },
+        }
+        VisitNode::visit(node, self);
+        self.span = span;
+    }
+}
+
+/// Marks `node` as generated from the source of `origin`.
+fn derived<T: 'static, U: 'static>(node: Node<T>, origin: &Node<U>) -> Node<T> {
+    if let Some(span) = origin.origin_span() {
+        node.derived_from().set(span);
+    }
+    node
+}
+
+/// Wraps `statements` in `setState(() { statements });`.
+fn set_state(statements: Vec<Node<ast::Statement>>) -> Node<ast::Statement> {
+    Node::new(ast::Statement::Expression(Some(Node::new(ast::Expr::Suffix(
+        Node::new(ast::Expr::Identifier(Symbol::intern("setState"))),
+        ast::Suffix::Call(
+            vec![],
+            ast::Args {
+                unnamed: vec![
+                    Node::new(ast::Expr::Closure(
+                        ast::FnSig::default(),
+                        ast::FnBody::Block(Node::new(ast::Statement::Block(statements))),
+                    )),
+                ],
+                named: vec![],
+                trailing_comments: vec![],
+            },
+        ),
+    )))))
+}
+
+/// Collects the names of the constructor parameters `config` passes.
+pub fn config_names(config: &Config, names: &mut Vec<Symbol>) {
+    match *config {
+        Config::Comments(_, ref config) => if let Some(ref config) = *config {
+            config_names(config, names);
+        },
+        Config::Field { name, .. } => names.push(name),
+        Config::EventHandler { name, .. } => names.push(event_handler_name(name)),
+        Config::Binding { name, .. } => {
+            names.push(name);
+            names.push(event_handler_name(Symbol::intern("changed")));
+        }
+    }
+}
+
+/// Turns an event name into the name of its handler parameter (`pressed` -> `onPressed`).
+fn event_handler_name(name: Symbol) -> Symbol {
+    let mut handler_name = String::from("on");
+    let name = name.as_str();
+    let mut name = name.chars();
+    handler_name.extend(name.next().unwrap().to_uppercase());
+    handler_name.extend(name);
+    Symbol::intern(&handler_name)
+}
+
+/// The parameters the named arguments of an instance bind to, by name, if
+/// what it creates is known.
+type BoundParams = Option<Vec<(Symbol, Option<resolve::Res>)>>;
+
+/// Returns the parameter the argument named `name` binds to among `params`:
+/// `None` if that isn't known, `Some(None)` if there's no such parameter.
+fn bound_param(params: &BoundParams, name: Symbol) -> Option<Option<resolve::Res>> {
+    let params = params.as_ref()?;
+    let param = params.iter().find(|&&(param, _)| param == name);
+    Some(param.and_then(|&(_, ref res)| res.clone()))
+}
+
+/// Returns the parameter names of the callback `param` takes, `param` being
+/// the parameter an event handler binds to.
+fn callback_params(param: &resolve::Res) -> Option<Vec<Symbol>> {
+    let ty = match *param {
+        resolve::Res::Var(ref var) => resolve::declared_type(var)?,
+        resolve::Res::Dsl(Res::Field(ref field)) => match **field.ty.as_ref()? {
+            Type::Dart(ref ty) => ty.clone(),
+        },
+        _ => return None,
+    };
+    fn_type_params(&ty)
+}
+
+/// Returns the parameter names of the function type `ty`, looking through
+/// a type alias (`typedef void ValueChanged<T>(T value);`).
+fn fn_type_params(ty: &Node<ast::Type>) -> Option<Vec<Symbol>> {
+    match *resolve::function_type(ty)? {
+        ast::Type::FunctionOld(ref sig) => {
+            Some(sig.required.iter().map(|arg| arg.var.name).collect())
+        }
+        ast::Type::Function(ref sig) => {
+            // Parameters of `Function` types may be only types, which parse
+            // as names, so those are named after their position.
+            let n = sig.required.len();
+            Some(
+                sig.required
+                    .iter()
+                    .enumerate()
+                    .map(|(i, arg)| match (&arg.ty.fcv, &*arg.ty.ty) {
+                        (&None, &ast::Type::Infer) if n == 1 => Symbol::intern("value"),
+                        (&None, &ast::Type::Infer) => Symbol::intern(&format!("value{}", i + 1)),
+                        _ => arg.var.name,
+                    })
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
 }
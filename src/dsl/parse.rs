@@ -1,16 +1,26 @@
 #![allow(unused_doc_comments)]
 
 use dart;
+use dart::ast::Parenter;
 use dsl::ast::*;
+use dsl::visit::Visit;
 use dart::parse::{ParseResult, Parser};
 use node::Node;
 
 impl<'a> Parser<'a> {
     fn dsl_type(&mut self) -> ParseResult<Node<Type>> {
-        Ok(Node::new(Type::Dart(self.dart_type()?)))
+        self.spanned(|p| Ok(Node::new(Type::Dart(p.dart_type()?))))
     }
 
     fn dsl_expr(&mut self) -> ParseResult<Node<Expr>> {
+        self.memoize("dsl_expr", |p| p.spanned(|p| p.dsl_expr_unspanned()))
+    }
+
+    fn dsl_expr_unspanned(&mut self) -> ParseResult<Node<Expr>> {
+        if !self.cur_comments.is_empty() {
+            let comments = self.cur_comments.drain(..).collect();
+            return Ok(Node::new(Expr::Comments(comments, self.dsl_expr()?)));
+        }
         if self.probe(|p| {
             p.parse_ident().is_ok() && (p.eat_punctuation('(') || p.eat_punctuation('{'))
         }) {
@@ -31,11 +41,13 @@ impl<'a> Parser<'a> {
             let mut config = vec![];
             if has_config {
                 while !self.out_of_tokens() {
-                    if self.is_punctuation('}') {
+                    if self.is_punctuation('}') && self.cur_comments.is_empty() {
                         break;
                     }
                     config.push(self.dsl_config()?);
-                    if !self.eat_punctuation(',') {
+                    if !self.eat_punctuation(',') &&
+                        !(self.is_punctuation('}') && !self.cur_comments.is_empty())
+                    {
                         break;
                     }
                 }
@@ -59,18 +71,45 @@ impl<'a> Parser<'a> {
             self.expect_punctuation(']')?;
             return Ok(Node::new(Expr::Array(exprs)));
         }
-        Ok(Node::new(Expr::Dart(self.dart_expr()?)))
+        Ok(Node::new(Expr::Dart(self.spanned(|p| p.dart_expr())?)))
     }
 
     fn dsl_config(&mut self) -> ParseResult<Config> {
+        if !self.cur_comments.is_empty() {
+            let comments = self.cur_comments.drain(..).collect();
+            let config = if self.is_punctuation('}') {
+                None
+            } else {
+                Some(Box::new(self.dsl_config()?))
+            };
+            return Ok(Config::Comments(comments, config));
+        }
         if self.eat_keyword("on") {
+            let name = self.parse_ident()?;
+            let name_span = self.prev_span();
+            let sig = if self.is_punctuation('(') {
+                Some(self.dart_fn_args(Node::new(dart::ast::Type::Infer))?)
+            } else {
+                None
+            };
             Ok(Config::EventHandler {
-                name: self.parse_ident()?,
-                block: self.dart_block()?,
+                name,
+                name_span: Some(name_span),
+                sig,
+                body: self.dart_fn_body(false)?,
             })
         } else {
             let name = self.parse_ident()?;
             self.expect_punctuation(':')?;
+            if self.probe(|p| p.eat_keyword("bind") && p.parse_ident().is_ok()) {
+                self.expect_keyword("bind")?;
+                return Ok(Config::Binding {
+                    name,
+                    field: self.spanned(|p| {
+                        Ok(Node::new(dart::ast::Expr::Identifier(p.parse_ident()?)))
+                    })?,
+                });
+            }
             Ok(Config::Field {
                 name,
                 value: self.dsl_expr()?,
@@ -79,9 +118,15 @@ impl<'a> Parser<'a> {
     }
 
     fn dsl_field_def(&mut self) -> ParseResult<Node<FieldDef>> {
+        self.memoize("dsl_field_def", |p| p.spanned(|p| p.dsl_field_def_unspanned()))
+    }
+
+    fn dsl_field_def_unspanned(&mut self) -> ParseResult<Node<FieldDef>> {
+        let comments = self.cur_comments.drain(..).collect();
         let mutable = self.eat_keyword("mut");
         let name = self.parse_ident()?;
         let mut fd = FieldDef {
+            comments,
             mutable,
             name,
             ty: None,
@@ -109,12 +154,20 @@ impl<'a> Parser<'a> {
     }
 
     fn dsl_item(&mut self) -> ParseResult<Node<Item>> {
-        if self.eat_keyword("def") {
+        self.spanned(|p| p.dsl_item_unspanned())
+    }
+
+    fn dsl_item_unspanned(&mut self) -> ParseResult<Node<Item>> {
+        if self.is_keyword("def") {
+            let comments = self.cur_comments.drain(..).collect();
+            self.expect_keyword("def")?;
             let name = self.parse_ident()?;
             self.expect_punctuation('{')?;
             let fields = self.dsl_field_defs()?;
             let mut dart_members = vec![];
-            while let Some(dart_member) = self.try(|p| p.dart_class_member(name)) {
+            while let Some(dart_member) =
+                self.try(|p| p.spanned(|p| p.dart_class_member(name)))
+            {
                 dart_members.push(dart_member);
             }
             let body = if self.eat_punctuation2('.', '.') {
@@ -125,13 +178,14 @@ impl<'a> Parser<'a> {
 
             self.expect_punctuation('}')?;
             return Ok(Node::new(Item::ComponentDef {
+                comments,
                 name,
                 fields,
                 dart_members,
                 body,
             }));
         }
-        Ok(Node::new(Item::Dart(self.dart_item()?)))
+        Ok(Node::new(Item::Dart(self.spanned(|p| p.dart_item())?)))
     }
 
     pub fn dsl_items(&mut self) -> ParseResult<Vec<Node<Item>>> {
@@ -141,4 +195,14 @@ impl<'a> Parser<'a> {
         }
         Ok(items)
     }
+
+    /// Parses a whole `.lyk` file, with its nodes linked to their parents.
+    pub fn dsl_module(mut self) -> ParseResult<Node<Module>> {
+        let module = Node::new(Module {
+            path: self.path().to_path_buf(),
+            items: self.dsl_items()?,
+        });
+        module.items.super_visit(&mut Parenter::new(&module));
+        Ok(module)
+    }
 }
@@ -1,6 +1,8 @@
 use dsl::ast::*;
+use dart::lex::Token;
 use dart::print::{BoxKind, Printer};
 use node::Node;
+use Span;
 
 impl Printer {
     pub fn dsl_items(mut self, items: &[Node<Item>]) -> String {
@@ -10,15 +12,25 @@ impl Printer {
         self.pretty_print()
     }
 
+    fn dsl_comments(&mut self, comments: &[Span]) {
+        for &comment in comments {
+            self.enter_block();
+            self.print_token(Token::Comment(comment));
+            self.exit();
+        }
+    }
+
     fn dsl_item(&mut self, item: &Item) {
         self.enter_block();
         match *item {
             Item::ComponentDef {
+                ref comments,
                 name,
                 ref fields,
                 ref dart_members,
                 ref body,
             } => {
+                self.dsl_comments(comments);
                 self.print_str("def ");
                 self.print_ident(name);
                 self.print_str(" {");
@@ -46,6 +58,7 @@ impl Printer {
     }
 
     fn dsl_field_def(&mut self, field: &FieldDef) {
+        self.dsl_comments(&field.comments);
         if field.mutable {
             self.print_str("mut ");
         }
@@ -62,16 +75,34 @@ impl Printer {
 
     fn dsl_config(&mut self, config: &Config) {
         match *config {
+            Config::Comments(ref comments, ref config) => {
+                self.dsl_comments(comments);
+                if let Some(ref config) = *config {
+                    self.dsl_config(config);
+                }
+            }
             Config::Field { name, ref value } => {
                 self.print_ident(name);
                 self.print_str(": ");
                 self.dsl_expr(value);
             }
-            Config::EventHandler { name, ref block } => {
+            Config::EventHandler {
+                name,
+                ref sig,
+                ref body,
+                ..
+            } => {
                 self.print_str("on ");
                 self.print_ident(name);
-                self.print_str(" ");
-                self.dart_statement(block);
+                if let Some(ref sig) = *sig {
+                    self.dart_fn_args(sig);
+                }
+                self.dart_function_body(body, false);
+            }
+            Config::Binding { name, ref field } => {
+                self.print_ident(name);
+                self.print_str(": bind ");
+                self.dart_expr(field);
             }
         }
     }
@@ -84,6 +115,10 @@ impl Printer {
 
     fn dsl_expr(&mut self, expr: &Expr) {
         match *expr {
+            Expr::Comments(ref comments, ref expr) => {
+                self.dsl_comments(comments);
+                self.dsl_expr(expr);
+            }
             Expr::Instance {
                 ref path,
                 ref unnamed,
@@ -1,9 +1,11 @@
 use dart;
-use dart::resolve::{Collector, Resolver, TopLevelResolver};
+use dart::resolve::{CallBinding, Collector, Param, Resolver, TopLevelResolver};
 use dart::visit::Visit as DartVisit;
-use dsl::ast::{FieldDef, Item};
+use dsl::ast::{Config, Expr, FieldDef, Item};
+use dsl::lower::{config_names, Strategy};
 use dsl::visit::{Visit, Visitor};
 use node::Node;
+use syntax::symbol::Symbol;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Res {
@@ -11,20 +13,53 @@ pub enum Res {
     Field(Node<FieldDef>),
 }
 
-pub fn resolve(items: &[Node<Item>], fully_resolve: bool) {
+/// Resolves the names in `items`, like `dart::resolve::resolve` does.
+/// Returns false if any name was unknown or ambiguous.
+pub fn resolve(items: &[Node<Item>], fully_resolve: bool) -> bool {
     let collector = &mut Collector::new();
 
     collector.import(None, "dart:core", &[], None);
 
     items.super_visit(collector);
     if collector.has_error {
-        return;
+        return false;
     }
 
     items.super_visit(&mut TopLevelResolver { collector });
     if fully_resolve {
         items.super_visit(&mut Resolver { collector });
     }
+    !collector.has_error
+}
+
+/// Binds arguments to the fields of `component` that become parameters of
+/// the class it lowers to, like `dart::resolve::bind_call`. Components only
+/// take named arguments.
+pub fn bind_component(
+    component: &Node<Item>,
+    unnamed: usize,
+    named: &[Symbol],
+) -> Option<CallBinding> {
+    let (fields, body) = match **component {
+        Item::ComponentDef {
+            ref fields,
+            ref body,
+            ..
+        } => (fields, body),
+        Item::Dart(_) => return None,
+    };
+    let strategy = Strategy::of(fields, body);
+    let params: Vec<_> = fields
+        .iter()
+        .filter(|field| strategy.takes_param(field))
+        .map(|field| Param {
+            name: field.name,
+            res: dart::resolve::Res::Dsl(Res::Field(field.clone())),
+            required: field.default.is_none(),
+        })
+        .collect();
+    let callee = dart::resolve::Res::Dsl(Res::Component(component.clone()));
+    Some(CallBinding::bind(callee, &[], &params, unnamed, named))
 }
 
 impl Collector {
@@ -60,4 +95,31 @@ impl<'a> Visitor for Resolver<'a> {
             item.super_visit(this);
         });
     }
+    fn dsl_config(&mut self, config: &Config) {
+        if let Config::EventHandler { .. } = *config {
+            self.in_lexical_scope(|this| config.super_visit(this));
+        } else {
+            config.super_visit(self);
+        }
+    }
+    fn dsl_expr(&mut self, expr: Node<Expr>) {
+        expr.super_visit(self);
+        if let Expr::Instance {
+            ref path,
+            ref unnamed,
+            ref config,
+        } = *expr
+        {
+            let mut named = vec![];
+            for config in config {
+                config_names(config, &mut named);
+            }
+            let binding = path.res()
+                .get()
+                .and_then(|res| dart::resolve::bind_call(res, unnamed.len(), &named));
+            if let Some(binding) = binding {
+                expr.binding().set(binding);
+            }
+        }
+    }
 }
@@ -1,4 +1,5 @@
 use dart;
+use dart::ast::Parenter;
 use dart::visit::Visit as DartVisit;
 use dsl::ast::{Config, Expr, FieldDef, Item, Type};
 use node::Node;
@@ -24,6 +25,21 @@ pub trait Visitor: dart::visit::Visitor {
     }
 }
 
+impl Visitor for Parenter {
+    fn dsl_item(&mut self, item: Node<Item>) {
+        self.link(item.clone(), |this| item.super_visit(this));
+    }
+    fn dsl_field_def(&mut self, field_def: Node<FieldDef>) {
+        self.link(field_def.clone(), |this| field_def.super_visit(this));
+    }
+    fn dsl_type(&mut self, ty: Node<Type>) {
+        self.link(ty.clone(), |this| ty.super_visit(this));
+    }
+    fn dsl_expr(&mut self, expr: Node<Expr>) {
+        self.link(expr.clone(), |this| expr.super_visit(this));
+    }
+}
+
 pub trait Visit {
     fn visit<V: Visitor>(&self, visitor: &mut V);
     fn super_visit<V: Visitor>(&self, visitor: &mut V);
@@ -89,11 +105,22 @@ impl Visit for Config {
     }
     fn super_visit<V: Visitor>(&self, visitor: &mut V) {
         match *self {
+            Config::Comments(_, ref config) => if let Some(ref config) = *config {
+                config.visit(visitor);
+            },
             Config::Field { ref value, .. } => {
                 value.visit(visitor);
             }
-            Config::EventHandler { ref block, .. } => {
-                block.visit(visitor);
+            Config::EventHandler {
+                ref sig, ref body, ..
+            } => {
+                if let Some(ref sig) = *sig {
+                    sig.visit(visitor);
+                }
+                body.visit(visitor);
+            }
+            Config::Binding { ref field, .. } => {
+                field.visit(visitor);
             }
         }
     }
@@ -118,6 +145,9 @@ impl Visit for Node<Expr> {
     }
     fn super_visit<V: Visitor>(&self, visitor: &mut V) {
         match **self {
+            Expr::Comments(_, ref expr) => {
+                expr.visit(visitor);
+            }
             Expr::Instance {
                 ref path,
                 ref unnamed,
@@ -21,18 +21,22 @@ pub mod node;
 
 pub mod dart {
     pub mod ast;
+    pub mod equiv;
     pub mod fold;
     pub mod lex;
     pub mod parse;
     pub mod print;
     pub mod resolve;
     pub mod sdk;
+    pub mod source_map;
+    pub mod summary;
     pub mod visit;
 }
 
 pub mod dsl {
     pub mod ast;
     pub mod fold;
+    pub mod highlight;
     pub mod lift;
     pub mod lower;
     pub mod parse;
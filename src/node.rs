@@ -7,6 +7,13 @@ use std::marker::Unsize;
 use std::ops::{CoerceUnsized, Deref};
 use std::rc::{Rc, Weak};
 
+/// A shared AST node, compared and hashed by identity.
+///
+/// Nodes, their `node_field!` side tables and the `Module::load` cache are
+/// confined to the thread that created them. Sharing them across threads
+/// would take more than an `Arc` here: the `Symbol`s and spans inside every
+/// AST refer to libsyntax's per-thread interner and `CodeMap`, so `Symbol`
+/// is neither `Send` nor `Sync`, and no side table could be made `Sync`.
 pub struct Node<T: ?Sized> {
     ptr: Rc<T>,
 }
@@ -1,10 +1,15 @@
+extern crate lyken;
 extern crate walkdir;
 
+use lyken::dart::lex::Lexer;
+use lyken::dart::parse::Parser;
 use walkdir::WalkDir;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
 use std::process::{Command, Stdio};
 
-#[test]
-fn all_dsl() {
+fn build_examples() {
     let status = Command::new("cargo")
         .args(&["build", "--examples", "--release"])
         .stdout(Stdio::null())
@@ -13,14 +18,23 @@ fn all_dsl() {
     if !status.success() {
         std::process::exit(1);
     }
+}
+
+#[test]
+fn all_dsl() {
+    build_examples();
 
     for entry in WalkDir::new("tests") {
         let entry = entry.unwrap();
+        if entry.path().starts_with("tests/dsl/errors") {
+            continue;
+        }
         if entry.path().extension().map_or(false, |x| x == "lyk") {
-            let status = Command::new("target/release/examples/dsl-lower")
-                .arg(entry.path())
-                .status()
-                .unwrap();
+            let mut command = Command::new("target/release/examples/dsl-lower");
+            for target in expected_lines(entry.path(), "// target: ") {
+                command.arg("--target").arg(target);
+            }
+            let status = command.arg(entry.path()).status().unwrap();
             if !status.success() {
                 std::process::exit(1);
             }
@@ -33,4 +47,127 @@ fn all_dsl() {
     if !status.success() {
         std::process::exit(1);
     }
+    let status = Command::new("target/release/examples/dart-print")
+        .arg("tests")
+        .status()
+        .unwrap();
+    if !status.success() {
+        std::process::exit(1);
+    }
+}
+
+/// Lowers each module in `tests/dsl/errors`, which has to fail and print
+/// every message listed in the module's `// error: ` comments.
+#[test]
+fn dsl_errors() {
+    build_examples();
+
+    for entry in WalkDir::new("tests/dsl/errors") {
+        let entry = entry.unwrap();
+        if entry.path().extension().map_or(false, |x| x == "lyk") {
+            let output = Command::new("target/release/examples/dsl-lower")
+                .arg(entry.path())
+                .output()
+                .unwrap();
+            assert!(
+                !output.status.success(),
+                "{}: lowered without errors",
+                entry.path().display()
+            );
+            check_output(entry.path(), "// error: ", &output.stdout);
+        }
+    }
+}
+
+/// Resolves each module in `tests/dsl` with `// resolves: ` comments, which
+/// list what `dsl-resolve` has to print for it, e.g. what a member access
+/// resolved to. A `// target: ` comment selects the platform to resolve for.
+#[test]
+fn dsl_resolutions() {
+    build_examples();
+
+    for entry in WalkDir::new("tests/dsl") {
+        let entry = entry.unwrap();
+        // `.dart` files next to `.lyk` ones are what `all_dsl` lowered them to.
+        let module = match entry.path().extension() {
+            Some(x) if x == "lyk" => true,
+            Some(x) if x == "dart" => !entry.path().with_extension("lyk").exists(),
+            _ => false,
+        };
+        if module && !expected_lines(entry.path(), "// resolves: ").is_empty() {
+            let mut command = Command::new("target/release/examples/dsl-resolve");
+            for target in expected_lines(entry.path(), "// target: ") {
+                command.arg("--target").arg(target);
+            }
+            let output = command.arg(entry.path()).output().unwrap();
+            check_output(entry.path(), "// resolves: ", &output.stdout);
+        }
+    }
+}
+
+/// Parses components nested 32 and 128 levels deep, which has to take
+/// about as many token bumps per token for both: backtracking out of a
+/// nested component and parsing it again would make the deeper one worse.
+#[test]
+fn nesting_is_linear() {
+    lyken::with_globals(|| {
+        let bumps_per_token = |depth| {
+            let tokens = Lexer::from_string(nested_component(depth)).tokenize().unwrap();
+            let mut parser = Parser::new(Path::new("nested.lyk"), &tokens);
+            parser.dsl_items().unwrap();
+            parser.bumps() as f64 / tokens.len() as f64
+        };
+        let (shallow, deep) = (bumps_per_token(32), bumps_per_token(128));
+        assert!(
+            deep < shallow * 1.5,
+            "{:.1} bumps per token at depth 32, {:.1} at depth 128",
+            shallow,
+            deep
+        );
+    });
+}
+
+/// Returns a component with a body nested `depth` levels deep, alternating
+/// between the kinds of nesting the parser has to backtrack over.
+fn nested_component(depth: usize) -> String {
+    let mut body = "Text('leaf')".to_string();
+    for i in 0..depth {
+        body = match i % 3 {
+            0 => format!("Padding {{ child: {} }}", body),
+            1 => format!("Column {{ children: [Text('{}'), {}] }}", i, body),
+            _ => format!("Opacity(opacity) {{ child: {} }}", body),
+        };
+    }
+    format!("def Nested {{\n  opacity: int = 1,\n\n  ..{}\n}}\n", body)
+}
+
+/// Returns the rest of each line of the file at `path` starting with
+/// `prefix`.
+fn expected_lines(path: &Path, prefix: &str) -> Vec<String> {
+    let mut source = String::new();
+    File::open(path)
+        .unwrap()
+        .read_to_string(&mut source)
+        .unwrap();
+    source
+        .lines()
+        .map(|line| line.trim_left())
+        .filter(|line| line.starts_with(prefix))
+        .map(|line| line[prefix.len()..].to_string())
+        .collect()
+}
+
+/// Checks that `stdout` has every line `expected_lines` finds in the file
+/// at `path`.
+fn check_output(path: &Path, prefix: &str, stdout: &[u8]) {
+    let stdout = String::from_utf8_lossy(stdout);
+    for expected in expected_lines(path, prefix) {
+        assert!(
+            stdout.lines().any(|line| line == expected),
+            "{}: expected `{}`, got:\n{}",
+            path.display(),
+            expected,
+            stdout
+        );
+    }
 }
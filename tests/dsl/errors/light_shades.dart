@@ -0,0 +1 @@
+const String shade = 'light';
@@ -0,0 +1,27 @@
+import 'package:flutter/material.dart';
+
+// Arguments bind to parameters by position or by name, and parameters no
+// argument binds to are missing if they're required.
+// resolves: `scale(2)` binds #0 -> times
+// resolves: `scale(2, 3.0, 4)` binds #0 -> times, #1 -> factor, #2 -> extra
+// resolves: `scale()` binds missing times
+// resolves: `new Badge(label: 'New', count: 1)` binds label -> label, count -> count
+// resolves: `new Badge(label: 'New', size: 2.0)` binds label -> label, size -> extra, missing count
+
+double scale(int times, [double factor = 1.0]) => times * factor;
+
+class Badge {
+  Badge({@required this.label, @required this.count, this.color});
+
+  final String label;
+  final int count;
+  final Color color;
+}
+
+void main() {
+  print(scale(2));
+  print(scale(2, 3.0, 4));
+  print(scale());
+  print(new Badge(label: 'New', count: 1));
+  print(new Badge(label: 'New', size: 2.0));
+}
@@ -0,0 +1,45 @@
+import 'package:flutter/material.dart';
+
+// Members resolve by the static type of their receiver, with the type
+// arguments of generic supertypes (`State<T>.widget`, `List<E>[]`) bound.
+// resolves: `widget.title` -> ChoicePage.title
+// resolves: `choices[0].icon` -> Choice.icon
+// resolves: `Theme.of(context).textTheme` -> ThemeData.textTheme
+
+// Cyclic initializers leave the type unknown instead of never finishing.
+// resolves: `first.length` -> unknown
+
+class Choice {
+  const Choice({this.title, this.icon});
+
+  final String title;
+  final IconData icon;
+}
+
+const List<Choice> choices = const <Choice>[
+  const Choice(title: 'Car', icon: Icons.directions_car),
+  const Choice(title: 'Bicycle', icon: Icons.directions_bike),
+];
+
+class ChoicePage extends StatefulWidget {
+  ChoicePage({Key key, this.title}) : super(key: key);
+
+  final String title;
+
+  @override
+  _ChoicePageState createState() => new _ChoicePageState();
+}
+
+class _ChoicePageState extends State<ChoicePage> {
+  @override
+  Widget build(BuildContext context) {
+    return new ListTile(
+      leading: new Icon(choices[0].icon),
+      title: new Text(widget.title, style: Theme.of(context).textTheme.title),
+    );
+  }
+}
+
+var first = second;
+var second = first;
+var firstLength = first.length;
@@ -0,0 +1,10 @@
+import 'package:flutter/material.dart';
+
+const Color accent = Colors.deepOrange;
+
+class Swatch {
+  final String label;
+  final Color color;
+
+  const Swatch(this.label, this.color);
+}
@@ -0,0 +1,8 @@
+part of swatches;
+
+// A part resolves in the scope of the library it belongs to, found by its
+// name in the directories above.
+// resolves: `base.withOpacity` -> Color.withOpacity
+// resolves: `Colors.amber` -> Colors.amber
+
+final List<Color> swatchList = <Color>[base, base.withOpacity(0.5), Colors.amber];
@@ -0,0 +1,7 @@
+library swatches;
+
+import 'package:flutter/material.dart';
+
+part 'src/swatch_list.dart';
+
+const Color base = Colors.teal;
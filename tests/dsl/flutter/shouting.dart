@@ -0,0 +1,4 @@
+/// Extensions that apply even when imported with a prefix.
+extension Shouted on Pattern {
+  String get shouted => toString().toUpperCase() + '!';
+}